@@ -3,7 +3,11 @@ use std::fs::create_dir_all;
 
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
-use mycosmwasm::msg::{GetPollResponse, ExecuteMsg, InstantiateMsg, QueryMsg};
+use mycosmwasm::msg::{
+    ExecuteMsg, GetCreditsResponse, GetPollResponse, GetRoleResponse, GetStatsResponse,
+    GetTallyResponse, GetVoteResponse, InstantiateMsg, IsEligibleResponse, ListPollsResponse,
+    ListVotersResponse, QueryMsg, SudoMsg,
+};
 use mycosmwasm::state::Config;
 
 fn main() {
@@ -15,6 +19,15 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(SudoMsg), &out_dir);
     export_schema(&schema_for!(Config), &out_dir);
     export_schema(&schema_for!(GetPollResponse), &out_dir);
+    export_schema(&schema_for!(ListPollsResponse), &out_dir);
+    export_schema(&schema_for!(GetCreditsResponse), &out_dir);
+    export_schema(&schema_for!(GetRoleResponse), &out_dir);
+    export_schema(&schema_for!(IsEligibleResponse), &out_dir);
+    export_schema(&schema_for!(ListVotersResponse), &out_dir);
+    export_schema(&schema_for!(GetVoteResponse), &out_dir);
+    export_schema(&schema_for!(GetTallyResponse), &out_dir);
+    export_schema(&schema_for!(GetStatsResponse), &out_dir);
 }
@@ -0,0 +1,175 @@
+//! Bounties escrowed with `CreatePoll`. When the poll closes with a winning
+//! option the bounty goes to its recipient, or is split evenly among the voters
+//! who backed the winner. It is refunded to the creator when the poll misses
+//! its quorum, ends tied or is cancelled or removed.
+
+use cosmwasm_std::{
+    coins, to_binary, Addr, BankMsg, Binary, Deps, Env, Order, StdResult, Storage, Uint128,
+};
+
+use crate::msg::GetBountyResponse;
+use crate::state::{Ballot, Poll, BALLOTS, BOUNTIES};
+use crate::tally::backs_option;
+
+/// Pays out the bounty of a poll that just closed with `winner` leading.
+pub(crate) fn pay_bounty(
+    storage: &mut dyn Storage,
+    poll: &Poll,
+    winner: Option<usize>,
+) -> StdResult<Vec<BankMsg>> {
+    let bounty = match BOUNTIES.may_load(storage, poll.id)? {
+        Some(bounty) => bounty,
+        None => return Ok(vec![]),
+    };
+    BOUNTIES.remove(storage, poll.id);
+    let send = |to: &Addr, amount: Uint128| BankMsg::Send {
+        to_address: to.to_string(),
+        amount: coins(amount.u128(), &bounty.amount.denom),
+    };
+
+    let winner = match winner {
+        Some(winner) => winner,
+        None => return Ok(vec![send(&bounty.creator, bounty.amount.amount)]),
+    };
+    if let Some(recipient) = &bounty.recipient {
+        return Ok(vec![send(recipient, bounty.amount.amount)]);
+    }
+    let backers = BALLOTS
+        .prefix(poll.id)
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref().map_or(true, |(_, Ballot { choice, .. })| {
+                backs_option(poll, choice, winner)
+            })
+        })
+        .map(|item| item.map(|(voter, _)| voter))
+        .collect::<StdResult<Vec<_>>>()?;
+    if backers.is_empty() {
+        return Ok(vec![send(&bounty.creator, bounty.amount.amount)]);
+    }
+
+    let share = bounty
+        .amount
+        .amount
+        .checked_div(Uint128::from(backers.len() as u128))?;
+    let mut msgs = vec![];
+    if !share.is_zero() {
+        msgs.extend(backers.iter().map(|voter| send(voter, share)));
+    }
+    // what does not divide evenly goes back to the creator
+    let rest = bounty.amount.amount - share * Uint128::from(backers.len() as u128);
+    if !rest.is_zero() {
+        msgs.push(send(&bounty.creator, rest));
+    }
+    Ok(msgs)
+}
+
+/// Returns the bounty of a poll that is called off to its creator.
+pub(crate) fn refund_bounty(storage: &mut dyn Storage, poll_id: u64) -> StdResult<Option<BankMsg>> {
+    let bounty = BOUNTIES.may_load(storage, poll_id)?;
+    if bounty.is_some() {
+        BOUNTIES.remove(storage, poll_id);
+    }
+    Ok(bounty.map(|bounty| BankMsg::Send {
+        to_address: bounty.creator.to_string(),
+        amount: vec![bounty.amount],
+    }))
+}
+
+pub fn query_get_bounty(deps: Deps, _env: Env, poll_id: u64) -> StdResult<Binary> {
+    let bounty = BOUNTIES.may_load(deps.storage, poll_id)?;
+    to_binary(&GetBountyResponse { bounty })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, from_binary, CosmosMsg};
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::error::ContractError;
+    use crate::msg::{BountyMsg, Choice, CreatePollMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+    use crate::state::Bounty;
+
+    use super::*;
+
+    #[test]
+    fn test_bounty_split_among_winners() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            bounty: Some(BountyMsg {
+                amount: coin(100, "ujuno"),
+                recipient: None,
+            }),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFunds { .. }));
+        let creator = mock_info("addr1", &[coin(100, "ujuno")]);
+        execute(deps.as_mut(), env.clone(), creator, msg).unwrap();
+
+        let resp: GetBountyResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::GetBounty { poll_id: 1 },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            resp.bounty,
+            Some(Bounty {
+                creator: Addr::unchecked("addr1"),
+                amount: coin(100, "ujuno"),
+                recipient: None,
+            })
+        );
+
+        for (voter, choice) in [
+            ("addr2", Choice::Yes),
+            ("addr3", Choice::Yes),
+            ("addr4", Choice::Yes),
+            ("addr5", Choice::No),
+        ] {
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice,
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        let result = execute(deps.as_mut(), env, mock_info("addr1", &[]), msg).unwrap();
+        let payouts: Vec<_> = result
+            .messages
+            .into_iter()
+            .filter_map(|msg| match msg.msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                    Some((to_address, amount[0].amount.u128()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            payouts,
+            vec![
+                ("addr2".to_string(), 33),
+                ("addr3".to_string(), 33),
+                ("addr4".to_string(), 33),
+                ("addr1".to_string(), 1),
+            ]
+        );
+        assert!(!BOUNTIES.has(&deps.storage, 1));
+    }
+}
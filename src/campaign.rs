@@ -0,0 +1,300 @@
+//! Campaigns: several polls run as one multi-question ballot. A campaign's
+//! owner adds their own polls to it before voting starts on them, and the
+//! campaign's settings replace the polls' own. Tallies and turnout can then be
+//! read for the whole campaign at once.
+
+use std::collections::BTreeMap;
+
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Env, Event, MessageInfo, Order, Response, StdError,
+    StdResult, Storage,
+};
+use cw_utils::nonpayable;
+
+use crate::contract::{ensure_not_blocked, ensure_open, load_poll, poll_tally, validate_threshold};
+use crate::error::ContractError;
+use crate::funding::ensure_name;
+use crate::msg::{GetCampaignTallyResponse, GetCampaignTurnoutResponse};
+use crate::state::{
+    polls, Campaign, CampaignSettings, BALLOTS, CAMPAIGNS, CAMPAIGN_COUNT, CONFIG, CREATORS,
+    POLL_CAMPAIGNS,
+};
+
+const MAX_CAMPAIGN_POLLS: usize = 20;
+
+pub fn execute_create_campaign(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    name: String,
+    settings: CampaignSettings,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ensure_not_blocked(deps.storage, &info.sender)?;
+    let config = CONFIG.load(deps.storage)?;
+    if config.restrict_creation && !CREATORS.has(deps.storage, &info.sender) {
+        return Err(ContractError::CreationNotAllowed {});
+    }
+    ensure_name(&name)?;
+    validate_threshold(settings.threshold)?;
+
+    let id = CAMPAIGN_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    CAMPAIGN_COUNT.save(deps.storage, &id)?;
+    let campaign = Campaign {
+        id,
+        owner: info.sender,
+        name,
+        poll_ids: vec![],
+        settings,
+    };
+    CAMPAIGNS.save(deps.storage, id, &campaign)?;
+
+    let event = Event::new("campaign_created")
+        .add_attribute("campaign_id", id.to_string())
+        .add_attribute("name", campaign.name)
+        .add_attribute("owner", campaign.owner);
+    Ok(Response::new()
+        .add_attribute("action", "create_campaign")
+        .add_attribute("campaign_id", id.to_string())
+        .add_event(event))
+}
+
+/// Adds a poll to a campaign and applies the campaign's settings to it. Only
+/// open polls of the campaign's owner that nobody voted on yet can be added,
+/// so that no ballot is counted under other settings.
+pub fn execute_add_poll_to_campaign(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    campaign_id: u64,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut campaign = load_campaign(deps.as_ref(), campaign_id)?;
+    if info.sender != campaign.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    if campaign.poll_ids.len() >= MAX_CAMPAIGN_POLLS {
+        return Err(ContractError::CampaignFull { campaign_id });
+    }
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    ensure_open(&poll)?;
+    let voted = BALLOTS
+        .prefix(poll_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_some();
+    if poll.creator != campaign.owner || voted || POLL_CAMPAIGNS.has(deps.storage, poll_id) {
+        return Err(ContractError::InvalidCampaignPoll { poll_id });
+    }
+
+    poll.quorum = campaign.settings.quorum.or(poll.quorum);
+    poll.threshold = campaign.settings.threshold.or(poll.threshold);
+    polls().save(deps.storage, poll_id, &poll)?;
+    POLL_CAMPAIGNS.save(deps.storage, poll_id, &campaign_id)?;
+    campaign.poll_ids.push(poll_id);
+    CAMPAIGNS.save(deps.storage, campaign_id, &campaign)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_poll_to_campaign")
+        .add_attribute("campaign_id", campaign_id.to_string())
+        .add_attribute("poll_id", poll_id.to_string()))
+}
+
+pub fn query_get_campaign_tally(deps: Deps, _env: Env, campaign_id: u64) -> StdResult<Binary> {
+    let campaign = CAMPAIGNS.load(deps.storage, campaign_id)?;
+    let polls = campaign
+        .poll_ids
+        .iter()
+        .map(|poll_id| poll_tally(deps.storage, &polls().load(deps.storage, *poll_id)?))
+        .collect::<StdResult<Vec<_>>>()?;
+    to_binary(&GetCampaignTallyResponse { campaign, polls })
+}
+
+/// Counts the ballots of each poll of the campaign by voter. Campaigns hold a
+/// bounded number of polls, but each poll's ballots are read in full.
+pub fn query_get_campaign_turnout(deps: Deps, _env: Env, campaign_id: u64) -> StdResult<Binary> {
+    let campaign = CAMPAIGNS.load(deps.storage, campaign_id)?;
+    let mut ballots_by_voter = BTreeMap::new();
+    for poll_id in &campaign.poll_ids {
+        for voter in BALLOTS
+            .prefix(*poll_id)
+            .keys(deps.storage, None, None, Order::Ascending)
+        {
+            *ballots_by_voter.entry(voter?).or_insert(0usize) += 1;
+        }
+    }
+    let ballots = ballots_by_voter.values().sum::<usize>() as u64;
+    let complete_ballots = ballots_by_voter
+        .values()
+        .filter(|ballots| **ballots == campaign.poll_ids.len())
+        .count() as u64;
+    to_binary(&GetCampaignTurnoutResponse {
+        campaign_id,
+        ballots,
+        voters: ballots_by_voter.len() as u64,
+        complete_ballots,
+    })
+}
+
+/// Drops a removed poll from the campaign it was added to, if any.
+pub(crate) fn unlink_campaign_poll(storage: &mut dyn Storage, poll_id: u64) -> StdResult<()> {
+    let campaign_id = match POLL_CAMPAIGNS.may_load(storage, poll_id)? {
+        Some(campaign_id) => campaign_id,
+        None => return Ok(()),
+    };
+    POLL_CAMPAIGNS.remove(storage, poll_id);
+    CAMPAIGNS.update(storage, campaign_id, |campaign| -> StdResult<_> {
+        let mut campaign = campaign.ok_or_else(|| StdError::not_found("campaign"))?;
+        campaign.poll_ids.retain(|id| *id != poll_id);
+        Ok(campaign)
+    })?;
+    Ok(())
+}
+
+fn load_campaign(deps: Deps, campaign_id: u64) -> Result<Campaign, ContractError> {
+    CAMPAIGNS
+        .may_load(deps.storage, campaign_id)?
+        .ok_or(ContractError::CampaignNotFound { campaign_id })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, Decimal, Uint128};
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{Choice, CreatePollMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+    use crate::state::PollStatus;
+
+    use super::*;
+
+    #[test]
+    fn test_campaign() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreateCampaign {
+            name: "Spring ballot".to_string(),
+            settings: CampaignSettings {
+                quorum: Some(Uint128::new(2)),
+                threshold: Some(Decimal::percent(60)),
+            },
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let questions = [
+            "Should we fund a grants program",
+            "Should we launch a token",
+            "Should we hire a designer",
+        ];
+        for question in questions {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+        let vote = |poll_id, choice| ExecuteMsg::Vote {
+            poll_id,
+            choice,
+            proof: None,
+            memo: None,
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            vote(3, Choice::Yes),
+        )
+        .unwrap();
+
+        let add = |poll_id| ExecuteMsg::AddPollToCampaign {
+            campaign_id: 1,
+            poll_id,
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), add(1)).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        // poll 3 already has a vote
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), add(3)).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::InvalidCampaignPoll { poll_id: 3 }
+        ));
+        for poll_id in [1, 2] {
+            execute(deps.as_mut(), env.clone(), info.clone(), add(poll_id)).unwrap();
+        }
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), add(1)).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::InvalidCampaignPoll { poll_id: 1 }
+        ));
+        let poll = polls().load(&deps.storage, 2).unwrap();
+        assert_eq!(poll.threshold, Some(Decimal::percent(60)));
+
+        let votes = [
+            ("addr2", 1, Choice::Yes),
+            ("addr2", 2, Choice::Yes),
+            ("addr3", 1, Choice::Yes),
+            ("addr3", 2, Choice::No),
+            ("addr4", 1, Choice::No),
+        ];
+        for (voter, poll_id, choice) in votes {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(voter, &[]),
+                vote(poll_id, choice),
+            )
+            .unwrap();
+        }
+        let msg = QueryMsg::GetCampaignTurnout { campaign_id: 1 };
+        let resp: GetCampaignTurnoutResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            resp,
+            GetCampaignTurnoutResponse {
+                campaign_id: 1,
+                ballots: 5,
+                voters: 3,
+                complete_ballots: 2,
+            }
+        );
+
+        // two thirds of yes clear the campaign's threshold, half do not
+        env.block.height += 100_000;
+        for poll_id in [1, 2] {
+            let msg = ExecuteMsg::ClosePoll { poll_id };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+        let msg = QueryMsg::GetCampaignTally { campaign_id: 1 };
+        let resp: GetCampaignTallyResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(resp.campaign.poll_ids, [1, 2]);
+        let statuses: Vec<_> = resp.polls.iter().map(|tally| &tally.status).collect();
+        assert_eq!(statuses, [&PollStatus::Passed, &PollStatus::Rejected]);
+
+        // removed polls leave the campaign
+        let msg = ExecuteMsg::RemovePoll {
+            poll_id: 2,
+            reason: "duplicate".to_string(),
+            slash_deposit: false,
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert!(!POLL_CAMPAIGNS.has(&deps.storage, 2));
+        let msg = QueryMsg::GetCampaignTally { campaign_id: 1 };
+        let resp: GetCampaignTallyResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(resp.campaign.poll_ids, [1]);
+        assert_eq!(resp.polls.len(), 1);
+        let msg = QueryMsg::GetCampaignTurnout { campaign_id: 1 };
+        let resp: GetCampaignTurnoutResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!((resp.ballots, resp.complete_ballots), (3, 3));
+    }
+}
@@ -0,0 +1,183 @@
+//! Weight caps: the most weight a single ballot can carry on a poll, either an
+//! absolute amount or a share of the supply of the poll's voting power.
+//!
+//! The supply is read from the same place `voter_weight` reads weights from:
+//! snapshots of bonded power at the poll's start, the cw20 token's supply, or
+//! the cw4 group's total weight. Sources without a known supply only accept
+//! absolute caps.
+
+use cosmwasm_std::{Decimal, Deps, StdResult, Uint128};
+use cw20::{Cw20QueryMsg, TokenInfoResponse};
+
+use crate::error::ContractError;
+use crate::msg::{Cw4QueryMsg, TotalWeightResponse};
+use crate::state::{Config, Poll, VotingPowerSource, WeightCap, TOTAL_POWER};
+
+/// Lowers `weight` to the cap of `poll`, if it has one.
+pub(crate) fn apply_weight_cap(
+    deps: Deps,
+    config: &Config,
+    poll: &Poll,
+    weight: Uint128,
+) -> Result<Uint128, ContractError> {
+    let cap = match &poll.weight_cap {
+        Some(WeightCap::Absolute { amount }) => *amount,
+        Some(WeightCap::ShareOfSupply { share }) => {
+            total_power(deps, config, poll.voting_power.as_ref(), poll.start_height)? * *share
+        }
+        None => return Ok(weight),
+    };
+    Ok(weight.min(cap))
+}
+
+pub(crate) fn validate_weight_cap(cap: Option<&WeightCap>) -> Result<(), ContractError> {
+    let valid = match cap {
+        Some(WeightCap::Absolute { amount }) => !amount.is_zero(),
+        Some(WeightCap::ShareOfSupply { share }) => !share.is_zero() && *share <= Decimal::one(),
+        None => true,
+    };
+    if !valid {
+        return Err(ContractError::InvalidWeightCap {});
+    }
+    Ok(())
+}
+
+/// Total voting power at `height` of polls weighed by `voting_power`, or by the
+/// configured source when it is not set.
+pub(crate) fn total_power(
+    deps: Deps,
+    config: &Config,
+    voting_power: Option<&VotingPowerSource>,
+    height: u64,
+) -> Result<Uint128, ContractError> {
+    if let Some(source) = voting_power {
+        return source_supply(deps, source, height);
+    }
+    if config.remote_power.is_some() {
+        return Err(ContractError::UnknownSupply {});
+    }
+    if config.snapshot_power {
+        return Ok(TOTAL_POWER
+            .may_load_at_height(deps.storage, height)?
+            .unwrap_or_default());
+    }
+    if let Some(token) = &config.cw20_token {
+        return Ok(token_supply(deps, token.as_str())?);
+    }
+    source_supply(deps, &config.voting_power, height)
+}
+
+fn source_supply(
+    deps: Deps,
+    source: &VotingPowerSource,
+    height: u64,
+) -> Result<Uint128, ContractError> {
+    match source {
+        VotingPowerSource::Cw20Balance { token } => Ok(token_supply(deps, token.as_str())?),
+        VotingPowerSource::Cw4Group { group } => {
+            let total: TotalWeightResponse = deps.querier.query_wasm_smart(
+                group,
+                &Cw4QueryMsg::TotalWeight {
+                    at_height: Some(height),
+                },
+            )?;
+            Ok(Uint128::from(total.weight))
+        }
+        _ => Err(ContractError::UnknownSupply {}),
+    }
+}
+
+fn token_supply(deps: Deps, token: &str) -> StdResult<Uint128> {
+    let info: TokenInfoResponse = deps
+        .querier
+        .query_wasm_smart(token, &Cw20QueryMsg::TokenInfo {})?;
+    Ok(info.total_supply)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{
+        from_binary, to_binary, ContractResult, SystemError, SystemResult, WasmQuery,
+    };
+    use cw20::Cw20ReceiveMsg;
+
+    use crate::contract::{execute, instantiate};
+    use crate::msg::{Choice, CreatePollMsg, ExecuteMsg, InstantiateMsg, ReceiveMsg};
+    use crate::state::polls;
+
+    use super::*;
+
+    #[test]
+    fn test_weight_cap() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let mut msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            weight_cap: Some(WeightCap::ShareOfSupply {
+                share: Decimal::percent(150),
+            }),
+            ..Default::default()
+        };
+        let err = instantiate(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidWeightCap {}));
+        msg.weight_cap = Some(WeightCap::Absolute {
+            amount: Uint128::new(50),
+        });
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "token" => {
+                assert_eq!(
+                    from_binary::<Cw20QueryMsg>(msg).unwrap(),
+                    Cw20QueryMsg::TokenInfo {}
+                );
+                let info = TokenInfoResponse {
+                    name: "token".to_string(),
+                    symbol: "TKN".to_string(),
+                    decimals: 6,
+                    total_supply: Uint128::new(1000),
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "unexpected query".to_string(),
+            }),
+        });
+
+        let caps = [
+            None,
+            Some(WeightCap::ShareOfSupply {
+                share: Decimal::percent(3),
+            }),
+        ];
+        let questions = ["Do you love spark IBC", "Do you love cosmwasm"];
+        let token = mock_info("token", &[]);
+        for (poll_id, (question, weight_cap)) in (1..).zip(questions.into_iter().zip(caps)) {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                weight_cap,
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            for (voter, amount) in [("addr2", 100), ("addr3", 20)] {
+                let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                    sender: voter.to_string(),
+                    amount: Uint128::new(amount),
+                    msg: to_binary(&ReceiveMsg::Vote {
+                        poll_id,
+                        choice: Choice::Yes,
+                    })
+                    .unwrap(),
+                });
+                execute(deps.as_mut(), env.clone(), token.clone(), msg).unwrap();
+            }
+        }
+        // the configured cap, then 3% of the token supply
+        let votes: Vec<_> = (1..=2)
+            .map(|poll_id| polls().load(&deps.storage, poll_id).unwrap().options[0].votes)
+            .collect();
+        assert_eq!(votes, [70, 50].map(Uint128::new).to_vec());
+    }
+}
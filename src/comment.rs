@@ -0,0 +1,225 @@
+//! Discussion threads on polls. Comments are numbered per poll, and a reply
+//! points at the comment it answers within the same poll.
+
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Env, Event, MessageInfo, Order, Response, StdResult,
+};
+use cw_storage_plus::Bound;
+use cw_utils::nonpayable;
+
+use crate::contract::{ensure_not_blocked, ensure_role, load_poll, DEFAULT_LIMIT, MAX_LIMIT};
+use crate::error::ContractError;
+use crate::msg::ListCommentsResponse;
+use crate::state::{Comment, Role, COMMENTS, COMMENT_COUNTS, CONFIG};
+
+const MAX_COMMENT_LEN: usize = 1024;
+
+pub fn execute_comment(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    body: String,
+    reply_to: Option<u64>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ensure_not_blocked(deps.storage, &info.sender)?;
+    load_poll(deps.storage, poll_id)?;
+    let body = body.trim().to_string();
+    if body.is_empty() {
+        return Err(ContractError::EmptyComment {});
+    }
+    if body.len() > MAX_COMMENT_LEN {
+        return Err(ContractError::FieldTooLong {
+            field: "body".to_string(),
+            max: MAX_COMMENT_LEN,
+        });
+    }
+    if let Some(comment_id) = reply_to {
+        load_comment(deps.as_ref(), poll_id, comment_id)?;
+    }
+
+    let id = COMMENT_COUNTS
+        .may_load(deps.storage, poll_id)?
+        .unwrap_or_default()
+        + 1;
+    COMMENT_COUNTS.save(deps.storage, poll_id, &id)?;
+    let comment = Comment {
+        id,
+        author: info.sender,
+        body,
+        reply_to,
+        created_at: env.block.time,
+    };
+    COMMENTS.save(deps.storage, (poll_id, id), &comment)?;
+
+    let mut event = Event::new("comment_posted")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("comment_id", id.to_string())
+        .add_attribute("author", comment.author.clone());
+    if let Some(reply_to) = reply_to {
+        event = event.add_attribute("reply_to", reply_to.to_string());
+    }
+    Ok(Response::new()
+        .add_attribute("action", "comment")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("comment_id", id.to_string())
+        .add_event(event))
+}
+
+/// Comments can be deleted by their author, the poll's creator, moderators and
+/// owners. Replies to a deleted comment are kept.
+pub fn execute_delete_comment(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    comment_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let poll = load_poll(deps.storage, poll_id)?;
+    let comment = load_comment(deps.as_ref(), poll_id, comment_id)?;
+    if info.sender != comment.author && info.sender != poll.creator {
+        let config = CONFIG.load(deps.storage)?;
+        ensure_role(
+            deps.storage,
+            &config,
+            &info.sender,
+            &[Role::Owner, Role::Moderator],
+        )?;
+    }
+    COMMENTS.remove(deps.storage, (poll_id, comment_id));
+
+    let event = Event::new("comment_deleted")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("comment_id", comment_id.to_string())
+        .add_attribute("deleted_by", info.sender);
+    Ok(Response::new()
+        .add_attribute("action", "delete_comment")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("comment_id", comment_id.to_string())
+        .add_event(event))
+}
+
+pub fn query_list_comments(
+    deps: Deps,
+    _env: Env,
+    poll_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let comments = COMMENTS
+        .prefix(poll_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, comment)| comment))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ListCommentsResponse { comments })
+}
+
+fn load_comment(deps: Deps, poll_id: u64, comment_id: u64) -> Result<Comment, ContractError> {
+    COMMENTS
+        .may_load(deps.storage, (poll_id, comment_id))?
+        .ok_or(ContractError::CommentNotFound {
+            poll_id,
+            comment_id,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, Addr};
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{CreatePollMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+
+    use super::*;
+
+    #[test]
+    fn test_comments() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "admin".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Comment {
+            poll_id: 1,
+            body: "  ".to_string(),
+            reply_to: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::EmptyComment {}));
+
+        let msg = ExecuteMsg::Comment {
+            poll_id: 1,
+            body: "Yes, for the relayers".to_string(),
+            reply_to: Some(1),
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::CommentNotFound {
+                poll_id: 1,
+                comment_id: 1
+            }
+        ));
+
+        let msg = ExecuteMsg::Comment {
+            poll_id: 1,
+            body: "Why ask?".to_string(),
+            reply_to: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap();
+        let msg = ExecuteMsg::Comment {
+            poll_id: 1,
+            body: "Yes, for the relayers".to_string(),
+            reply_to: Some(1),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("addr3", &[]), msg).unwrap();
+
+        // only the author, the poll's creator or a moderator may delete
+        let msg = ExecuteMsg::DeleteComment {
+            poll_id: 1,
+            comment_id: 2,
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let msg = ExecuteMsg::DeleteComment {
+            poll_id: 1,
+            comment_id: 1,
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let msg = QueryMsg::ListComments {
+            poll_id: 1,
+            start_after: None,
+            limit: None,
+        };
+        let resp: ListCommentsResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            resp.comments,
+            vec![Comment {
+                id: 2,
+                author: Addr::unchecked("addr3"),
+                body: "Yes, for the relayers".to_string(),
+                reply_to: Some(1),
+                created_at: env.block.time,
+            }]
+        );
+    }
+}
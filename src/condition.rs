@@ -0,0 +1,237 @@
+//! Conditional polls: a poll created with `opens_if_passed` stays pending
+//! until its parent closes. It opens, with the voting period it was created
+//! with, if the parent passed and is cancelled otherwise.
+
+use cosmwasm_std::{BlockInfo, Empty, Event, Order, Response, StdResult, Storage};
+use cw_utils::{Duration, Expiration, Scheduled};
+
+use crate::contract::{cancel_poll, ensure_open, load_poll};
+use crate::error::ContractError;
+use crate::state::{polls, Poll, PollCondition, PollStatus, DEPENDENT_POLLS};
+
+/// The condition of a poll that opens once `parent` passes, keeping the time
+/// left until `expiration` as its voting period.
+pub(crate) fn poll_condition(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    parent: u64,
+    expiration: Expiration,
+) -> Result<PollCondition, ContractError> {
+    ensure_open(&load_poll(storage, parent)?)?;
+    let voting_period = match expiration {
+        Expiration::AtHeight(end) => Some(Duration::Height(end - block.height)),
+        Expiration::AtTime(end) => Some(Duration::Time(end.seconds() - block.time.seconds())),
+        Expiration::Never {} => None,
+    };
+    Ok(PollCondition {
+        parent,
+        voting_period,
+        met: false,
+    })
+}
+
+pub(crate) fn add_dependent(storage: &mut dyn Storage, poll: &Poll) -> StdResult<()> {
+    match &poll.condition {
+        Some(condition) => DEPENDENT_POLLS.save(storage, (condition.parent, poll.id), &Empty {}),
+        None => Ok(()),
+    }
+}
+
+/// Opens the polls waiting on `parent` if it passed, cancels them otherwise.
+pub(crate) fn settle_dependents(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    parent: &Poll,
+) -> Result<Response, ContractError> {
+    let dependents = DEPENDENT_POLLS
+        .prefix(parent.id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut response = Response::new();
+    for poll_id in dependents {
+        DEPENDENT_POLLS.remove(storage, (parent.id, poll_id));
+        // dependents removed with `RemovePoll` are skipped
+        let mut poll = match polls().may_load(storage, poll_id)? {
+            Some(poll) if poll.status == PollStatus::Open => poll,
+            _ => continue,
+        };
+        if parent.status != PollStatus::Passed {
+            let cancelled_by = format!("poll {}", parent.id);
+            let cancelled = cancel_poll(storage, block, poll, &cancelled_by)?;
+            response = response
+                .add_submessages(cancelled.messages)
+                .add_events(cancelled.events);
+            continue;
+        }
+        open(&mut poll, block);
+        polls().save(storage, poll_id, &poll)?;
+        response = response.add_event(
+            Event::new("poll_opened")
+                .add_attribute("poll_id", poll_id.to_string())
+                .add_attribute("parent", parent.id.to_string())
+                .add_attribute("expiration", poll.expiration.to_string()),
+        );
+    }
+    Ok(response)
+}
+
+/// Starts the voting period of a poll whose condition was just met.
+fn open(poll: &mut Poll, block: &BlockInfo) {
+    let voting_period = match &mut poll.condition {
+        Some(condition) => {
+            condition.met = true;
+            condition.voting_period
+        }
+        None => return,
+    };
+    poll.start_height = block.height;
+    poll.expiration = match voting_period {
+        Some(period) => period.after(block),
+        None => Expiration::Never {},
+    };
+    if let Some(decay) = &mut poll.weight_decay {
+        decay.opens = match decay.opens {
+            Scheduled::AtHeight(_) => Scheduled::AtHeight(block.height),
+            Scheduled::AtTime(_) => Scheduled::AtTime(block.time),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+
+    use crate::contract::{execute, instantiate};
+    use crate::msg::{Choice, CreatePollMsg, ExecuteMsg, InstantiateMsg};
+
+    use super::*;
+
+    #[test]
+    fn test_conditional_poll() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let polls_to_create = [
+            ("Should we fund a grants program", None),
+            ("Should we launch a token", None),
+            ("Should grants be paid monthly", Some(1)),
+            ("Should the token be inflationary", Some(2)),
+        ];
+        for (question, opens_if_passed) in polls_to_create {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                expiration: Some(Expiration::AtHeight(env.block.height + 50)),
+                opens_if_passed,
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+        let vote = |poll_id, choice| ExecuteMsg::Vote {
+            poll_id,
+            choice,
+            proof: None,
+            memo: None,
+        };
+        let voter = mock_info("addr2", &[]);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            voter.clone(),
+            vote(3, Choice::Yes),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::PollNotStarted { poll_id: 3 }));
+
+        for (poll_id, choice) in [(1, Choice::Yes), (2, Choice::No)] {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                voter.clone(),
+                vote(poll_id, choice),
+            )
+            .unwrap();
+        }
+        env.block.height += 50;
+        for poll_id in [1, 2] {
+            let msg = ExecuteMsg::ClosePoll { poll_id };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        // the voting period runs from when the parent passed
+        let poll = polls().load(&deps.storage, 3).unwrap();
+        assert_eq!(poll.status, PollStatus::Open);
+        assert_eq!(poll.expiration, Expiration::AtHeight(env.block.height + 50));
+        execute(deps.as_mut(), env.clone(), voter, vote(3, Choice::Yes)).unwrap();
+        let poll = polls().load(&deps.storage, 4).unwrap();
+        assert_eq!(poll.status, PollStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_remove_conditional_poll() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let polls_to_create = [
+            ("Should we fund a grants program", None),
+            ("Should grants be paid monthly", Some(1)),
+            ("Should we launch a token", None),
+            ("Should the token be inflationary", Some(3)),
+        ];
+        for (question, opens_if_passed) in polls_to_create {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                expiration: Some(Expiration::AtHeight(env.block.height + 50)),
+                opens_if_passed,
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+        let remove = |poll_id| ExecuteMsg::RemovePoll {
+            poll_id,
+            reason: "spam".to_string(),
+            slash_deposit: false,
+        };
+
+        // a removed dependent no longer blocks its parent from closing
+        execute(deps.as_mut(), env.clone(), info.clone(), remove(2)).unwrap();
+        assert!(!DEPENDENT_POLLS.has(&deps.storage, (1, 2)));
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap();
+        env.block.height += 50;
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(
+            polls().load(&deps.storage, 1).unwrap().status,
+            PollStatus::Passed
+        );
+
+        // removing a parent cancels the polls waiting on it
+        let result = execute(deps.as_mut(), env, info, remove(3)).unwrap();
+        assert!(result
+            .events
+            .iter()
+            .any(|event| event.ty == "poll_cancelled"));
+        assert_eq!(
+            polls().load(&deps.storage, 4).unwrap().status,
+            PollStatus::Cancelled
+        );
+        assert!(!DEPENDENT_POLLS.has(&deps.storage, (3, 4)));
+    }
+}
@@ -1,239 +1,8292 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
-use cw2::set_contract_version;
+use cosmwasm_std::{
+    from_binary, to_binary, to_vec, Addr, Api, BankMsg, Binary, BlockInfo, Coin, Decimal, Deps,
+    DepsMut, Empty, Env, Event, MessageInfo, Order, Reply, Response, StdError, StdResult, Storage,
+    SubMsg, SubMsgResult, Uint128, Uint64, WasmMsg,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::Bound;
+use cw_utils::{must_pay, nonpayable, Duration, Expiration, PaymentError, Scheduled};
+use semver::Version;
+use sha2::{Digest, Sha256};
 
+use crate::bounty::{pay_bounty, query_get_bounty, refund_bounty};
+use crate::campaign::{
+    execute_add_poll_to_campaign, execute_create_campaign, query_get_campaign_tally,
+    query_get_campaign_turnout, unlink_campaign_poll,
+};
+use crate::cap::{apply_weight_cap, total_power, validate_weight_cap};
+use crate::comment::{execute_comment, execute_delete_comment, query_list_comments};
+use crate::condition::{add_dependent, poll_condition, settle_dependents};
+use crate::cw3::{
+    query_list_proposals, query_list_votes, query_proposal, query_threshold, query_vote,
+};
+use crate::dao::{query_info, query_total_power_at_height, query_voting_power_at_height};
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, GetPollResponse, InstantiateMsg, QueryMsg};
-use crate::state::{Config, Poll, CONFIG, POLLS};
+use crate::factory::{
+    execute_create_poll_contract, query_child_address, query_list_children, reply_instantiate_child,
+};
+use crate::funding::{
+    execute_close_round, execute_contribute, execute_create_round, execute_fund_round,
+    execute_list_project, query_get_round,
+};
+use crate::ibc::{finalize_packets, tally_packet};
+use crate::icq::{query_remote_power, register_query_msg, registered_query_id};
+use crate::incentive::{
+    accrue_incentives, create_incentive, execute_claim_incentives, execute_withdraw_incentive,
+    query_get_accrued_incentives, query_get_incentive,
+};
+use crate::market::{
+    execute_claim_payout, execute_place_bet, execute_resolve_poll, query_get_stake, settle_stakes,
+};
+use crate::msg::{
+    ChannelTally, Choice, CommitRevealMsg, CreatePollMsg, ExecuteMsg, GetCreditsResponse,
+    GetPollResponse, GetRemotePowerResponse, GetRoleResponse, GetStatsResponse, GetTallyResponse,
+    GetVoteResponse, HookExecuteMsg, InstantiateMsg, IsEligibleResponse, ListHooksResponse,
+    ListPollsResponse, ListRemoteTalliesResponse, ListVotersResponse, MemberChangedHookMsg,
+    MigrateMsg, NftGateMsg, OptionDetails, OptionTally, PollHookMsg, PollOverrides, PollSummary,
+    QueryMsg, ReceiveMsg, SudoMsg, VoteItem, VoterInfo, VotingPowerSourceMsg, WeightDecayMsg,
+};
+use crate::power::nft_count;
+use crate::raffle::{cancel_raffle, execute_nois_receive, execute_start_raffle, query_get_raffle};
+use crate::receipt::{query_get_receipt, receipt_msg, reply_mint_receipt};
+use crate::reputation::{
+    apply_bonus, forget_vote, query_get_reputation, query_leaderboard, record_poll_created,
+    record_poll_won, record_vote, validate_bonus,
+};
+use crate::rewards::{
+    execute_claim_rewards, execute_fund_rewards, execute_sweep_rewards, fund_rewards,
+    open_reward_claims, query_get_reward_pool, refund_rewards,
+};
+use crate::runoff::{open_runoff, query_list_runoff_rounds, validate_runoff};
+use crate::state::{
+    polls, Ballot, Bounty, CommitReveal, Commitment, Config, HubLink, NftGate, PendingAdmin, Poll,
+    PollDeposit, PollKind, PollOption, PollSeries, PollStatus, RemotePower, RewardAsset, Role,
+    Stats, TieBreak, VotingPowerSource, WeightDecay, BALLOTS, BLOCKLIST, BOUNTIES, CHANNELS,
+    COMMENTS, COMMENT_COUNTS, COMMITMENTS, CONFIG, CREATORS, CREDITS, DEPENDENT_POLLS, DEPOSITS,
+    ENDORSEMENTS, ESCROWS, EXECUTING_POLL, FEES, GROUP_WEIGHTS, HOOKS, HUB_POLLS, PENDING_ADMIN,
+    POLL_COUNT, POLL_TAGS, POLL_VOTERS, REGISTERING_VOTER, REMOTE_POWER, REMOTE_POWER_QUERIES,
+    REMOTE_TALLIES, ROLES, SERIES, STATS, TOTAL_POWER, VOTERS, VOTING_POWER,
+};
+use crate::streak::{apply_streak_bonus, query_get_streak, record_close, validate_streak_bonus};
+use crate::survey::{
+    execute_create_survey, execute_submit_survey, query_get_survey, query_get_survey_answers,
+};
+use crate::tally::{
+    add_vote, binary_outcome, decayed_weight, instant_runoff, leading_option, option_index,
+    petition_reached, quadratic_cost, quorum_reached, remove_vote, runoff_options, tied_options,
+    total_votes, turnout_reached,
+};
+use crate::template::{
+    execute_create_poll_from_template, execute_register_template, execute_remove_template,
+    query_list_templates,
+};
+use crate::tiebreak::{awaiting_draw, extend_deadline, request_draw, validate_tie_break};
+use crate::writein::{
+    execute_add_option, execute_vote_write_in, validate_new_options, validate_write_ins,
+};
 
 const CONTRACT_NAME: &str = "crates.io:mycosmwasm";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+pub(crate) const MAX_POLL_OPTIONS: usize = 10;
+pub(crate) const MAX_OPTION_LEN: usize = 64;
+const DEFAULT_QUESTION_MIN_LEN: u32 = 1;
+const DEFAULT_QUESTION_MAX_LEN: u32 = 256;
+const MAX_DESCRIPTION_LEN: usize = 2048;
+const MAX_TAGS: usize = 5;
+const MAX_TAG_LEN: usize = 32;
+const MAX_LINK_LEN: usize = 256;
+const MAX_MEMO_LEN: usize = 512;
+const MAX_BATCH_VOTES: usize = 10;
+const DEFAULT_MAX_MULTICALL: u32 = 10;
+const MAX_DECAY_FACTOR: u64 = 10;
+
+/// Reply to a failed message dispatched by `ExecutePoll`.
+const EXECUTE_POLL_REPLY_ID: u64 = 1;
+/// Reply to `RegisterRemotePower` carrying the id of the new interchain query.
+const REGISTER_QUERY_REPLY_ID: u64 = 2;
+/// Reply to a failed `PollHookMsg`.
+const HOOK_REPLY_ID: u64 = 3;
+/// Reply to `CreatePollContract` carrying the address of the new child.
+pub(crate) const INSTANTIATE_CHILD_REPLY_ID: u64 = 4;
+/// Reply to a vote receipt mint.
+pub(crate) const MINT_RECEIPT_REPLY_ID: u64 = 5;
+
+pub(crate) const DEFAULT_LIMIT: u32 = 10;
+pub(crate) const MAX_LIMIT: u32 = 30;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     let validated_admin_address = deps.api.addr_validate(&msg.admin_address)?;
+    let cw20_token = msg
+        .cw20_token
+        .map(|token| deps.api.addr_validate(&token))
+        .transpose()?;
+    let voting_power = msg
+        .voting_power
+        .map(|source| validate_voting_power(deps.api, source))
+        .transpose()?
+        .unwrap_or_default();
+    let nois_proxy = msg
+        .nois_proxy
+        .map(|proxy| deps.api.addr_validate(&proxy))
+        .transpose()?;
+    let receipt_minter = msg
+        .receipt_minter
+        .map(|minter| deps.api.addr_validate(&minter))
+        .transpose()?;
+    if msg.snapshot_power && cw20_token.is_none() {
+        return Err(ContractError::SnapshotRequiresToken {});
+    }
+
+    let question_min_len = msg.question_min_len.unwrap_or(DEFAULT_QUESTION_MIN_LEN);
+    let question_max_len = msg.question_max_len.unwrap_or(DEFAULT_QUESTION_MAX_LEN);
+    if question_min_len == 0 || question_min_len > question_max_len {
+        return Err(ContractError::InvalidQuestionBounds {});
+    }
+    validate_threshold(msg.default_threshold)?;
+    validate_voting_period(msg.default_voting_period)?;
+    validate_bonus(msg.reputation_bonus.as_ref())?;
+    validate_streak_bonus(msg.streak_bonus.as_ref())?;
+    validate_weight_cap(msg.weight_cap.as_ref())?;
+
+    for validator in &msg.stake_validators {
+        ensure_validator(deps.as_ref(), validator)?;
+    }
+
+    let config = Config {
+        admin_address: validated_admin_address,
+        voting_power,
+        stake_validators: msg.stake_validators,
+        cw20_token,
+        escrow_cw20: msg.escrow_cw20,
+        snapshot_power: msg.snapshot_power,
+        poll_deposit: msg.poll_deposit,
+        creation_fee: msg.creation_fee,
+        paused: false,
+        restrict_creation: msg.restrict_creation,
+        question_min_len,
+        question_max_len,
+        max_multicall: msg.max_multicall.unwrap_or(DEFAULT_MAX_MULTICALL),
+        default_quorum: msg.default_quorum,
+        default_threshold: msg.default_threshold,
+        default_voting_period: msg.default_voting_period,
+        execution_delay: msg.execution_delay,
+        veto_period: msg.veto_period,
+        remote_power: msg.remote_power,
+        nois_proxy,
+        nois_fee: msg.nois_fee,
+        receipt_minter,
+        reputation_bonus: msg.reputation_bonus,
+        streak_bonus: msg.streak_bonus,
+        weight_cap: msg.weight_cap,
+    };
+    CONFIG.save(deps.storage, &config)?;
+    POLL_COUNT.save(deps.storage, &0)?;
+    STATS.save(deps.storage, &Stats::default())?;
+
+    Ok(Response::new().add_attribute("action", "instantiate"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // Group hooks and randomness are still accepted so membership changes are
+    // not reverted and raffles are not left undrawn.
+    let exempt = matches!(
+        msg,
+        ExecuteMsg::Unpause {} | ExecuteMsg::MemberChangedHook(_) | ExecuteMsg::NoisReceive { .. }
+    );
+    if !exempt && CONFIG.load(deps.storage)?.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    match msg {
+        ExecuteMsg::CreatePoll(msg) => execute_create_poll(deps, env, info, msg),
+        ExecuteMsg::CreatePollContract(msg) => execute_create_poll_contract(deps, env, info, msg),
+        ExecuteMsg::Vote {
+            poll_id,
+            choice,
+            proof,
+            memo,
+        } => execute_vote(deps, env, info, poll_id, choice, proof, memo),
+        ExecuteMsg::VoteWriteIn {
+            poll_id,
+            label,
+            proof,
+            memo,
+        } => execute_vote_write_in(deps, env, info, poll_id, label, proof, memo),
+        ExecuteMsg::AddOption { poll_id, label } => {
+            execute_add_option(deps, env, info, poll_id, label)
+        }
+        ExecuteMsg::VoteMany { votes } => execute_vote_many(deps, env, info, votes),
+        ExecuteMsg::Multicall { msgs } => execute_multicall(deps, env, info, msgs),
+        ExecuteMsg::ClonePoll { poll_id, overrides } => {
+            execute_clone_poll(deps, env, info, poll_id, overrides)
+        }
+        ExecuteMsg::RegisterTemplate(msg) => execute_register_template(deps, env, info, msg),
+        ExecuteMsg::RemoveTemplate { template_id } => {
+            execute_remove_template(deps, env, info, template_id)
+        }
+        ExecuteMsg::CreatePollFromTemplate {
+            template_id,
+            question,
+            description,
+            tags,
+        } => execute_create_poll_from_template(
+            deps,
+            env,
+            info,
+            template_id,
+            question,
+            description,
+            tags,
+        ),
+        ExecuteMsg::Endorse { poll_id } => execute_endorse(deps, env, info, poll_id),
+        ExecuteMsg::Comment {
+            poll_id,
+            body,
+            reply_to,
+        } => execute_comment(deps, env, info, poll_id, body, reply_to),
+        ExecuteMsg::DeleteComment {
+            poll_id,
+            comment_id,
+        } => execute_delete_comment(deps, env, info, poll_id, comment_id),
+        ExecuteMsg::CreateSurvey {
+            title,
+            questions,
+            expiration,
+        } => execute_create_survey(deps, env, info, title, questions, expiration),
+        ExecuteMsg::SubmitSurvey { survey_id, answers } => {
+            execute_submit_survey(deps, env, info, survey_id, answers)
+        }
+        ExecuteMsg::CreateRound {
+            name,
+            denom,
+            expiration,
+        } => execute_create_round(deps, env, info, name, denom, expiration),
+        ExecuteMsg::FundRound { round_id } => execute_fund_round(deps, env, info, round_id),
+        ExecuteMsg::ListProject {
+            round_id,
+            name,
+            recipient,
+        } => execute_list_project(deps, env, info, round_id, name, recipient),
+        ExecuteMsg::Contribute { round_id, project } => {
+            execute_contribute(deps, env, info, round_id, project)
+        }
+        ExecuteMsg::CloseRound { round_id } => execute_close_round(deps, env, info, round_id),
+        ExecuteMsg::CreateCampaign { name, settings } => {
+            execute_create_campaign(deps, env, info, name, settings)
+        }
+        ExecuteMsg::AddPollToCampaign {
+            campaign_id,
+            poll_id,
+        } => execute_add_poll_to_campaign(deps, env, info, campaign_id, poll_id),
+        ExecuteMsg::ProposeNewAdmin { address, expiry } => {
+            execute_propose_new_admin(deps, env, info, address, expiry)
+        }
+        ExecuteMsg::AcceptAdmin {} => execute_accept_admin(deps, env, info),
+        ExecuteMsg::GrantRole { address, role } => {
+            execute_grant_role(deps, env, info, address, role)
+        }
+        ExecuteMsg::RevokeRole { address } => execute_revoke_role(deps, env, info, address),
+        ExecuteMsg::AddToBlocklist { address } => {
+            execute_add_to_blocklist(deps, env, info, address)
+        }
+        ExecuteMsg::RemoveFromBlocklist { address } => {
+            execute_remove_from_blocklist(deps, env, info, address)
+        }
+        ExecuteMsg::UpdateStakeValidators { add, remove } => {
+            execute_update_stake_validators(deps, env, info, add, remove)
+        }
+        ExecuteMsg::UpdateCreators { add, remove } => {
+            execute_update_creators(deps, env, info, add, remove)
+        }
+        ExecuteMsg::MemberChangedHook(msg) => execute_member_changed_hook(deps, env, info, msg),
+        ExecuteMsg::AddHook { address } => execute_add_hook(deps, env, info, address),
+        ExecuteMsg::RemoveHook { address } => execute_remove_hook(deps, env, info, address),
+        ExecuteMsg::ClosePoll { poll_id } => execute_close_poll(deps, env, info, poll_id),
+        ExecuteMsg::ExecutePoll { poll_id } => execute_execute_poll(deps, env, info, poll_id),
+        ExecuteMsg::VetoPoll { poll_id } => execute_veto_poll(deps, env, info, poll_id),
+        ExecuteMsg::SyncTally { poll_id } => execute_sync_tally(deps, env, info, poll_id),
+        ExecuteMsg::RegisterRemotePower {} => execute_register_remote_power(deps, env, info),
+        ExecuteMsg::UpdatePoll {
+            poll_id,
+            question,
+            options,
+            description,
+        } => execute_update_poll(deps, env, info, poll_id, question, options, description),
+        ExecuteMsg::CancelPoll { poll_id } => execute_cancel_poll(deps, env, info, poll_id),
+        ExecuteMsg::RemovePoll {
+            poll_id,
+            reason,
+            slash_deposit,
+        } => execute_remove_poll(deps, env, info, poll_id, reason, slash_deposit),
+        ExecuteMsg::Receive(msg) => execute_receive(deps, env, info, msg),
+        ExecuteMsg::WithdrawTokens { poll_id } => execute_withdraw_tokens(deps, env, info, poll_id),
+        ExecuteMsg::CommitVote { poll_id, hash } => {
+            execute_commit_vote(deps, env, info, poll_id, hash)
+        }
+        ExecuteMsg::RevealVote {
+            poll_id,
+            choice,
+            salt,
+        } => execute_reveal_vote(deps, env, info, poll_id, choice, salt),
+        ExecuteMsg::DepositCredits { poll_id } => execute_deposit_credits(deps, env, info, poll_id),
+        ExecuteMsg::PlaceBet { poll_id, option } => {
+            execute_place_bet(deps, env, info, poll_id, option)
+        }
+        ExecuteMsg::ResolvePoll { poll_id, outcome } => {
+            execute_resolve_poll(deps, env, info, poll_id, outcome)
+        }
+        ExecuteMsg::ClaimPayout { poll_id } => execute_claim_payout(deps, env, info, poll_id),
+        ExecuteMsg::FundRewards { poll_id } => execute_fund_rewards(deps, env, info, poll_id),
+        ExecuteMsg::ClaimRewards { poll_id } => execute_claim_rewards(deps, env, info, poll_id),
+        ExecuteMsg::StartRaffle {
+            poll_id,
+            winners,
+            prize,
+        } => execute_start_raffle(deps, env, info, poll_id, winners, prize),
+        ExecuteMsg::NoisReceive { callback } => execute_nois_receive(deps, env, info, callback),
+        ExecuteMsg::ClaimIncentives {} => execute_claim_incentives(deps, env, info),
+        ExecuteMsg::WithdrawIncentive { incentive_id } => {
+            execute_withdraw_incentive(deps, env, info, incentive_id)
+        }
+        ExecuteMsg::SweepRewards { poll_id, recipient } => {
+            execute_sweep_rewards(deps, env, info, poll_id, recipient)
+        }
+        ExecuteMsg::UpdateVote { poll_id, choice } => {
+            execute_update_vote(deps, env, info, poll_id, choice)
+        }
+        ExecuteMsg::RetractVote { poll_id } => execute_retract_vote(deps, env, info, poll_id),
+        ExecuteMsg::Unbond { amount } => execute_unbond(deps, env, info, amount),
+        ExecuteMsg::WithdrawFees { recipient } => execute_withdraw_fees(deps, env, info, recipient),
+        ExecuteMsg::UpdateConfig {
+            default_quorum,
+            default_threshold,
+            default_voting_period,
+            execution_delay,
+            veto_period,
+            max_multicall,
+        } => execute_update_config(
+            deps,
+            env,
+            info,
+            ConfigUpdate {
+                default_quorum,
+                default_threshold,
+                default_voting_period,
+                execution_delay,
+                veto_period,
+                max_multicall,
+            },
+        ),
+        ExecuteMsg::Pause {} => execute_set_paused(deps, env, info, true),
+        ExecuteMsg::Unpause {} => execute_set_paused(deps, env, info, false),
+    }
+}
+
+fn execute_create_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: CreatePollMsg,
+) -> Result<Response, ContractError> {
+    create_poll(deps, &env, &info.sender, Some(&info.funds), msg)
+}
+
+/// Validates and saves a new poll. `funds` are the coins sent with `CreatePoll`,
+/// and are `None` for instances the contract opens itself, which skip the
+/// creation checks, fees and deposits.
+pub(crate) fn create_poll(
+    deps: DepsMut,
+    env: &Env,
+    creator: &Addr,
+    funds: Option<&[Coin]>,
+    msg: CreatePollMsg,
+) -> Result<Response, ContractError> {
+    let CreatePollMsg {
+        question,
+        description,
+        tags,
+        link,
+        options,
+        option_details,
+        kind,
+        start,
+        expiration,
+        nft_gate,
+        commit_reveal,
+        veto_threshold,
+        quorum,
+        threshold,
+        voters,
+        merkle_root,
+        msgs,
+        hub,
+        voting_power,
+        recurrence,
+        bounty,
+        weight_decay,
+        weight_cap,
+        min_power,
+        turnout,
+        tie_break,
+        runoff_period,
+        opens_if_passed,
+        allow_write_ins,
+        allow_new_options,
+    } = msg;
+
+    let config = CONFIG.load(deps.storage)?;
+    if funds.is_some() {
+        ensure_not_blocked(deps.storage, creator)?;
+        if config.restrict_creation && !CREATORS.has(deps.storage, creator) {
+            return Err(ContractError::CreationNotAllowed {});
+        }
+    }
+    let question = normalize_question(&config, &question)?;
+    ensure_question_unused(deps.storage, &question)?;
+
+    let expiration = match (expiration, config.default_voting_period) {
+        (Some(expiration), _) => expiration,
+        (None, Some(period)) => voting_period_end(&env.block, start, period),
+        (None, None) => Expiration::Never {},
+    };
+    if expiration.is_expired(&env.block) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+    let starts_too_late = match (start, expiration) {
+        (Some(Scheduled::AtHeight(start)), Expiration::AtHeight(end)) => start >= end,
+        (Some(Scheduled::AtTime(start)), Expiration::AtTime(end)) => start >= end,
+        _ => false,
+    };
+    if starts_too_late {
+        return Err(ContractError::InvalidStart {});
+    }
+    let condition = opens_if_passed
+        .map(|parent| {
+            if start.is_some() || commit_reveal.is_some() {
+                return Err(ContractError::InvalidCondition {});
+            }
+            poll_condition(deps.storage, &env.block, parent, expiration)
+        })
+        .transpose()?;
+    let weight_decay = weight_decay
+        .map(|decay| {
+            let max = Decimal::from_ratio(MAX_DECAY_FACTOR, 1u64);
+            if [decay.initial, decay.closing]
+                .iter()
+                .any(|factor| factor.is_zero() || *factor > max)
+            {
+                return Err(ContractError::InvalidWeightDecay {});
+            }
+            let opens = match (start, expiration) {
+                (None, Expiration::AtHeight(_)) => Scheduled::AtHeight(env.block.height),
+                (None, Expiration::AtTime(_)) => Scheduled::AtTime(env.block.time),
+                (Some(Scheduled::AtHeight(start)), Expiration::AtHeight(_)) => {
+                    Scheduled::AtHeight(start)
+                }
+                (Some(Scheduled::AtTime(start)), Expiration::AtTime(_)) => Scheduled::AtTime(start),
+                _ => return Err(ContractError::InvalidWeightDecay {}),
+            };
+            Ok(WeightDecay {
+                initial: decay.initial,
+                closing: decay.closing,
+                opens,
+            })
+        })
+        .transpose()?;
+
+    let options = match (&kind, options) {
+        (PollKind::Petition { target, .. }, None) if !target.is_zero() => {
+            vec!["signatures".to_string()]
+        }
+        (PollKind::Petition { .. }, _) => return Err(ContractError::InvalidPetition {}),
+        (_, options) => {
+            let options = options.unwrap_or_else(|| vec!["yes".to_string(), "no".to_string()]);
+            validate_options(&options)?;
+            options
+        }
+    };
+    validate_option_details(options.len(), &option_details)?;
+    validate_runoff(&kind, &options, runoff_period)?;
+    validate_description(&description)?;
+    if link.as_ref().is_some_and(|link| link.len() > MAX_LINK_LEN) {
+        return Err(ContractError::FieldTooLong {
+            field: "link".to_string(),
+            max: MAX_LINK_LEN,
+        });
+    }
+    if tags.len() > MAX_TAGS {
+        return Err(ContractError::TooManyTags { max: MAX_TAGS });
+    }
+    for (i, tag) in tags.iter().enumerate() {
+        if tag.is_empty() || tag.len() > MAX_TAG_LEN || tags[..i].contains(tag) {
+            return Err(ContractError::InvalidTag { tag: tag.clone() });
+        }
+    }
+
+    if let Some(threshold) = veto_threshold {
+        if threshold.is_zero() || threshold > Decimal::one() {
+            return Err(ContractError::InvalidVetoThreshold {});
+        }
+    }
+    let quorum = quorum.or(config.default_quorum);
+    let threshold_set = threshold.is_some();
+    let threshold = threshold.or(config.default_threshold);
+    validate_threshold(threshold)?;
+    let weight_cap = weight_cap.or_else(|| config.weight_cap.clone());
+    validate_weight_cap(weight_cap.as_ref())?;
+
+    let voting_power = voting_power
+        .map(|source| validate_voting_power(deps.api, source))
+        .transpose()?;
+    let total_power = turnout
+        .map(|turnout| {
+            if turnout.is_zero() || turnout > Decimal::one() {
+                return Err(ContractError::InvalidTurnout {});
+            }
+            total_power(
+                deps.as_ref(),
+                &config,
+                voting_power.as_ref(),
+                env.block.height,
+            )
+        })
+        .transpose()?;
+    let nft_gate = nft_gate
+        .map(|gate| -> StdResult<_> {
+            Ok(NftGate {
+                collection: deps.api.addr_validate(&gate.collection)?,
+                weight_by_count: gate.weight_by_count,
+            })
+        })
+        .transpose()?;
+
+    if merkle_root
+        .as_ref()
+        .is_some_and(|root| root.len() != 32 || voters.is_some())
+    {
+        return Err(ContractError::InvalidMerkleRoot {});
+    }
+
+    if let Some(hub) = &hub {
+        if !CHANNELS.has(deps.storage, &hub.channel_id) {
+            return Err(ContractError::UnknownChannel {
+                channel_id: hub.channel_id.clone(),
+            });
+        }
+        if HUB_POLLS.has(deps.storage, (&hub.channel_id, hub.poll_id)) {
+            return Err(ContractError::HubPollLinked {
+                channel_id: hub.channel_id.clone(),
+                poll_id: hub.poll_id,
+            });
+        }
+    }
+
+    let commit_reveal = match commit_reveal {
+        Some(_) if expiration == Expiration::Never {} => {
+            return Err(ContractError::CommitRevealRequiresExpiration {});
+        }
+        Some(commit_reveal) => Some(CommitReveal {
+            reveal_expiration: (expiration + commit_reveal.reveal_window)?,
+            deposit: commit_reveal.deposit,
+        }),
+        None => None,
+    };
+
+    if let Some(recurrence) = &recurrence {
+        if recurrence.count == 0
+            || matches!(recurrence.interval, Duration::Height(0) | Duration::Time(0))
+        {
+            return Err(ContractError::InvalidRecurrence {});
+        }
+    }
+
+    let bounty = bounty
+        .map(|bounty| -> Result<_, ContractError> {
+            if bounty.amount.amount.is_zero() || funds.is_none() {
+                return Err(ContractError::InvalidBounty {});
+            }
+            Ok(Bounty {
+                creator: creator.clone(),
+                amount: bounty.amount,
+                recipient: bounty
+                    .recipient
+                    .map(|recipient| deps.api.addr_validate(&recipient))
+                    .transpose()?,
+            })
+        })
+        .transpose()?;
+
+    if let Some(funds) = funds {
+        let required = creation_funds(&config, bounty.as_ref())?;
+        if !same_coins(funds, &required) {
+            let required = required
+                .iter()
+                .map(|coin| coin.to_string())
+                .collect::<Vec<_>>();
+            return Err(ContractError::InvalidFunds {
+                required: required.join(","),
+            });
+        }
+        if let Some(fee) = &config.creation_fee {
+            FEES.update(deps.storage, &fee.denom, |collected| -> StdResult<_> {
+                Ok(collected.unwrap_or_default().checked_add(fee.amount)?)
+            })?;
+        }
+    }
+
+    let poll_id = POLL_COUNT.load(deps.storage)? + 1;
+    POLL_COUNT.save(deps.storage, &poll_id)?;
+    STATS.update(deps.storage, |mut stats| -> StdResult<_> {
+        stats.open_polls += 1;
+        Ok(stats)
+    })?;
+    if let (Some(_), Some(amount)) = (funds, &config.poll_deposit) {
+        DEPOSITS.save(
+            deps.storage,
+            poll_id,
+            &PollDeposit {
+                depositor: creator.clone(),
+                amount: amount.clone(),
+            },
+        )?;
+    }
+    if let Some(bounty) = &bounty {
+        BOUNTIES.save(deps.storage, poll_id, bounty)?;
+    }
+    let series = recurrence.map(|recurrence| PollSeries {
+        first: poll_id,
+        previous: None,
+        interval: recurrence.interval,
+        remaining: recurrence.count - 1,
+    });
+    if series.is_some() {
+        SERIES.save(deps.storage, (poll_id, poll_id), &Empty {})?;
+    }
+
+    let mut option_details = option_details.into_iter();
+    let poll = Poll {
+        id: poll_id,
+        creator: creator.clone(),
+        question,
+        description,
+        tags,
+        link,
+        start_height: env.block.height,
+        start,
+        options: options
+            .into_iter()
+            .map(|label| {
+                let details = option_details.next().unwrap_or_default();
+                PollOption {
+                    label,
+                    votes: Uint128::zero(),
+                    description: details.description,
+                    image: details.image,
+                }
+            })
+            .collect(),
+        kind,
+        abstain_votes: Uint128::zero(),
+        veto_votes: Uint128::zero(),
+        veto_threshold,
+        quorum,
+        threshold,
+        expiration,
+        status: PollStatus::Open,
+        nft_gate,
+        commit_reveal,
+        private: voters.is_some() || merkle_root.is_some(),
+        merkle_root,
+        msgs,
+        executable_at: None,
+        veto_deadline: None,
+        hub: hub.map(|hub| HubLink {
+            channel_id: hub.channel_id,
+            poll_id: hub.poll_id,
+            sequence: 0,
+            result: None,
+        }),
+        voting_power,
+        endorsements: 0,
+        series,
+        weight_decay,
+        weight_cap,
+        min_power,
+        turnout,
+        total_power,
+        tie_break,
+        tied: vec![],
+        tie_winner: None,
+        runoff_period,
+        runoff_of: None,
+        runoff: None,
+        condition,
+        allow_write_ins,
+        allow_new_options,
+    };
+    validate_tie_break(&config, &poll)?;
+    if poll.allow_write_ins {
+        validate_write_ins(&poll, threshold_set)?;
+    }
+    if poll.allow_new_options {
+        validate_new_options(&poll, threshold_set)?;
+    }
+
+    for voter in voters.unwrap_or_default() {
+        let voter = deps.api.addr_validate(&voter)?;
+        POLL_VOTERS.save(deps.storage, (poll_id, &voter), &Empty {})?;
+    }
+    polls().save(deps.storage, poll_id, &poll)?;
+    add_dependent(deps.storage, &poll)?;
+    record_poll_created(deps.storage, creator)?;
+    if let Some(hub) = &poll.hub {
+        HUB_POLLS.save(deps.storage, (&hub.channel_id, hub.poll_id), &poll_id)?;
+    }
+    for tag in &poll.tags {
+        POLL_TAGS.save(deps.storage, (tag, poll_id), &Empty {})?;
+    }
+
+    let hook = PollHookMsg::PollCreated {
+        poll_id,
+        creator: poll.creator.to_string(),
+    };
+    let event = Event::new("poll_created")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("question", poll.question)
+        .add_attribute("creator", poll.creator);
+
+    Ok(Response::new()
+        .add_submessages(hook_msgs(deps.storage, &hook)?)
+        .add_attribute("action", "create_poll")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_event(event))
+}
+
+fn execute_update_poll(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    question: Option<String>,
+    options: Option<Vec<String>>,
+    description: Option<String>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    if info.sender != poll.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+    ensure_open(&poll)?;
+    let committed = COMMITMENTS
+        .prefix(poll_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .next()
+        .is_some();
+    if committed || !total_votes(&poll)?.is_zero() {
+        return Err(ContractError::PollHasVotes { poll_id });
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    if let Some(question) = question {
+        let question = normalize_question(&config, &question)?;
+        if question != poll.question {
+            ensure_question_unused(deps.storage, &question)?;
+            poll.question = question;
+        }
+    }
+    if let Some(options) = options {
+        if matches!(
+            poll.kind,
+            PollKind::Petition { .. } | PollKind::Prediction { .. }
+        ) {
+            return Err(ContractError::OptionsFixed { poll_id });
+        }
+        validate_options(&options)?;
+        validate_runoff(&poll.kind, &options, poll.runoff_period)?;
+        // options whose label is unchanged keep their details
+        let option_details: Vec<_> = options
+            .iter()
+            .map(|label| {
+                poll.options
+                    .iter()
+                    .find(|option| option.label == *label)
+                    .map(|option| OptionDetails {
+                        description: option.description.clone(),
+                        image: option.image.clone(),
+                    })
+                    .unwrap_or_default()
+            })
+            .collect();
+        validate_option_details(options.len(), &option_details)?;
+        poll.options = options
+            .into_iter()
+            .zip(option_details)
+            .map(|(label, details)| PollOption {
+                label,
+                votes: Uint128::zero(),
+                description: details.description,
+                image: details.image,
+            })
+            .collect();
+        // the threshold cannot be updated and was checked at creation
+        validate_tie_break(&config, &poll)?;
+        if poll.allow_write_ins {
+            validate_write_ins(&poll, false)?;
+        }
+        if poll.allow_new_options {
+            validate_new_options(&poll, false)?;
+        }
+    }
+    if description.is_some() {
+        validate_description(&description)?;
+        poll.description = description;
+    }
+    polls().save(deps.storage, poll_id, &poll)?;
+
+    let event = Event::new("poll_updated")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("question", poll.question);
+    Ok(Response::new()
+        .add_attribute("action", "update_poll")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_event(event))
+}
+
+fn execute_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    choice: Choice,
+    proof: Option<Vec<Binary>>,
+    memo: Option<String>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    vote(deps, &env, &info.sender, poll_id, choice, proof, memo)
+}
+
+fn execute_vote_many(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    votes: Vec<VoteItem>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    if votes.is_empty() || votes.len() > MAX_BATCH_VOTES {
+        return Err(ContractError::InvalidBatchSize {
+            max: MAX_BATCH_VOTES,
+        });
+    }
+
+    let mut response = Response::new();
+    let mut poll_ids = vec![];
+    for VoteItem {
+        poll_id,
+        choice,
+        proof,
+        memo,
+    } in votes
+    {
+        let cast = vote(
+            deps.branch(),
+            &env,
+            &info.sender,
+            poll_id,
+            choice,
+            proof,
+            memo,
+        )?;
+        response = response
+            .add_submessages(cast.messages)
+            .add_events(cast.events);
+        poll_ids.push(poll_id.to_string());
+    }
+
+    Ok(response
+        .add_attribute("action", "vote_many")
+        .add_attribute("voter", info.sender)
+        .add_attribute("votes", poll_ids.len().to_string())
+        .add_attribute("poll_ids", poll_ids.join(",")))
+}
+
+fn execute_multicall(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msgs: Vec<ExecuteMsg>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    if msgs.is_empty() || msgs.len() > config.max_multicall as usize {
+        return Err(ContractError::InvalidMulticallSize {
+            max: config.max_multicall,
+        });
+    }
+
+    let count = msgs.len();
+    let mut response = Response::new();
+    for msg in msgs {
+        // their replies find the message they answer through a single stored
+        // item, which a second message of the same kind would overwrite
+        if matches!(
+            msg,
+            ExecuteMsg::Multicall { .. }
+                | ExecuteMsg::ExecutePoll { .. }
+                | ExecuteMsg::RegisterRemotePower {}
+                | ExecuteMsg::CreatePollContract(_)
+        ) {
+            return Err(ContractError::NotMulticallable {});
+        }
+        let inner = execute(deps.branch(), env.clone(), info.clone(), msg)?;
+        response = response
+            .add_submessages(inner.messages)
+            .add_attributes(inner.attributes)
+            .add_events(inner.events);
+    }
+
+    Ok(response
+        .add_attribute("action", "multicall")
+        .add_attribute("msgs", count.to_string()))
+}
+
+/// Checks `proof` if the poll has a merkle root and casts `voter`'s ballot with
+/// their current weight.
+pub(crate) fn vote(
+    deps: DepsMut,
+    env: &Env,
+    voter: &Addr,
+    poll_id: u64,
+    choice: Choice,
+    proof: Option<Vec<Binary>>,
+    memo: Option<String>,
+) -> Result<Response, ContractError> {
+    let poll = load_poll(deps.storage, poll_id)?;
+    if let (Some(root), Some(proof)) = (&poll.merkle_root, proof) {
+        if !verify_merkle_proof(root, voter, &proof) {
+            return Err(ContractError::InvalidMerkleProof {});
+        }
+        POLL_VOTERS.save(deps.storage, (poll_id, voter), &Empty {})?;
+    }
+    let weight = voter_weight(deps.as_ref(), &poll, voter)?;
+    cast_vote(deps, env, voter, poll_id, choice, weight, memo)
+}
+
+fn execute_commit_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    hash: Binary,
+) -> Result<Response, ContractError> {
+    let poll = load_poll(deps.storage, poll_id)?;
+    ensure_open(&poll)?;
+    let commit_reveal = poll
+        .commit_reveal
+        .as_ref()
+        .ok_or(ContractError::NotCommitReveal {})?;
+    if poll.expiration.is_expired(&env.block) {
+        return Err(ContractError::CommitPhaseOver {});
+    }
+    ensure_started(&poll, &env.block)?;
+    ensure_eligible(deps.storage, &poll, &info.sender)?;
+    if COMMITMENTS.has(deps.storage, (poll_id, &info.sender)) {
+        return Err(ContractError::AlreadyVoted { poll_id });
+    }
+    match &commit_reveal.deposit {
+        Some(deposit) if info.funds != vec![deposit.clone()] => {
+            return Err(ContractError::InvalidFunds {
+                required: deposit.to_string(),
+            });
+        }
+        Some(_) => {}
+        None => {
+            nonpayable(&info)?;
+        }
+    }
+
+    let weight = voter_weight(deps.as_ref(), &poll, &info.sender)?;
+    COMMITMENTS.save(
+        deps.storage,
+        (poll_id, &info.sender),
+        &Commitment { hash, weight },
+    )?;
+
+    let event = Event::new("vote_committed")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("voter", info.sender)
+        .add_attribute("weight", weight);
+
+    Ok(Response::new()
+        .add_attribute("action", "commit_vote")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_event(event))
+}
+
+fn execute_reveal_vote(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    choice: Choice,
+    salt: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    ensure_open(&poll)?;
+    let commit_reveal = poll
+        .commit_reveal
+        .clone()
+        .ok_or(ContractError::NotCommitReveal {})?;
+    if !poll.expiration.is_expired(&env.block)
+        || commit_reveal.reveal_expiration.is_expired(&env.block)
+    {
+        return Err(ContractError::NotRevealPhase {});
+    }
+
+    let commitment = COMMITMENTS
+        .may_load(deps.storage, (poll_id, &info.sender))?
+        .ok_or(ContractError::NoCommitment {})?;
+    if commitment.hash != commitment_hash(&info.sender, &choice, &salt)? {
+        return Err(ContractError::CommitmentMismatch {});
+    }
+    COMMITMENTS.remove(deps.storage, (poll_id, &info.sender));
+
+    let ballot = record_ballot(
+        deps.branch(),
+        &env,
+        &mut poll,
+        &info.sender,
+        choice,
+        commitment.weight,
+        None,
+    )?;
+
+    let hook = PollHookMsg::VoteCast {
+        poll_id,
+        voter: info.sender.to_string(),
+        choice: ballot.choice.clone(),
+        weight: ballot.weight,
+    };
+    let mut response = Response::new()
+        .add_submessages(receipt_msg(
+            deps.storage,
+            poll_id,
+            &info.sender,
+            &ballot.choice,
+        )?)
+        .add_submessages(hook_msgs(deps.storage, &hook)?)
+        .add_attribute("action", "reveal_vote")
+        .add_attribute("weight", ballot.weight)
+        .add_event(ballot_event(
+            "vote_revealed",
+            poll_id,
+            &info.sender,
+            &ballot,
+        ));
+    if let Some(deposit) = commit_reveal.deposit {
+        response = response.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![deposit],
+        });
+    }
+    Ok(response)
+}
+
+/// Computes the commitment expected by `CommitVote` for a later `RevealVote`.
+pub fn commitment_hash(voter: &Addr, choice: &Choice, salt: &str) -> StdResult<Binary> {
+    let mut hasher = Sha256::new();
+    hasher.update(voter.as_bytes());
+    hasher.update(to_vec(choice)?);
+    hasher.update(salt.as_bytes());
+    Ok(Binary::from(hasher.finalize().as_slice()))
+}
+
+/// Checks that `voter` is a leaf of the Merkle tree with the given `root`. Leaves
+/// are `sha256(voter)` and each parent hashes its two children in ascending order.
+pub fn verify_merkle_proof(root: &Binary, voter: &Addr, proof: &[Binary]) -> bool {
+    let leaf: [u8; 32] = Sha256::digest(voter.as_bytes()).into();
+    let hash = proof.iter().try_fold(leaf, |hash, sibling| {
+        let sibling: [u8; 32] = sibling.as_slice().try_into().ok()?;
+        let (first, second) = if hash <= sibling {
+            (hash, sibling)
+        } else {
+            (sibling, hash)
+        };
+        let mut hasher = Sha256::new();
+        hasher.update(first);
+        hasher.update(second);
+        Some(hasher.finalize().into())
+    });
+    hash.is_some_and(|hash| hash.as_slice() == root.as_slice())
+}
+
+fn execute_deposit_credits(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    let poll = load_poll(deps.storage, poll_id)?;
+    ensure_open(&poll)?;
+    if voting_end(&poll).is_expired(&env.block) {
+        return Err(ContractError::PollExpired {});
+    }
+    let denom = match &poll.kind {
+        PollKind::Quadratic {
+            credit_denom: Some(denom),
+            ..
+        } => denom,
+        _ => return Err(ContractError::NoCreditSales {}),
+    };
+
+    let amount = must_pay(&info, denom)?;
+    let bought = u64::try_from(amount.u128()).map_err(|_| ContractError::DepositTooLarge {})?;
+
+    let mut account = CREDITS
+        .may_load(deps.storage, (poll_id, &info.sender))?
+        .unwrap_or_default();
+    account.deposited = Uint64::new(account.deposited)
+        .checked_add(Uint64::new(bought))?
+        .u64();
+    CREDITS.save(deps.storage, (poll_id, &info.sender), &account)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "deposit_credits")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("voter", info.sender)
+        .add_attribute("credits", bought.to_string()))
+}
+
+fn execute_receive(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    wrapper: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    if config.cw20_token.as_ref() != Some(&info.sender) {
+        return Err(ContractError::UnsupportedToken {});
+    }
+
+    let voter = deps.api.addr_validate(&wrapper.sender)?;
+    match from_binary(&wrapper.msg)? {
+        ReceiveMsg::Vote { .. } if config.snapshot_power => Err(ContractError::BondToVote {}),
+        ReceiveMsg::Vote { poll_id, choice } => {
+            let mut response = cast_vote(
+                deps.branch(),
+                &env,
+                &voter,
+                poll_id,
+                choice,
+                wrapper.amount,
+                None,
+            )?;
+            if config.escrow_cw20 {
+                ESCROWS.save(deps.storage, (poll_id, &voter), &wrapper.amount)?;
+            } else {
+                response =
+                    response.add_message(cw20_transfer(&info.sender, &voter, wrapper.amount)?);
+            }
+            Ok(response)
+        }
+        ReceiveMsg::FundRewards { poll_id } => {
+            let asset = RewardAsset::Cw20 {
+                address: info.sender,
+            };
+            fund_rewards(deps.storage, &voter, poll_id, asset, wrapper.amount)
+        }
+        ReceiveMsg::CreateIncentive {
+            target,
+            per_poll,
+            expiration,
+        } => create_incentive(
+            deps.storage,
+            &env.block,
+            &voter,
+            wrapper.amount,
+            target,
+            per_poll,
+            expiration,
+        ),
+        ReceiveMsg::Bond {} => {
+            VOTING_POWER.update(
+                deps.storage,
+                &voter,
+                env.block.height,
+                |power| -> StdResult<_> {
+                    Ok(power.unwrap_or_default().checked_add(wrapper.amount)?)
+                },
+            )?;
+            TOTAL_POWER.update(deps.storage, env.block.height, |total| -> StdResult<_> {
+                Ok(total.unwrap_or_default().checked_add(wrapper.amount)?)
+            })?;
+            Ok(Response::new()
+                .add_attribute("action", "bond")
+                .add_attribute("voter", voter)
+                .add_attribute("amount", wrapper.amount))
+        }
+    }
+}
+
+fn execute_unbond(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    let token = config
+        .cw20_token
+        .ok_or(ContractError::NoTokenConfigured {})?;
+
+    VOTING_POWER.update(
+        deps.storage,
+        &info.sender,
+        env.block.height,
+        |power| -> Result<_, ContractError> {
+            power
+                .unwrap_or_default()
+                .checked_sub(amount)
+                .map_err(|_| ContractError::InsufficientBond {})
+        },
+    )?;
+    TOTAL_POWER.update(deps.storage, env.block.height, |total| -> StdResult<_> {
+        Ok(total.unwrap_or_default().checked_sub(amount)?)
+    })?;
+
+    Ok(Response::new()
+        .add_message(cw20_transfer(&token, &info.sender, amount)?)
+        .add_attribute("action", "unbond")
+        .add_attribute("voter", info.sender)
+        .add_attribute("amount", amount))
+}
+
+fn execute_withdraw_tokens(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let poll = load_poll(deps.storage, poll_id)?;
+    if poll.status == PollStatus::Open {
+        return Err(ContractError::PollStillOpen {});
+    }
+
+    let amount = ESCROWS
+        .may_load(deps.storage, (poll_id, &info.sender))?
+        .ok_or(ContractError::NothingToWithdraw {})?;
+    ESCROWS.remove(deps.storage, (poll_id, &info.sender));
+
+    let config = CONFIG.load(deps.storage)?;
+    let token = config
+        .cw20_token
+        .ok_or(ContractError::NoTokenConfigured {})?;
+
+    Ok(Response::new()
+        .add_message(cw20_transfer(&token, &info.sender, amount)?)
+        .add_attribute("action", "withdraw_tokens")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("voter", info.sender)
+        .add_attribute("amount", amount))
+}
+
+pub(crate) fn cast_vote(
+    mut deps: DepsMut,
+    env: &Env,
+    voter: &Addr,
+    poll_id: u64,
+    choice: Choice,
+    weight: Uint128,
+    memo: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut poll = load_poll(deps.storage, poll_id)?;
+
+    ensure_open(&poll)?;
+    if poll.commit_reveal.is_some() {
+        return Err(ContractError::CommitRevealRequired {});
+    }
+    if poll.expiration.is_expired(&env.block) {
+        return Err(ContractError::PollExpired {});
+    }
+    ensure_started(&poll, &env.block)?;
+
+    let ballot = record_ballot(deps.branch(), env, &mut poll, voter, choice, weight, memo)?;
+    let hook = PollHookMsg::VoteCast {
+        poll_id,
+        voter: voter.to_string(),
+        choice: ballot.choice.clone(),
+        weight: ballot.weight,
+    };
+    let mut response = Response::new()
+        .add_submessages(receipt_msg(deps.storage, poll_id, voter, &ballot.choice)?)
+        .add_submessages(hook_msgs(deps.storage, &hook)?)
+        .add_attribute("action", "vote")
+        .add_attribute("weight", ballot.weight)
+        .add_event(ballot_event("vote_cast", poll_id, voter, &ballot));
+    if petition_reached(&poll) {
+        let event = Event::new("petition_threshold_reached")
+            .add_attribute("poll_id", poll_id.to_string())
+            .add_attribute("signatures", poll.options[0].votes);
+        let closed = close_poll(deps, env, poll, voter, &[])?;
+        response = response
+            .add_submessages(closed.messages)
+            .add_attributes(closed.attributes)
+            .add_event(event)
+            .add_events(closed.events);
+    }
+    Ok(response)
+}
+
+/// Event describing `voter`'s ballot, emitted as `wasm-<ty>`.
+fn ballot_event(ty: &str, poll_id: u64, voter: &Addr, ballot: &Ballot) -> Event {
+    let event = Event::new(ty)
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("voter", voter)
+        .add_attribute("choice", ballot.choice.to_string())
+        .add_attribute("weight", ballot.weight);
+    match &ballot.memo {
+        Some(memo) => event.add_attribute("memo", memo),
+        None => event,
+    }
+}
+
+/// Validates and tallies a ballot on an open poll, returning the ballot saved.
+fn record_ballot(
+    deps: DepsMut,
+    env: &Env,
+    poll: &mut Poll,
+    voter: &Addr,
+    choice: Choice,
+    mut weight: Uint128,
+    memo: Option<String>,
+) -> Result<Ballot, ContractError> {
+    ensure_eligible(deps.storage, poll, voter)?;
+    if memo.as_ref().is_some_and(|memo| memo.len() > MAX_MEMO_LEN) {
+        return Err(ContractError::FieldTooLong {
+            field: "memo".to_string(),
+            max: MAX_MEMO_LEN,
+        });
+    }
+    if BALLOTS.has(deps.storage, (poll.id, voter)) {
+        return Err(ContractError::AlreadyVoted { poll_id: poll.id });
+    }
+
+    if let Some(gate) = &poll.nft_gate {
+        let count = nft_count(deps.as_ref(), &gate.collection, voter)?;
+        if count == 0 {
+            return Err(ContractError::NftGateNotMet {});
+        }
+        if gate.weight_by_count {
+            weight = Uint128::from(count);
+        }
+    }
+    if let Some(required) = poll.min_power {
+        if weight < required {
+            return Err(ContractError::InsufficientVotingPower {
+                required,
+                actual: weight,
+            });
+        }
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    weight = apply_bonus(deps.storage, &config, voter, weight)?;
+    weight = apply_streak_bonus(deps.storage, &config, voter, weight)?;
+    weight = decayed_weight(poll, &env.block, weight)?;
+    weight = apply_weight_cap(deps.as_ref(), &config, poll, weight)?;
+    if weight.is_zero() {
+        return Err(ContractError::NoVotingPower {});
+    }
+    add_vote(poll, &choice, weight)?;
+    charge_credits(deps.storage, poll, voter, &choice)?;
+    record_vote(deps.storage, voter)?;
+
+    let ballot = Ballot {
+        choice,
+        weight,
+        height: env.block.height,
+        memo,
+    };
+    BALLOTS.save(deps.storage, (poll.id, voter), &ballot)?;
+    let first_vote = !VOTERS.has(deps.storage, voter);
+    if first_vote {
+        VOTERS.save(deps.storage, voter, &Empty {})?;
+    }
+    STATS.update(deps.storage, |mut stats| -> StdResult<_> {
+        stats.total_votes += 1;
+        stats.unique_voters += u64::from(first_vote);
+        Ok(stats)
+    })?;
+    polls().save(deps.storage, poll.id, poll)?;
+    Ok(ballot)
+}
+
+fn execute_update_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    choice: Choice,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    ensure_amendable(&poll, &env)?;
+    ensure_eligible(deps.storage, &poll, &info.sender)?;
+
+    let mut ballot = BALLOTS
+        .may_load(deps.storage, (poll_id, &info.sender))?
+        .ok_or(ContractError::NoVote { poll_id })?;
+
+    remove_vote(&mut poll, &ballot.choice, ballot.weight)?;
+    refund_credits(deps.storage, &poll, &info.sender, &ballot.choice)?;
+    add_vote(&mut poll, &choice, ballot.weight)?;
+    charge_credits(deps.storage, &poll, &info.sender, &choice)?;
+
+    let previous_choice = std::mem::replace(&mut ballot.choice, choice);
+    ballot.height = env.block.height;
+    BALLOTS.save(deps.storage, (poll_id, &info.sender), &ballot)?;
+    polls().save(deps.storage, poll_id, &poll)?;
+
+    let event = ballot_event("vote_updated", poll_id, &info.sender, &ballot)
+        .add_attribute("previous_choice", previous_choice.to_string());
+
+    Ok(Response::new()
+        .add_attribute("action", "update_vote")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("previous_choice", previous_choice.to_string())
+        .add_attribute("new_choice", ballot.choice.to_string())
+        .add_event(event))
+}
+
+fn execute_retract_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    ensure_amendable(&poll, &env)?;
+
+    let ballot = BALLOTS
+        .may_load(deps.storage, (poll_id, &info.sender))?
+        .ok_or(ContractError::NoVote { poll_id })?;
+
+    remove_vote(&mut poll, &ballot.choice, ballot.weight)?;
+    refund_credits(deps.storage, &poll, &info.sender, &ballot.choice)?;
+    forget_vote(deps.storage, &info.sender)?;
+    BALLOTS.remove(deps.storage, (poll_id, &info.sender));
+    polls().save(deps.storage, poll_id, &poll)?;
+    STATS.update(deps.storage, |mut stats| -> StdResult<_> {
+        stats.total_votes = Uint64::new(stats.total_votes)
+            .checked_sub(Uint64::one())?
+            .u64();
+        Ok(stats)
+    })?;
+
+    let mut response = Response::new()
+        .add_attribute("action", "retract_vote")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("previous_choice", ballot.choice.to_string())
+        .add_event(ballot_event(
+            "vote_retracted",
+            poll_id,
+            &info.sender,
+            &ballot,
+        ));
+
+    if let Some(amount) = ESCROWS.may_load(deps.storage, (poll_id, &info.sender))? {
+        ESCROWS.remove(deps.storage, (poll_id, &info.sender));
+        if let Some(token) = CONFIG.load(deps.storage)?.cw20_token {
+            response = response.add_message(cw20_transfer(&token, &info.sender, amount)?);
+        }
+    }
+    Ok(response)
+}
+
+/// Ballots can only be changed while the poll accepts plain votes.
+fn ensure_amendable(poll: &Poll, env: &Env) -> Result<(), ContractError> {
+    ensure_open(poll)?;
+    if poll.commit_reveal.is_some() {
+        return Err(ContractError::RevealedVoteFinal {});
+    }
+    if poll.expiration.is_expired(&env.block) {
+        return Err(ContractError::PollExpired {});
+    }
+    Ok(())
+}
+
+fn charge_credits(
+    storage: &mut dyn Storage,
+    poll: &Poll,
+    voter: &Addr,
+    choice: &Choice,
+) -> Result<(), ContractError> {
+    if let (PollKind::Quadratic { credits, .. }, Choice::Allocation(allocations)) =
+        (&poll.kind, choice)
+    {
+        let mut account = CREDITS
+            .may_load(storage, (poll.id, voter))?
+            .unwrap_or_default();
+        let cost = Uint64::new(quadratic_cost(allocations)?);
+        let available = Uint64::new(*credits)
+            .checked_add(Uint64::new(account.deposited))?
+            .checked_sub(Uint64::new(account.spent))?;
+        if cost > available {
+            return Err(ContractError::InsufficientCredits { cost: cost.u64() });
+        }
+        account.spent = Uint64::new(account.spent).checked_add(cost)?.u64();
+        CREDITS.save(storage, (poll.id, voter), &account)?;
+    }
+    Ok(())
+}
+
+fn refund_credits(
+    storage: &mut dyn Storage,
+    poll: &Poll,
+    voter: &Addr,
+    choice: &Choice,
+) -> Result<(), ContractError> {
+    if let Choice::Allocation(allocations) = choice {
+        let mut account = CREDITS
+            .may_load(storage, (poll.id, voter))?
+            .unwrap_or_default();
+        account.spent = Uint64::new(account.spent)
+            .checked_sub(Uint64::new(quadratic_cost(allocations)?))?
+            .u64();
+        CREDITS.save(storage, (poll.id, voter), &account)?;
+    }
+    Ok(())
+}
+
+fn execute_close_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    let poll = load_poll(deps.storage, poll_id)?;
+
+    ensure_open(&poll)?;
+    if poll
+        .condition
+        .as_ref()
+        .is_some_and(|condition| !condition.met)
+    {
+        return Err(ContractError::PollNotStarted { poll_id });
+    }
+    let config = CONFIG.load(deps.storage)?;
+    if !voting_end(&poll).is_expired(&env.block)
+        && ensure_role(
+            deps.storage,
+            &config,
+            &info.sender,
+            &[Role::Owner, Role::Moderator],
+        )
+        .is_err()
+    {
+        return Err(ContractError::PollNotExpired {});
+    }
+
+    let closed = close_poll(deps, &env, poll, &info.sender, &info.funds)?;
+    Ok(Response::new()
+        .add_attribute("action", "close_poll")
+        .add_submessages(closed.messages)
+        .add_attributes(closed.attributes)
+        .add_events(closed.events))
+}
+
+/// Closes an open poll, unless its tie-break keeps it open, see `TieBreak`.
+/// `funds` pay for the Nois draw of a tie, and must be empty otherwise.
+fn close_poll(
+    deps: DepsMut,
+    env: &Env,
+    mut poll: Poll,
+    closed_by: &Addr,
+    funds: &[Coin],
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if let PollKind::Conviction { max_blocks } = poll.kind {
+        tally_conviction(deps.storage, &env.block, &mut poll, max_blocks)?;
+    }
+    // a runoff settles the tie between the leading options
+    let tied = match runoff_options(&poll)? {
+        Some(_) => vec![],
+        None => tied_options(&poll)?,
+    };
+    let required = match &config.nois_fee {
+        Some(fee) if !tied.is_empty() && poll.tie_break == TieBreak::RandomViaNois => {
+            vec![fee.clone()]
+        }
+        _ => vec![],
+    };
+    if !same_coins(funds, &required) {
+        if required.is_empty() {
+            return Err(PaymentError::NonPayable {}.into());
+        }
+        return Err(ContractError::InvalidFunds {
+            required: required[0].to_string(),
+        });
+    }
+    if !tied.is_empty() {
+        match poll.tie_break {
+            TieBreak::Reject => {}
+            TieBreak::FavorYes => {
+                poll.tie_winner = option_index(&poll, &Choice::Yes).map(|index| index as u32)
+            }
+            TieBreak::ExtendDeadline { by } if poll.tied.is_empty() => {
+                return extend_deadline(deps.storage, &env.block, poll.id, tied, by);
+            }
+            TieBreak::ExtendDeadline { .. } => {}
+            TieBreak::RandomViaNois => {
+                return request_draw(deps.storage, &config, poll, tied, required)
+            }
+        }
+    }
+    finalize_poll(deps, env, poll, closed_by)
+}
+
+/// Records the outcome of a poll whose tie, if any, was broken, refunds its
+/// deposit and opens the next instance of its series.
+pub(crate) fn finalize_poll(
+    mut deps: DepsMut,
+    env: &Env,
+    mut poll: Poll,
+    closed_by: &Addr,
+) -> Result<Response, ContractError> {
+    let poll_id = poll.id;
+    let config = CONFIG.load(deps.storage)?;
+    let winner_index = if quorum_reached(&poll)? {
+        winning_option(deps.storage, &poll)?
+    } else {
+        None
+    };
+    poll.status = if turnout_reached(&poll)? {
+        binary_outcome(&poll)?.unwrap_or(PollStatus::Closed)
+    } else {
+        PollStatus::QuorumNotMet
+    };
+    if poll.status == PollStatus::Passed {
+        poll.executable_at = config.execution_delay.map(|delay| delay.after(&env.block));
+        poll.veto_deadline = config.veto_period.map(|period| period.after(&env.block));
+        record_poll_won(deps.storage, &poll.creator)?;
+    }
+    STATS.update(deps.storage, |mut stats| -> StdResult<_> {
+        stats.open_polls = Uint64::new(stats.open_polls)
+            .checked_sub(Uint64::one())?
+            .u64();
+        Ok(stats)
+    })?;
+    polls().save(deps.storage, poll_id, &poll)?;
+    record_close(deps.storage, poll_id)?;
+    collect_credit_payments(deps.storage, &poll)?;
+
+    let winner = winner_index.map(|index| poll.options[index].label.clone());
+    let hook = PollHookMsg::PollClosed {
+        poll_id,
+        status: poll.status,
+        winner: winner.clone(),
+    };
+    let mut event = Event::new("poll_closed")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("question", poll.question.clone())
+        .add_attribute("closed_by", closed_by)
+        .add_attribute("status", poll.status.to_string());
+    if let Some(winner) = &winner {
+        event = event.add_attribute("winner", winner);
+    }
+
+    let mut response = Response::new()
+        .add_messages(finalize_packets(deps.storage, env, &poll, winner)?)
+        .add_submessages(hook_msgs(deps.storage, &hook)?)
+        .add_event(event);
+    if let Some(refund) = refund_poll_deposit(deps.storage, poll_id)? {
+        response = response.add_message(refund);
+    }
+    response = response.add_messages(pay_bounty(deps.storage, &poll, winner_index)?);
+    open_reward_claims(deps.storage, &env.block, &poll)?;
+    accrue_incentives(deps.storage, &env.block, &poll)?;
+    let dependents = settle_dependents(deps.storage, &env.block, &poll)?;
+    response = response
+        .add_submessages(dependents.messages)
+        .add_events(dependents.events);
+    if let (Some(options), Some(period)) = (runoff_options(&poll)?, poll.runoff_period) {
+        let (runoff_id, runoff) = open_runoff(deps.branch(), env, &poll, options, period)?;
+        response = response
+            .add_submessages(runoff.messages)
+            .add_events(runoff.events)
+            .add_attribute("runoff_poll_id", runoff_id.to_string());
+    }
+    if let Some(series) = poll.series.as_ref().filter(|series| series.remaining > 0) {
+        let (next_id, next) = open_next_instance(deps.branch(), env, &poll, series)?;
+        response = response
+            .add_submessages(next.messages)
+            .add_events(next.events)
+            .add_attribute("next_poll_id", next_id.to_string());
+    }
+    Ok(response)
+}
+
+fn execute_execute_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    if poll.status != PollStatus::Passed {
+        return Err(ContractError::PollNotPassed { poll_id });
+    }
+    if let Some(veto_deadline) = poll.veto_deadline {
+        if !veto_deadline.is_expired(&env.block) {
+            return Err(ContractError::VetoPeriodOpen {
+                poll_id,
+                veto_deadline,
+            });
+        }
+    }
+    if let Some(executable_at) = poll.executable_at {
+        if !executable_at.is_expired(&env.block) {
+            return Err(ContractError::TimelockNotExpired {
+                poll_id,
+                executable_at,
+            });
+        }
+    }
+    if poll.msgs.is_empty() {
+        return Err(ContractError::NothingToExecute { poll_id });
+    }
+
+    poll.status = PollStatus::Executed;
+    polls().save(deps.storage, poll_id, &poll)?;
+    EXECUTING_POLL.save(deps.storage, &poll_id)?;
+
+    let event = Event::new("poll_executed")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("executed_by", info.sender)
+        .add_attribute("msgs", poll.msgs.len().to_string());
+    // a failing message is reported to `reply` instead of aborting the others
+    let msgs = poll
+        .msgs
+        .into_iter()
+        .map(|msg| SubMsg::reply_on_error(msg, EXECUTE_POLL_REPLY_ID));
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_attribute("action", "execute_poll")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_event(event))
+}
+
+fn execute_sync_tally(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    let mut hub = poll.hub.clone().ok_or(ContractError::NoHub { poll_id })?;
+    if hub.result.is_some() {
+        return Err(ContractError::PollClosed { poll_id });
+    }
+    hub.sequence += 1;
+    poll.hub = Some(hub.clone());
+    polls().save(deps.storage, poll_id, &poll)?;
+
+    Ok(Response::new()
+        .add_message(tally_packet(&env, &poll, &hub)?)
+        .add_attribute("action", "sync_tally")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("sequence", hub.sequence.to_string()))
+}
+
+fn execute_register_remote_power(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    ensure_not_blocked(deps.storage, &info.sender)?;
+    let config = CONFIG.load(deps.storage)?;
+    let remote_power = config.remote_power.ok_or(ContractError::NoRemotePower {})?;
+    if REMOTE_POWER.has(deps.storage, &info.sender) {
+        return Err(ContractError::RemotePowerRegistered {});
+    }
+    let account = deps.api.addr_canonicalize(info.sender.as_str())?;
+    REGISTERING_VOTER.save(deps.storage, &info.sender)?;
+
+    let msg = register_query_msg(&env.contract.address, &remote_power, &account);
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(msg, REGISTER_QUERY_REPLY_ID))
+        .add_attribute("action", "register_remote_power")
+        .add_attribute("voter", info.sender))
+}
+
+fn execute_clone_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    overrides: PollOverrides,
+) -> Result<Response, ContractError> {
+    let poll = load_poll(deps.storage, poll_id)?;
+    // the question is cut short for the number to fit
+    let suffix = format!(" #{}", POLL_COUNT.load(deps.storage)? + 1);
+    let max_len = CONFIG.load(deps.storage)?.question_max_len as usize;
+    let mut question = poll.question.clone();
+    if question.len() + suffix.len() > max_len {
+        let end = (0..=max_len.saturating_sub(suffix.len()))
+            .rev()
+            .find(|end| question.is_char_boundary(*end))
+            .unwrap_or_default();
+        question.truncate(end);
+    }
+    let question = format!("{}{}", question.trim_end(), suffix);
+    let mut msg = poll_msg(deps.storage, poll)?;
+    msg.question = overrides.question.unwrap_or(question);
+    if overrides.description.is_some() {
+        msg.description = overrides.description;
+    }
+    if let Some(tags) = overrides.tags {
+        msg.tags = tags;
+    }
+    if overrides.options.is_some() {
+        msg.options = overrides.options;
+        msg.option_details = vec![];
+    }
+    msg.start = overrides.start;
+    msg.expiration = overrides.expiration;
+    msg.quorum = overrides.quorum.or(msg.quorum);
+    msg.threshold = overrides.threshold.or(msg.threshold);
+    Ok(
+        execute_create_poll(deps, env, info, msg)?
+            .add_attribute("cloned_from", poll_id.to_string()),
+    )
+}
+
+/// A `CreatePollMsg` with the settings of `poll`, leaving out its voting window,
+/// hub link and recurrence.
+pub(crate) fn poll_msg(storage: &dyn Storage, poll: Poll) -> StdResult<CreatePollMsg> {
+    // voters of merkle polls are only listed once they prove membership
+    let voters = if poll.private && poll.merkle_root.is_none() {
+        let voters = POLL_VOTERS
+            .prefix(poll.id)
+            .keys(storage, None, None, Order::Ascending)
+            .map(|voter| voter.map(String::from))
+            .collect::<StdResult<Vec<_>>>()?;
+        Some(voters)
+    } else {
+        None
+    };
+    let commit_reveal = poll
+        .commit_reveal
+        .map(|commit_reveal| -> StdResult<_> {
+            Ok(CommitRevealMsg {
+                reveal_window: reveal_window(&poll.expiration, &commit_reveal.reveal_expiration)?,
+                deposit: commit_reveal.deposit,
+            })
+        })
+        .transpose()?;
+
+    let option_details = if poll
+        .options
+        .iter()
+        .any(|option| option.description.is_some() || option.image.is_some())
+    {
+        poll.options
+            .iter()
+            .map(|option| OptionDetails {
+                description: option.description.clone(),
+                image: option.image.clone(),
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+
+    Ok(CreatePollMsg {
+        question: poll.question,
+        description: poll.description,
+        tags: poll.tags,
+        link: poll.link,
+        option_details,
+        options: match poll.kind {
+            PollKind::Petition { .. } => None,
+            _ => Some(
+                poll.options
+                    .into_iter()
+                    .map(|option| option.label)
+                    .collect(),
+            ),
+        },
+        kind: poll.kind,
+        start: None,
+        expiration: None,
+        nft_gate: poll.nft_gate.map(|gate| NftGateMsg {
+            collection: gate.collection.into_string(),
+            weight_by_count: gate.weight_by_count,
+        }),
+        commit_reveal,
+        veto_threshold: poll.veto_threshold,
+        quorum: poll.quorum,
+        threshold: poll.threshold,
+        voters,
+        merkle_root: poll.merkle_root,
+        msgs: poll.msgs,
+        hub: None,
+        voting_power: poll.voting_power.map(VotingPowerSourceMsg::from),
+        recurrence: None,
+        bounty: None,
+        weight_decay: poll.weight_decay.map(|decay| WeightDecayMsg {
+            initial: decay.initial,
+            closing: decay.closing,
+        }),
+        weight_cap: poll.weight_cap,
+        min_power: poll.min_power,
+        turnout: poll.turnout,
+        tie_break: poll.tie_break,
+        runoff_period: poll.runoff_period,
+        opens_if_passed: None,
+        allow_write_ins: poll.allow_write_ins,
+        allow_new_options: poll.allow_new_options,
+    })
+}
+
+/// Opens the instance of a recurring poll that follows `poll`, on behalf of its
+/// creator.
+fn open_next_instance(
+    mut deps: DepsMut,
+    env: &Env,
+    poll: &Poll,
+    series: &PollSeries,
+) -> Result<(u64, Response), ContractError> {
+    let first_question = match polls().may_load(deps.storage, series.first)? {
+        Some(first) => first.question,
+        None => poll.question.clone(),
+    };
+    let next_id = POLL_COUNT.load(deps.storage)? + 1;
+    let mut msg = poll_msg(deps.storage, poll.clone())?;
+    msg.question = format!("{} #{}", first_question, next_id);
+    msg.expiration = Some(series.interval.after(&env.block));
+    let response = create_poll(deps.branch(), env, &poll.creator, None, msg)?;
+
+    let mut next = load_poll(deps.storage, next_id)?;
+    next.series = Some(PollSeries {
+        first: series.first,
+        previous: Some(poll.id),
+        interval: series.interval,
+        remaining: series.remaining - 1,
+    });
+    polls().save(deps.storage, next_id, &next)?;
+    SERIES.save(deps.storage, (series.first, next_id), &Empty {})?;
+    Ok((next_id, response))
+}
+
+/// Length of the reveal phase of a commit-reveal poll ending at `expiration`.
+fn reveal_window(expiration: &Expiration, reveal_expiration: &Expiration) -> StdResult<Duration> {
+    match (expiration, reveal_expiration) {
+        (Expiration::AtHeight(end), Expiration::AtHeight(reveal_end)) => {
+            Ok(Duration::Height(reveal_end - end))
+        }
+        (Expiration::AtTime(end), Expiration::AtTime(reveal_end)) => {
+            Ok(Duration::Time(reveal_end.seconds() - end.seconds()))
+        }
+        _ => Err(StdError::generic_err(
+            "Reveal phase does not match expiration",
+        )),
+    }
+}
+
+fn execute_endorse(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ensure_not_blocked(deps.storage, &info.sender)?;
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    ensure_open(&poll)?;
+    if ENDORSEMENTS.has(deps.storage, (poll_id, &info.sender)) {
+        return Err(ContractError::AlreadyEndorsed { poll_id });
+    }
+    ENDORSEMENTS.save(deps.storage, (poll_id, &info.sender), &Empty {})?;
+    poll.endorsements += 1;
+    polls().save(deps.storage, poll_id, &poll)?;
+
+    let event = Event::new("poll_endorsed")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("endorser", info.sender)
+        .add_attribute("endorsements", poll.endorsements.to_string());
+    Ok(Response::new()
+        .add_attribute("action", "endorse")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_event(event))
+}
+
+fn execute_veto_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(
+        deps.storage,
+        &config,
+        &info.sender,
+        &[Role::Owner, Role::VetoCouncil],
+    )?;
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    if poll.status != PollStatus::Passed {
+        return Err(ContractError::PollNotPassed { poll_id });
+    }
+    if poll
+        .veto_deadline
+        .is_none_or(|deadline| deadline.is_expired(&env.block))
+    {
+        return Err(ContractError::VetoPeriodOver { poll_id });
+    }
+
+    poll.status = PollStatus::Vetoed;
+    polls().save(deps.storage, poll_id, &poll)?;
+
+    let event = Event::new("poll_vetoed")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("question", poll.question)
+        .add_attribute("vetoed_by", info.sender);
+    Ok(Response::new()
+        .add_attribute("action", "veto_poll")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_event(event))
+}
+
+fn execute_cancel_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let poll = load_poll(deps.storage, poll_id)?;
+    ensure_open(&poll)?;
+    if info.sender != poll.creator {
+        let config = CONFIG.load(deps.storage)?;
+        ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+    }
+
+    let cancelled = cancel_poll(deps.storage, &env.block, poll, info.sender.as_str())?;
+    Ok(cancelled
+        .add_attribute("action", "cancel_poll")
+        .add_attribute("poll_id", poll_id.to_string()))
+}
+
+/// Calls off an open poll, refunding what was escrowed for it, along with the
+/// polls waiting on it to pass.
+pub(crate) fn cancel_poll(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    mut poll: Poll,
+    cancelled_by: &str,
+) -> Result<Response, ContractError> {
+    let poll_id = poll.id;
+    poll.status = PollStatus::Cancelled;
+    STATS.update(storage, |mut stats| -> StdResult<_> {
+        stats.open_polls = Uint64::new(stats.open_polls)
+            .checked_sub(Uint64::one())?
+            .u64();
+        Ok(stats)
+    })?;
+    polls().save(storage, poll_id, &poll)?;
+
+    let event = Event::new("poll_cancelled")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("question", poll.question.clone())
+        .add_attribute("cancelled_by", cancelled_by);
+    let mut response = Response::new().add_event(event);
+    if let Some(refund) = refund_poll_deposit(storage, poll_id)? {
+        response = response.add_message(refund);
+    }
+    if let Some(refund) = refund_bounty(storage, poll_id)? {
+        response = response.add_message(refund);
+    }
+    response = response.add_messages(refund_rewards(storage, poll_id)?);
+    response = response.add_messages(remove_credits(storage, &poll, true)?);
+    let dependents = settle_dependents(storage, block, &poll)?;
+    Ok(response
+        .add_messages(refund_commitments(storage, &poll)?)
+        .add_submessages(dependents.messages)
+        .add_events(dependents.events))
+}
+
+fn execute_remove_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    reason: String,
+    slash_deposit: bool,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+    remove_poll(
+        deps.storage,
+        &env.block,
+        &config,
+        poll_id,
+        reason,
+        slash_deposit,
+    )
+}
+
+/// Deletes a poll and everything stored for it, see `ExecuteMsg::RemovePoll`.
+fn remove_poll(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    config: &Config,
+    poll_id: u64,
+    reason: String,
+    slash_deposit: bool,
+) -> Result<Response, ContractError> {
+    let poll = load_poll(storage, poll_id)?;
+
+    let mut response = Response::new()
+        .add_attribute("action", "remove_poll")
+        .add_attribute("poll_id", poll_id.to_string());
+    if slash_deposit {
+        if let Some(deposit) = DEPOSITS.may_load(storage, poll_id)? {
+            DEPOSITS.remove(storage, poll_id);
+            FEES.update(
+                storage,
+                &deposit.amount.denom,
+                |collected| -> StdResult<_> {
+                    Ok(collected
+                        .unwrap_or_default()
+                        .checked_add(deposit.amount.amount)?)
+                },
+            )?;
+        }
+    } else if let Some(refund) = refund_poll_deposit(storage, poll_id)? {
+        response = response.add_message(refund);
+    }
+    if let Some(refund) = refund_bounty(storage, poll_id)? {
+        response = response.add_message(refund);
+    }
+    response = response.add_messages(refund_rewards(storage, poll_id)?);
+    if let Some(refund) = cancel_raffle(storage, poll_id)? {
+        response = response.add_message(refund);
+    }
+    response = response.add_messages(refund_commitments(storage, &poll)?);
+    response = response.add_messages(settle_stakes(storage, &poll)?);
+    // closed polls already collected what was paid for their credits
+    let open = poll.status == PollStatus::Open;
+    response = response.add_messages(remove_credits(storage, &poll, open)?);
+
+    let escrows = ESCROWS
+        .prefix(poll_id)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    if let Some(token) = &config.cw20_token {
+        for (voter, amount) in &escrows {
+            response = response.add_message(cw20_transfer(token, voter, *amount)?);
+        }
+    }
+    for (voter, _) in escrows {
+        ESCROWS.remove(storage, (poll_id, &voter));
+    }
+
+    let voters = BALLOTS
+        .prefix(poll_id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for voter in voters {
+        BALLOTS.remove(storage, (poll_id, &voter));
+    }
+    let allowed = POLL_VOTERS
+        .prefix(poll_id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for voter in allowed {
+        POLL_VOTERS.remove(storage, (poll_id, &voter));
+    }
+    for tag in &poll.tags {
+        POLL_TAGS.remove(storage, (tag, poll_id));
+    }
+    if let Some(series) = &poll.series {
+        SERIES.remove(storage, (series.first, poll_id));
+    }
+    let endorsers = ENDORSEMENTS
+        .prefix(poll_id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for endorser in endorsers {
+        ENDORSEMENTS.remove(storage, (poll_id, &endorser));
+    }
+    let comments = COMMENTS
+        .prefix(poll_id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for comment_id in comments {
+        COMMENTS.remove(storage, (poll_id, comment_id));
+    }
+    COMMENT_COUNTS.remove(storage, poll_id);
+    let channels = REMOTE_TALLIES
+        .prefix(poll_id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for channel_id in channels {
+        REMOTE_TALLIES.remove(storage, (poll_id, &channel_id));
+    }
+    if let Some(hub) = &poll.hub {
+        HUB_POLLS.remove(storage, (&hub.channel_id, hub.poll_id));
+    }
+    if let Some(condition) = &poll.condition {
+        DEPENDENT_POLLS.remove(storage, (condition.parent, poll_id));
+    }
+    unlink_campaign_poll(storage, poll_id)?;
+    // an open poll never passed, so the polls waiting on it are cancelled
+    let dependents = settle_dependents(storage, block, &poll)?;
+    response = response
+        .add_submessages(dependents.messages)
+        .add_events(dependents.events);
+    if poll.status == PollStatus::Open {
+        STATS.update(storage, |mut stats| -> StdResult<_> {
+            stats.open_polls = Uint64::new(stats.open_polls)
+                .checked_sub(Uint64::one())?
+                .u64();
+            Ok(stats)
+        })?;
+    }
+    polls().remove(storage, poll_id)?;
+
+    let event = Event::new("poll_removed")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("question", poll.question)
+        .add_attribute("creator", poll.creator)
+        .add_attribute("reason", reason)
+        .add_attribute("deposit_slashed", slash_deposit.to_string());
+    Ok(response.add_event(event))
+}
+
+/// Drops every unrevealed commitment on `poll`, returning the messages that hand
+/// back their deposits since they can no longer be revealed.
+fn refund_commitments(storage: &mut dyn Storage, poll: &Poll) -> StdResult<Vec<BankMsg>> {
+    let committers = COMMITMENTS
+        .prefix(poll.id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let deposit = poll
+        .commit_reveal
+        .as_ref()
+        .and_then(|commit_reveal| commit_reveal.deposit.clone());
+    let mut refunds = vec![];
+    for committer in committers {
+        COMMITMENTS.remove(storage, (poll.id, &committer));
+        if let Some(deposit) = &deposit {
+            refunds.push(BankMsg::Send {
+                to_address: committer.to_string(),
+                amount: vec![deposit.clone()],
+            });
+        }
+    }
+    Ok(refunds)
+}
+
+/// Adds the coins paid for the credits of a quadratic poll that closed to the
+/// collected fees.
+fn collect_credit_payments(storage: &mut dyn Storage, poll: &Poll) -> StdResult<()> {
+    let denom = match &poll.kind {
+        PollKind::Quadratic {
+            credit_denom: Some(denom),
+            ..
+        } => denom,
+        _ => return Ok(()),
+    };
+    let paid = CREDITS
+        .prefix(poll.id)
+        .range(storage, None, None, Order::Ascending)
+        .try_fold(Uint128::zero(), |paid, item| -> StdResult<_> {
+            Ok(paid.checked_add(item?.1.deposited.into())?)
+        })?;
+    if paid.is_zero() {
+        return Ok(());
+    }
+    FEES.update(storage, denom, |collected| -> StdResult<_> {
+        Ok(collected.unwrap_or_default().checked_add(paid)?)
+    })?;
+    Ok(())
+}
+
+/// Deletes the credit accounts of `poll`, returning the messages that pay
+/// back what voters paid for their credits when `refund` is set.
+fn remove_credits(storage: &mut dyn Storage, poll: &Poll, refund: bool) -> StdResult<Vec<BankMsg>> {
+    let accounts = CREDITS
+        .prefix(poll.id)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let denom = match &poll.kind {
+        PollKind::Quadratic {
+            credit_denom: Some(denom),
+            ..
+        } if refund => Some(denom),
+        _ => None,
+    };
+    let mut refunds = vec![];
+    for (voter, account) in accounts {
+        CREDITS.remove(storage, (poll.id, &voter));
+        if let Some(denom) = denom.filter(|_| account.deposited > 0) {
+            refunds.push(BankMsg::Send {
+                to_address: voter.to_string(),
+                amount: vec![Coin::new(account.deposited.into(), denom)],
+            });
+        }
+    }
+    Ok(refunds)
+}
+
+/// Removes the creation deposit held for `poll_id`, returning the message that
+/// sends it back to the depositor.
+pub(crate) fn refund_poll_deposit(
+    storage: &mut dyn Storage,
+    poll_id: u64,
+) -> StdResult<Option<BankMsg>> {
+    let deposit = DEPOSITS.may_load(storage, poll_id)?;
+    if deposit.is_some() {
+        DEPOSITS.remove(storage, poll_id);
+    }
+    Ok(deposit.map(|deposit| BankMsg::Send {
+        to_address: deposit.depositor.to_string(),
+        amount: vec![deposit.amount],
+    }))
+}
+
+/// Replaces the stake on each option of a conviction poll with the conviction
+/// its ballots accrued by now.
+fn tally_conviction(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    poll: &mut Poll,
+    max_blocks: Option<u64>,
+) -> Result<(), ContractError> {
+    for option in &mut poll.options {
+        option.votes = Uint128::zero();
+    }
+    for item in BALLOTS
+        .prefix(poll.id)
+        .range(storage, None, None, Order::Ascending)
+    {
+        let (_, ballot) = item?;
+        let mut blocks = block.height.saturating_sub(ballot.height);
+        if let Some(max_blocks) = max_blocks {
+            blocks = blocks.min(max_blocks);
+        }
+        let conviction = ballot.weight.checked_mul(Uint128::from(blocks))?;
+        add_vote(poll, &ballot.choice, conviction)?;
+    }
+    Ok(())
+}
+
+/// Index of the option currently winning `poll`: the plurality leader, or the
+/// instant-runoff winner for ranked-choice polls.
+fn winning_option(storage: &dyn Storage, poll: &Poll) -> StdResult<Option<usize>> {
+    if let Some(winner) = poll.tie_winner {
+        return Ok(Some(winner as usize));
+    }
+    if runoff_options(poll)?.is_some() {
+        return Ok(None);
+    }
+    Ok(match poll.kind {
+        PollKind::SingleChoice
+        | PollKind::Approval
+        | PollKind::Quadratic { .. }
+        | PollKind::Prediction { .. }
+        | PollKind::Conviction { .. } => leading_option(poll),
+        PollKind::Petition { .. } => None,
+        PollKind::RankedChoice => {
+            let rankings = BALLOTS
+                .prefix(poll.id)
+                .range(storage, None, None, Order::Ascending)
+                .filter_map(|item| match item {
+                    Ok((
+                        _,
+                        Ballot {
+                            choice: Choice::Ranking(ranking),
+                            weight,
+                            ..
+                        },
+                    )) => Some(Ok((ranking, weight))),
+                    Ok(_) => None,
+                    Err(err) => Some(Err(err)),
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+            instant_runoff(poll.options.len(), &rankings)?
+        }
+    })
+}
+
+fn execute_withdraw_fees(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    let fees = FEES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect::<StdResult<Vec<_>>>()?;
+    if fees.is_empty() {
+        return Err(ContractError::NothingToWithdraw {});
+    }
+    for fee in &fees {
+        FEES.remove(deps.storage, &fee.denom);
+    }
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: fees,
+        })
+        .add_attribute("action", "withdraw_fees")
+        .add_attribute("recipient", recipient))
+}
+
+/// Settings changed by `UpdateConfig`, fields left as `None` are kept.
+struct ConfigUpdate {
+    default_quorum: Option<Uint128>,
+    default_threshold: Option<Decimal>,
+    default_voting_period: Option<Duration>,
+    execution_delay: Option<Duration>,
+    veto_period: Option<Duration>,
+    max_multicall: Option<u32>,
+}
+
+fn execute_update_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    update: ConfigUpdate,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+    apply_config_update(&mut config, update)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
+fn apply_config_update(config: &mut Config, update: ConfigUpdate) -> Result<(), ContractError> {
+    validate_threshold(update.default_threshold)?;
+    validate_voting_period(update.default_voting_period)?;
+
+    if update.default_quorum.is_some() {
+        config.default_quorum = update.default_quorum;
+    }
+    if update.default_threshold.is_some() {
+        config.default_threshold = update.default_threshold;
+    }
+    if update.default_voting_period.is_some() {
+        config.default_voting_period = update.default_voting_period;
+    }
+    if update.execution_delay.is_some() {
+        config.execution_delay = update.execution_delay;
+    }
+    if update.veto_period.is_some() {
+        config.veto_period = update.veto_period;
+    }
+    if let Some(max_multicall) = update.max_multicall {
+        config.max_multicall = max_multicall;
+    }
+    Ok(())
+}
+
+fn execute_set_paused(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_role(
+        deps.storage,
+        &config,
+        &info.sender,
+        &[Role::Owner, Role::Operator],
+    )?;
+
+    config.paused = paused;
+    CONFIG.save(deps.storage, &config)?;
+
+    let action = if paused { "pause" } else { "unpause" };
+    Ok(Response::new().add_attribute("action", action))
+}
+
+fn execute_propose_new_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    address: String,
+    expiry: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+    if expiry.is_some_and(|expiry| expiry.is_expired(&env.block)) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+
+    let address = deps.api.addr_validate(&address)?;
+    PENDING_ADMIN.save(
+        deps.storage,
+        &PendingAdmin {
+            address: address.clone(),
+            expiry,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose_new_admin")
+        .add_attribute("pending_admin", address))
+}
+
+fn execute_accept_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let pending = PENDING_ADMIN
+        .may_load(deps.storage)?
+        .filter(|pending| pending.address == info.sender)
+        .ok_or(ContractError::Unauthorized {})?;
+    if pending
+        .expiry
+        .is_some_and(|expiry| expiry.is_expired(&env.block))
+    {
+        return Err(ContractError::AdminProposalExpired {});
+    }
+    PENDING_ADMIN.remove(deps.storage);
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let previous_admin = std::mem::replace(&mut config.admin_address, pending.address);
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "accept_admin")
+        .add_attribute("previous_admin", previous_admin)
+        .add_attribute("new_admin", config.admin_address))
+}
+
+fn execute_grant_role(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+    role: Role,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+
+    let address = deps.api.addr_validate(&address)?;
+    ROLES.save(deps.storage, &address, &role)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "grant_role")
+        .add_attribute("address", address)
+        .add_attribute("role", role.to_string()))
+}
+
+fn execute_revoke_role(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+
+    let address = deps.api.addr_validate(&address)?;
+    if !ROLES.has(deps.storage, &address) {
+        return Err(ContractError::NoRole {
+            address: address.to_string(),
+        });
+    }
+    ROLES.remove(deps.storage, &address);
+
+    Ok(Response::new()
+        .add_attribute("action", "revoke_role")
+        .add_attribute("address", address))
+}
+
+fn execute_add_to_blocklist(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(
+        deps.storage,
+        &config,
+        &info.sender,
+        &[Role::Owner, Role::Moderator],
+    )?;
+
+    let address = deps.api.addr_validate(&address)?;
+    BLOCKLIST.save(deps.storage, &address, &Empty {})?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_to_blocklist")
+        .add_attribute("address", address))
+}
+
+fn execute_remove_from_blocklist(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(
+        deps.storage,
+        &config,
+        &info.sender,
+        &[Role::Owner, Role::Moderator],
+    )?;
+
+    let address = deps.api.addr_validate(&address)?;
+    BLOCKLIST.remove(deps.storage, &address);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_from_blocklist")
+        .add_attribute("address", address))
+}
+
+fn execute_update_creators(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+
+    let mut response = Response::new().add_attribute("action", "update_creators");
+    for address in add {
+        let address = deps.api.addr_validate(&address)?;
+        CREATORS.save(deps.storage, &address, &Empty {})?;
+        response = response.add_attribute("added", address);
+    }
+    for address in remove {
+        let address = deps.api.addr_validate(&address)?;
+        CREATORS.remove(deps.storage, &address);
+        response = response.add_attribute("removed", address);
+    }
+    Ok(response)
+}
+
+fn execute_member_changed_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: MemberChangedHookMsg,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    let group = VotingPowerSource::Cw4Group {
+        group: info.sender.clone(),
+    };
+    if config.voting_power != group {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for diff in &msg.diffs {
+        let member = deps.api.addr_validate(&diff.key)?;
+        match diff.new {
+            Some(weight) => GROUP_WEIGHTS.save(deps.storage, &member, &weight, env.block.height)?,
+            None => GROUP_WEIGHTS.remove(deps.storage, &member, env.block.height)?,
+        }
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "member_changed_hook")
+        .add_attribute("changed", msg.diffs.len().to_string()))
+}
+
+fn execute_add_hook(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+    let address = deps.api.addr_validate(&address)?;
+    if HOOKS.has(deps.storage, &address) {
+        return Err(ContractError::HookAlreadyRegistered {
+            address: address.into_string(),
+        });
+    }
+    HOOKS.save(deps.storage, &address, &Empty {})?;
+
+    Ok(Response::new()
+        .add_attribute("action", "add_hook")
+        .add_attribute("hook", address))
+}
+
+fn execute_remove_hook(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+    let address = deps.api.addr_validate(&address)?;
+    if !HOOKS.has(deps.storage, &address) {
+        return Err(ContractError::HookNotRegistered {
+            address: address.into_string(),
+        });
+    }
+    HOOKS.remove(deps.storage, &address);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_hook")
+        .add_attribute("hook", address))
+}
+
+/// `msg` wrapped for every registered hook, replying only if the hook fails.
+pub(crate) fn hook_msgs(storage: &dyn Storage, msg: &PollHookMsg) -> StdResult<Vec<SubMsg>> {
+    let msg = to_binary(&HookExecuteMsg::PollHook(msg.clone()))?;
+    HOOKS
+        .keys(storage, None, None, Order::Ascending)
+        .map(|hook| {
+            let execute = WasmMsg::Execute {
+                contract_addr: hook?.into_string(),
+                msg: msg.clone(),
+                funds: vec![],
+            };
+            Ok(SubMsg::reply_on_error(execute, HOOK_REPLY_ID))
+        })
+        .collect()
+}
+
+fn execute_update_stake_validators(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+
+    let mut response = Response::new().add_attribute("action", "update_stake_validators");
+    for validator in add {
+        ensure_validator(deps.as_ref(), &validator)?;
+        if !config.stake_validators.contains(&validator) {
+            config.stake_validators.push(validator.clone());
+        }
+        response = response.add_attribute("added", validator);
+    }
+    for validator in remove {
+        config
+            .stake_validators
+            .retain(|existing| *existing != validator);
+        response = response.add_attribute("removed", validator);
+    }
+    CONFIG.save(deps.storage, &config)?;
+    Ok(response)
+}
+
+fn ensure_validator(deps: Deps, validator: &str) -> Result<(), ContractError> {
+    if deps.querier.query_validator(validator)?.is_none() {
+        return Err(ContractError::UnknownValidator {
+            validator: validator.to_string(),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn ensure_not_blocked(
+    storage: &dyn Storage,
+    address: &Addr,
+) -> Result<(), ContractError> {
+    if BLOCKLIST.has(storage, address) {
+        return Err(ContractError::Blocklisted {
+            address: address.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn ensure_eligible(storage: &dyn Storage, poll: &Poll, voter: &Addr) -> Result<(), ContractError> {
+    ensure_not_blocked(storage, voter)?;
+    if poll.private && !POLL_VOTERS.has(storage, (poll.id, voter)) {
+        return Err(ContractError::NotEligible {});
+    }
+    Ok(())
+}
+
+/// Fails unless `sender` holds one of the `allowed` roles. The admin always counts
+/// as an owner.
+pub(crate) fn ensure_role(
+    storage: &dyn Storage,
+    config: &Config,
+    sender: &Addr,
+    allowed: &[Role],
+) -> Result<(), ContractError> {
+    let role = if *sender == config.admin_address {
+        Some(Role::Owner)
+    } else {
+        ROLES.may_load(storage, sender)?
+    };
+    match role {
+        Some(role) if allowed.contains(&role) => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        EXECUTE_POLL_REPLY_ID => reply_execute_poll(deps, msg.result),
+        REGISTER_QUERY_REPLY_ID => reply_register_query(deps, msg.result),
+        HOOK_REPLY_ID => Ok(reply_hook(msg.result)),
+        INSTANTIATE_CHILD_REPLY_ID => reply_instantiate_child(deps, msg),
+        MINT_RECEIPT_REPLY_ID => reply_mint_receipt(deps, msg.result),
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
+
+/// Marks the poll being executed as failed when one of its messages errors.
+fn reply_execute_poll(deps: DepsMut, result: SubMsgResult) -> Result<Response, ContractError> {
+    let error = match result {
+        SubMsgResult::Ok(_) => return Ok(Response::new()),
+        SubMsgResult::Err(error) => error,
+    };
+    let poll_id = EXECUTING_POLL.load(deps.storage)?;
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    poll.status = PollStatus::Failed;
+    polls().save(deps.storage, poll_id, &poll)?;
+
+    let event = Event::new("poll_execution_failed")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("error", error);
+    Ok(Response::new()
+        .add_attribute("action", "reply_execute_poll")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_event(event))
+}
+
+/// Swallows a hook failure so a misbehaving hook cannot block polls.
+fn reply_hook(result: SubMsgResult) -> Response {
+    let mut response = Response::new().add_attribute("action", "reply_hook");
+    if let SubMsgResult::Err(error) = result {
+        response = response.add_event(Event::new("hook_failed").add_attribute("error", error));
+    }
+    response
+}
+
+/// Links the interchain query registered by `RegisterRemotePower` to its voter.
+fn reply_register_query(deps: DepsMut, result: SubMsgResult) -> Result<Response, ContractError> {
+    let response = result.into_result().map_err(StdError::generic_err)?;
+    let query_id = registered_query_id(response.data)?;
+    let voter = REGISTERING_VOTER.load(deps.storage)?;
+    REGISTERING_VOTER.remove(deps.storage);
+    REMOTE_POWER_QUERIES.save(deps.storage, query_id, &voter)?;
+    REMOTE_POWER.save(
+        deps.storage,
+        &voter,
+        &RemotePower {
+            query_id,
+            power: Uint128::zero(),
+            height: 0,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "reply_register_query")
+        .add_attribute("voter", voter)
+        .add_attribute("query_id", query_id.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    match msg {
+        SudoMsg::Pause {} => sudo_set_paused(deps, true),
+        SudoMsg::Unpause {} => sudo_set_paused(deps, false),
+        SudoMsg::SetConfig {
+            admin_address,
+            default_quorum,
+            default_threshold,
+            default_voting_period,
+            execution_delay,
+            veto_period,
+            max_multicall,
+        } => sudo_set_config(
+            deps,
+            admin_address,
+            ConfigUpdate {
+                default_quorum,
+                default_threshold,
+                default_voting_period,
+                execution_delay,
+                veto_period,
+                max_multicall,
+            },
+        ),
+        SudoMsg::RemovePoll {
+            poll_id,
+            reason,
+            slash_deposit,
+        } => {
+            let config = CONFIG.load(deps.storage)?;
+            remove_poll(
+                deps.storage,
+                &env.block,
+                &config,
+                poll_id,
+                reason,
+                slash_deposit,
+            )
+        }
+        SudoMsg::KvQueryResult { query_id } => sudo_kv_query_result(deps, query_id),
+    }
+}
+
+/// Caches the power proven by a new interchain query result, ignoring results
+/// no newer than the cached one.
+fn sudo_kv_query_result(deps: DepsMut, query_id: u64) -> Result<Response, ContractError> {
+    let voter = REMOTE_POWER_QUERIES
+        .may_load(deps.storage, query_id)?
+        .ok_or(ContractError::UnknownQuery { query_id })?;
+    let config = CONFIG.load(deps.storage)?;
+    let remote_power = config.remote_power.ok_or(ContractError::NoRemotePower {})?;
+    let mut cached = REMOTE_POWER.load(deps.storage, &voter)?;
+
+    let (power, height) = query_remote_power(deps.as_ref(), query_id, &remote_power.source)?;
+    if height > cached.height {
+        cached.power = power;
+        cached.height = height;
+        REMOTE_POWER.save(deps.storage, &voter, &cached)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "sudo_kv_query_result")
+        .add_attribute("voter", voter)
+        .add_attribute("power", cached.power)
+        .add_attribute("height", cached.height.to_string()))
+}
+
+fn sudo_set_paused(deps: DepsMut, paused: bool) -> Result<Response, ContractError> {
+    CONFIG.update(deps.storage, |mut config| -> StdResult<_> {
+        config.paused = paused;
+        Ok(config)
+    })?;
+
+    let action = if paused { "sudo_pause" } else { "sudo_unpause" };
+    Ok(Response::new().add_attribute("action", action))
+}
+
+fn sudo_set_config(
+    deps: DepsMut,
+    admin_address: Option<String>,
+    update: ConfigUpdate,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if let Some(admin_address) = admin_address {
+        config.admin_address = deps.api.addr_validate(&admin_address)?;
+        PENDING_ADMIN.remove(deps.storage);
+    }
+    apply_config_update(&mut config, update)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "sudo_set_config")
+        .add_attribute("admin", config.admin_address))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            contract: stored.contract,
+        });
+    }
+
+    let stored_version: Version = stored.version.parse()?;
+    let version: Version = CONTRACT_VERSION.parse()?;
+    if stored_version > version {
+        return Err(ContractError::CannotMigrateFromNewer {
+            version: stored_version.to_string(),
+        });
+    }
+
+    migrate_state(deps.storage, &stored_version)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored_version.to_string())
+        .add_attribute("to_version", version.to_string()))
+}
+
+/// Runs the state transforms needed to bring storage written by `from_version`
+/// up to the current layout. Add a branch here whenever a release changes it.
+fn migrate_state(_storage: &mut dyn Storage, _from_version: &Version) -> Result<(), ContractError> {
+    Ok(())
+}
+
+/// Trims `question` and checks it against the configured length bounds.
+pub(crate) fn normalize_question(config: &Config, question: &str) -> Result<String, ContractError> {
+    let question = question.trim();
+    if question.chars().any(char::is_control) {
+        return Err(ContractError::QuestionControlCharacters {});
+    }
+    if question.len() < config.question_min_len as usize {
+        return Err(ContractError::QuestionTooShort {
+            min: config.question_min_len,
+        });
+    }
+    if question.len() > config.question_max_len as usize {
+        return Err(ContractError::QuestionTooLong {
+            max: config.question_max_len,
+        });
+    }
+    Ok(question.to_string())
+}
+
+fn ensure_question_unused(storage: &dyn Storage, question: &str) -> Result<(), ContractError> {
+    if polls()
+        .idx
+        .question
+        .item(storage, question.to_string())?
+        .is_some()
+    {
+        return Err(ContractError::PollAlreadyExists {
+            question: question.to_string(),
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_options(options: &[String]) -> Result<(), ContractError> {
+    if options.len() < 2 || options.len() > MAX_POLL_OPTIONS {
+        return Err(ContractError::InvalidOptionCount {
+            min: 2,
+            max: MAX_POLL_OPTIONS,
+        });
+    }
+    for (i, label) in options.iter().enumerate() {
+        if label.trim().is_empty() || label.len() > MAX_OPTION_LEN || options[..i].contains(label) {
+            return Err(ContractError::InvalidOption {
+                label: label.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn validate_option_details(options: usize, details: &[OptionDetails]) -> Result<(), ContractError> {
+    if !details.is_empty() && details.len() != options {
+        return Err(ContractError::InvalidOptionDetails {});
+    }
+    for OptionDetails { description, image } in details {
+        validate_description(description)?;
+        if image
+            .as_ref()
+            .is_some_and(|image| image.len() > MAX_LINK_LEN)
+        {
+            return Err(ContractError::FieldTooLong {
+                field: "image".to_string(),
+                max: MAX_LINK_LEN,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn validate_description(description: &Option<String>) -> Result<(), ContractError> {
+    if description
+        .as_ref()
+        .is_some_and(|description| description.len() > MAX_DESCRIPTION_LEN)
+    {
+        return Err(ContractError::FieldTooLong {
+            field: "description".to_string(),
+            max: MAX_DESCRIPTION_LEN,
+        });
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_threshold(threshold: Option<Decimal>) -> Result<(), ContractError> {
+    if threshold.is_some_and(|threshold| threshold.is_zero() || threshold > Decimal::one()) {
+        return Err(ContractError::InvalidThreshold {});
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_voting_period(period: Option<Duration>) -> Result<(), ContractError> {
+    if matches!(period, Some(Duration::Height(0) | Duration::Time(0))) {
+        return Err(ContractError::InvalidVotingPeriod {});
+    }
+    Ok(())
+}
+
+/// Expiration of a poll that runs for `period`, from its start when that is
+/// given in the same unit and from the current block otherwise.
+fn voting_period_end(block: &BlockInfo, start: Option<Scheduled>, period: Duration) -> Expiration {
+    match (start, period) {
+        (Some(Scheduled::AtHeight(height)), Duration::Height(blocks)) => {
+            Expiration::AtHeight(height.saturating_add(blocks))
+        }
+        (Some(Scheduled::AtTime(time)), Duration::Time(seconds)) => {
+            Expiration::AtTime(time.plus_seconds(seconds))
+        }
+        _ => period.after(block),
+    }
+}
+
+/// Funds `CreatePoll` must be sent with: the refundable deposit, the fee and
+/// the bounty.
+fn creation_funds(config: &Config, bounty: Option<&Bounty>) -> StdResult<Vec<Coin>> {
+    let mut funds: Vec<Coin> = vec![];
+    let bounty = bounty.map(|bounty| &bounty.amount);
+    for coin in config
+        .poll_deposit
+        .iter()
+        .chain(&config.creation_fee)
+        .chain(bounty)
+    {
+        match funds.iter_mut().find(|c| c.denom == coin.denom) {
+            Some(existing) => existing.amount = existing.amount.checked_add(coin.amount)?,
+            None => funds.push(coin.clone()),
+        }
+    }
+    Ok(funds)
+}
+
+fn same_coins(sent: &[Coin], required: &[Coin]) -> bool {
+    sent.len() == required.len() && required.iter().all(|coin| sent.contains(coin))
+}
+
+pub(crate) fn cw20_transfer(token: &Addr, recipient: &Addr, amount: Uint128) -> StdResult<WasmMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount,
+        })?,
+        funds: vec![],
+    })
+}
+
+/// Weight of a vote cast directly with `Vote` or `CommitVote`.
+fn voter_weight(deps: Deps, poll: &Poll, voter: &Addr) -> Result<Uint128, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if let Some(source) = &poll.voting_power {
+        return source.weight(deps, &config, voter);
+    }
+    if config.remote_power.is_some() {
+        return Ok(REMOTE_POWER
+            .may_load(deps.storage, voter)?
+            .map(|remote_power| remote_power.power)
+            .unwrap_or_default());
+    }
+    if config.snapshot_power {
+        return Ok(VOTING_POWER
+            .may_load_at_height(deps.storage, voter, poll.start_height)?
+            .unwrap_or_default());
+    }
+    if config.cw20_token.is_some() {
+        return Err(ContractError::VoteWithTokens {});
+    }
+    config.voting_power.weight(deps, &config, voter)
+}
+
+fn validate_voting_power(
+    api: &dyn Api,
+    source: VotingPowerSourceMsg,
+) -> Result<VotingPowerSource, ContractError> {
+    Ok(match source {
+        VotingPowerSourceMsg::FixedOnePerAddress => VotingPowerSource::FixedOnePerAddress,
+        VotingPowerSourceMsg::Cw20Balance { token } => VotingPowerSource::Cw20Balance {
+            token: api.addr_validate(&token)?,
+        },
+        VotingPowerSourceMsg::Cw721Count { collection } => VotingPowerSource::Cw721Count {
+            collection: api.addr_validate(&collection)?,
+        },
+        VotingPowerSourceMsg::NativeStake => VotingPowerSource::NativeStake,
+        VotingPowerSourceMsg::Cw4Group { group } => VotingPowerSource::Cw4Group {
+            group: api.addr_validate(&group)?,
+        },
+        VotingPowerSourceMsg::LpShare {
+            pair,
+            lp_token,
+            asset,
+            rate,
+        } => VotingPowerSource::LpShare {
+            pair: api.addr_validate(&pair)?,
+            lp_token: api.addr_validate(&lp_token)?,
+            asset,
+            rate: validate_rate(rate)?,
+        },
+        VotingPowerSourceMsg::Vesting {
+            base,
+            vesting,
+            multiplier,
+        } => VotingPowerSource::Vesting {
+            base: Box::new(validate_voting_power(api, *base)?),
+            vesting: api.addr_validate(&vesting)?,
+            multiplier: validate_rate(multiplier.unwrap_or(Decimal::one()))?,
+        },
+    })
+}
+
+fn validate_rate(rate: Decimal) -> Result<Decimal, ContractError> {
+    if rate.is_zero() || rate > Decimal::one() {
+        return Err(ContractError::InvalidRate {});
+    }
+    Ok(rate)
+}
+
+pub(crate) fn ensure_open(poll: &Poll) -> Result<(), ContractError> {
+    if poll.status != PollStatus::Open {
+        return Err(ContractError::PollClosed { poll_id: poll.id });
+    }
+    if awaiting_draw(poll) {
+        return Err(ContractError::TieDrawPending { poll_id: poll.id });
+    }
+    Ok(())
+}
+
+pub(crate) fn ensure_started(poll: &Poll, block: &BlockInfo) -> Result<(), ContractError> {
+    if !has_started(poll, block) {
+        return Err(ContractError::PollNotStarted { poll_id: poll.id });
+    }
+    Ok(())
+}
+
+fn has_started(poll: &Poll, block: &BlockInfo) -> bool {
+    poll.start.is_none_or(|start| start.is_triggered(block))
+        && poll
+            .condition
+            .as_ref()
+            .is_none_or(|condition| condition.met)
+}
+
+/// The point after which no more ballots can be counted.
+fn voting_end(poll: &Poll) -> Expiration {
+    match &poll.commit_reveal {
+        Some(commit_reveal) => commit_reveal.reveal_expiration,
+        None => poll.expiration,
+    }
+}
+
+pub(crate) fn load_poll(storage: &dyn Storage, poll_id: u64) -> Result<Poll, ContractError> {
+    polls()
+        .may_load(storage, poll_id)?
+        .ok_or(ContractError::PollNotFound { poll_id })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetPoll { poll_id } => query_get_poll(deps, env, poll_id),
+        QueryMsg::GetPollByQuestion { question } => query_get_poll_by_question(deps, env, question),
+        QueryMsg::ListPolls { start_after, limit } => {
+            query_list_polls(deps, env, start_after, limit)
+        }
+        QueryMsg::GetConfig => to_binary(&CONFIG.load(deps.storage)?),
+        QueryMsg::GetCredits { poll_id, voter } => query_get_credits(deps, env, poll_id, voter),
+        QueryMsg::GetRaffle { poll_id } => query_get_raffle(deps, env, poll_id),
+        QueryMsg::GetReceipt { poll_id, voter } => query_get_receipt(deps, env, poll_id, voter),
+        QueryMsg::GetReputation { address } => query_get_reputation(deps, env, address),
+        QueryMsg::Leaderboard { limit } => query_leaderboard(deps, env, limit),
+        QueryMsg::GetStreak { address } => query_get_streak(deps, env, address),
+        QueryMsg::GetRewardPool { poll_id } => query_get_reward_pool(deps, env, poll_id),
+        QueryMsg::GetIncentive { incentive_id } => query_get_incentive(deps, env, incentive_id),
+        QueryMsg::GetAccruedIncentives { voter } => query_get_accrued_incentives(deps, env, voter),
+        QueryMsg::GetBounty { poll_id } => query_get_bounty(deps, env, poll_id),
+        QueryMsg::GetStake { poll_id, bettor } => query_get_stake(deps, env, poll_id, bettor),
+        QueryMsg::GetRole { address } => query_get_role(deps, env, address),
+        QueryMsg::IsEligible { poll_id, voter } => query_is_eligible(deps, env, poll_id, voter),
+        QueryMsg::ListVoters {
+            poll_id,
+            start_after,
+            limit,
+        } => query_list_voters(deps, env, poll_id, start_after, limit),
+        QueryMsg::GetVote { poll_id, voter } => query_get_vote(deps, env, poll_id, voter),
+        QueryMsg::ListPollsByStatus {
+            status,
+            start_after,
+            limit,
+        } => query_list_polls_by_status(deps, env, status, start_after, limit),
+        QueryMsg::ListPollsByCreator {
+            creator,
+            start_after,
+            limit,
+        } => query_list_polls_by_creator(deps, env, creator, start_after, limit),
+        QueryMsg::GetTally { poll_id } => query_get_tally(deps, env, poll_id),
+        QueryMsg::GetStats {} => query_get_stats(deps, env),
+        QueryMsg::ListPollsByTag {
+            tag,
+            start_after,
+            limit,
+        } => query_list_polls_by_tag(deps, env, tag, start_after, limit),
+        QueryMsg::ListRemoteTallies { poll_id } => query_list_remote_tallies(deps, env, poll_id),
+        QueryMsg::GetRemotePower { voter } => query_get_remote_power(deps, env, voter),
+        QueryMsg::SearchPolls {
+            prefix,
+            start_after,
+            limit,
+        } => query_search_polls(deps, env, prefix, start_after, limit),
+        QueryMsg::ListHooks {} => query_list_hooks(deps, env),
+        QueryMsg::ListSeries {
+            poll_id,
+            start_after,
+            limit,
+        } => query_list_series(deps, env, poll_id, start_after, limit),
+        QueryMsg::ListRunoffRounds { poll_id } => query_list_runoff_rounds(deps, env, poll_id),
+        QueryMsg::ListTrendingPolls { limit } => query_list_trending_polls(deps, env, limit),
+        QueryMsg::Proposal { proposal_id } => query_proposal(deps, env, proposal_id),
+        QueryMsg::ListProposals { start_after, limit } => {
+            query_list_proposals(deps, env, start_after, limit)
+        }
+        QueryMsg::Vote { proposal_id, voter } => query_vote(deps, env, proposal_id, voter),
+        QueryMsg::ListVotes {
+            proposal_id,
+            start_after,
+            limit,
+        } => query_list_votes(deps, env, proposal_id, start_after, limit),
+        QueryMsg::Threshold {} => query_threshold(deps, env),
+        QueryMsg::VotingPowerAtHeight { address, height } => {
+            query_voting_power_at_height(deps, env, address, height)
+        }
+        QueryMsg::TotalPowerAtHeight { height } => query_total_power_at_height(deps, env, height),
+        QueryMsg::Info {} => query_info(deps, env),
+        QueryMsg::ListComments {
+            poll_id,
+            start_after,
+            limit,
+        } => query_list_comments(deps, env, poll_id, start_after, limit),
+        QueryMsg::GetRound { round_id } => query_get_round(deps, env, round_id),
+        QueryMsg::ListTemplates { start_after, limit } => {
+            query_list_templates(deps, env, start_after, limit)
+        }
+        QueryMsg::GetCampaignTally { campaign_id } => {
+            query_get_campaign_tally(deps, env, campaign_id)
+        }
+        QueryMsg::GetCampaignTurnout { campaign_id } => {
+            query_get_campaign_turnout(deps, env, campaign_id)
+        }
+        QueryMsg::GetSurvey { survey_id } => query_get_survey(deps, env, survey_id),
+        QueryMsg::GetSurveyAnswers {
+            survey_id,
+            respondent,
+        } => query_get_survey_answers(deps, env, survey_id, respondent),
+        QueryMsg::ChildAddress { poll_id } => query_child_address(deps, env, poll_id),
+        QueryMsg::ListChildren { start_after, limit } => {
+            query_list_children(deps, env, start_after, limit)
+        }
+    }
+}
+
+fn query_list_hooks(deps: Deps, _env: Env) -> StdResult<Binary> {
+    let hooks = HOOKS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    to_binary(&ListHooksResponse { hooks })
+}
+
+fn query_is_eligible(deps: Deps, _env: Env, poll_id: u64, voter: String) -> StdResult<Binary> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let poll = polls().load(deps.storage, poll_id)?;
+    let eligible = ensure_eligible(deps.storage, &poll, &voter).is_ok();
+    to_binary(&IsEligibleResponse { eligible })
+}
+
+fn query_get_role(deps: Deps, _env: Env, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let config = CONFIG.load(deps.storage)?;
+    let role = if address == config.admin_address {
+        Some(Role::Owner)
+    } else {
+        ROLES.may_load(deps.storage, &address)?
+    };
+    to_binary(&GetRoleResponse { role })
+}
+
+fn query_get_credits(deps: Deps, _env: Env, poll_id: u64, voter: String) -> StdResult<Binary> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let poll = polls().load(deps.storage, poll_id)?;
+    let base = match poll.kind {
+        PollKind::Quadratic { credits, .. } => credits,
+        _ => 0,
+    };
+    let account = CREDITS
+        .may_load(deps.storage, (poll_id, &voter))?
+        .unwrap_or_default();
+    let total = Uint64::new(base).checked_add(Uint64::new(account.deposited))?;
+    to_binary(&GetCreditsResponse {
+        total: total.u64(),
+        spent: account.spent,
+        remaining: total.checked_sub(Uint64::new(account.spent))?.u64(),
+    })
+}
+
+fn query_get_poll(deps: Deps, env: Env, poll_id: u64) -> StdResult<Binary> {
+    let poll = polls().may_load(deps.storage, poll_id)?;
+    let is_open = poll.as_ref().is_some_and(|poll| is_open(poll, &env.block));
+    to_binary(&GetPollResponse { poll, is_open })
+}
+
+fn query_get_poll_by_question(deps: Deps, env: Env, question: String) -> StdResult<Binary> {
+    let poll = polls()
+        .idx
+        .question
+        .item(deps.storage, question)?
+        .map(|(_, poll)| poll);
+    let is_open = poll.as_ref().is_some_and(|poll| is_open(poll, &env.block));
+    to_binary(&GetPollResponse { poll, is_open })
+}
+
+fn query_list_polls(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let polls = polls()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.and_then(|(_, poll)| poll_summary(&poll, &env.block)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ListPollsResponse { polls })
+}
+
+fn query_list_polls_by_status(
+    deps: Deps,
+    env: Env,
+    status: PollStatus,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    // pending polls are stored as open and told apart by their start
+    let stored = match status {
+        PollStatus::Pending => PollStatus::Open,
+        status => status,
+    };
+    let polls = polls()
+        .idx
+        .status
+        .prefix(stored.to_string())
+        .range(deps.storage, start, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, poll)| current_status(poll, &env.block) == status)
+        })
+        .take(limit)
+        .map(|item| item.and_then(|(_, poll)| poll_summary(&poll, &env.block)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ListPollsResponse { polls })
+}
+
+fn query_list_polls_by_creator(
+    deps: Deps,
+    env: Env,
+    creator: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let creator = deps.api.addr_validate(&creator)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let polls = polls()
+        .idx
+        .creator
+        .prefix(creator)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.and_then(|(_, poll)| poll_summary(&poll, &env.block)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ListPollsResponse { polls })
+}
+
+fn query_get_tally(deps: Deps, _env: Env, poll_id: u64) -> StdResult<Binary> {
+    let poll = polls().load(deps.storage, poll_id)?;
+    to_binary(&poll_tally(deps.storage, &poll)?)
+}
+
+pub(crate) fn poll_tally(storage: &dyn Storage, poll: &Poll) -> StdResult<GetTallyResponse> {
+    let total_votes = total_votes(poll)?;
+    let share = |votes: Uint128| {
+        if total_votes.is_zero() {
+            Decimal::zero()
+        } else {
+            Decimal::from_ratio(votes, total_votes)
+        }
+    };
+
+    let winner = winning_option(storage, poll)?.map(|index| poll.options[index].label.clone());
+    let options = poll
+        .options
+        .iter()
+        .map(|option| OptionTally {
+            label: option.label.clone(),
+            votes: option.votes,
+            percentage: share(option.votes),
+        })
+        .collect();
+
+    Ok(GetTallyResponse {
+        poll_id: poll.id,
+        status: poll.status,
+        options,
+        abstain_votes: poll.abstain_votes,
+        veto_votes: poll.veto_votes,
+        total_votes,
+        winner,
+    })
+}
+
+fn query_get_stats(deps: Deps, _env: Env) -> StdResult<Binary> {
+    let stats = STATS.load(deps.storage)?;
+    to_binary(&GetStatsResponse {
+        total_polls: POLL_COUNT.load(deps.storage)?,
+        total_votes: stats.total_votes,
+        open_polls: stats.open_polls,
+        unique_voters: stats.unique_voters,
+    })
+}
+
+fn query_list_polls_by_tag(
+    deps: Deps,
+    env: Env,
+    tag: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let polls = POLL_TAGS
+        .prefix(&tag)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|poll_id| {
+            let poll = polls().load(deps.storage, poll_id?)?;
+            poll_summary(&poll, &env.block)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ListPollsResponse { polls })
+}
+
+fn query_list_series(
+    deps: Deps,
+    env: Env,
+    poll_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let first = polls()
+        .load(deps.storage, poll_id)?
+        .series
+        .map_or(poll_id, |series| series.first);
+
+    let polls = SERIES
+        .prefix(first)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|id| poll_summary(&polls().load(deps.storage, id?)?, &env.block))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ListPollsResponse { polls })
+}
+
+fn query_list_trending_polls(deps: Deps, env: Env, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let polls = polls()
+        .idx
+        .endorsements
+        .sub_prefix(PollStatus::Open.to_string())
+        .range(deps.storage, None, None, Order::Descending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, poll)| is_open(poll, &env.block))
+        })
+        .take(limit)
+        .map(|item| item.and_then(|(_, poll)| poll_summary(&poll, &env.block)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ListPollsResponse { polls })
+}
+
+fn query_search_polls(
+    deps: Deps,
+    env: Env,
+    prefix: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = match start_after {
+        Some(question) if question >= prefix => Bound::exclusive(question),
+        _ => Bound::inclusive(prefix.clone()),
+    };
+
+    let polls = polls()
+        .idx
+        .question
+        .range(deps.storage, Some(start), None, Order::Ascending)
+        .take_while(|item| {
+            item.as_ref()
+                .map_or(true, |(_, poll)| poll.question.starts_with(&prefix))
+        })
+        .take(limit)
+        .map(|item| item.and_then(|(_, poll)| poll_summary(&poll, &env.block)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ListPollsResponse { polls })
+}
+
+fn query_list_remote_tallies(deps: Deps, _env: Env, poll_id: u64) -> StdResult<Binary> {
+    let tallies = REMOTE_TALLIES
+        .prefix(poll_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(channel_id, tally)| ChannelTally { channel_id, tally }))
+        .collect::<StdResult<Vec<_>>>()?;
+    to_binary(&ListRemoteTalliesResponse { tallies })
+}
+
+fn query_get_remote_power(deps: Deps, _env: Env, voter: String) -> StdResult<Binary> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let remote_power = REMOTE_POWER.may_load(deps.storage, &voter)?;
+    to_binary(&GetRemotePowerResponse { remote_power })
+}
+
+fn query_list_voters(
+    deps: Deps,
+    _env: Env,
+    poll_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|voter| deps.api.addr_validate(&voter))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+
+    let voters = BALLOTS
+        .prefix(poll_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(voter, ballot)| VoterInfo {
+                voter,
+                choice: ballot.choice,
+                weight: ballot.weight,
+                memo: ballot.memo,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ListVotersResponse { voters })
+}
+
+fn query_get_vote(deps: Deps, _env: Env, poll_id: u64, voter: String) -> StdResult<Binary> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let ballot = BALLOTS.may_load(deps.storage, (poll_id, &voter))?;
+    to_binary(&GetVoteResponse { ballot })
+}
+
+pub(crate) fn poll_summary(poll: &Poll, block: &BlockInfo) -> StdResult<PollSummary> {
+    Ok(PollSummary {
+        id: poll.id,
+        question: poll.question.clone(),
+        total_votes: total_votes(poll)?,
+        expiration: poll.expiration,
+        status: current_status(poll, block),
+        is_open: is_open(poll, block),
+        endorsements: poll.endorsements,
+    })
+}
+
+fn is_open(poll: &Poll, block: &BlockInfo) -> bool {
+    poll.status == PollStatus::Open
+        && has_started(poll, block)
+        && !voting_end(poll).is_expired(block)
+}
+
+/// The poll's status as reported by queries, `Pending` until it starts.
+pub(crate) fn current_status(poll: &Poll, block: &BlockInfo) -> PollStatus {
+    if poll.status == PollStatus::Open && !has_started(poll, block) {
+        PollStatus::Pending
+    } else {
+        poll.status
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::{
+        attr, coin, from_binary, from_slice,
+        testing::{mock_dependencies, mock_env, mock_info, MockQuerier},
+        ContractResult, CosmosMsg, FullDelegation, ReplyOn, SubMsgResponse, SystemError,
+        SystemResult, Validator, WasmQuery,
+    };
+
+    use cw_utils::{Duration, PaymentError};
+
+    use cw721::{Cw721QueryMsg, TokensResponse};
+
+    use crate::msg::{
+        Allocation, Cw4QueryMsg, InstantiateMsg, MemberDiff, MemberResponse, RecurrenceMsg,
+    };
+    use crate::state::{RemotePowerConfig, RemotePowerSource};
+
+    use super::*;
+
+    #[test]
+    fn test_instantiate() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let result = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+        assert_eq!(result.attributes, vec![attr("action", "instantiate")])
+    }
+
+    #[test]
+    fn test_create_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        assert_eq!(
+            result.attributes,
+            vec![attr("action", "create_poll"), attr("poll_id", "1")]
+        );
+        assert_eq!(
+            result.events,
+            vec![Event::new("poll_created")
+                .add_attribute("poll_id", "1")
+                .add_attribute("question", "Do you love spark IBC")
+                .add_attribute("creator", "addr1")]
+        );
+
+        let msg = QueryMsg::GetConfig;
+
+        let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+
+        let config: Config = from_binary(&rs_binary).unwrap();
+
+        assert_eq!(config.admin_address, Addr::unchecked("addr1"));
+    }
+
+    #[test]
+    fn test_vote() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        assert_eq!(
+            result.attributes,
+            vec![attr("action", "create_poll"), attr("poll_id", "1")]
+        );
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+
+        let result = execute(deps.as_mut(), env, info, msg).unwrap();
+
+        assert_eq!(
+            result.attributes,
+            vec![attr("action", "vote"), attr("weight", "1")]
+        );
+        assert_eq!(
+            result.events,
+            vec![Event::new("vote_cast")
+                .add_attribute("poll_id", "1")
+                .add_attribute("voter", "addr1")
+                .add_attribute("choice", "yes")
+                .add_attribute("weight", "1")]
+        );
+    }
+
+    #[test]
+    fn test_unexpected_funds() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        let funds = mock_info("addr1", &[coin(10, "uatom")]);
+        let err = execute(deps.as_mut(), env.clone(), funds.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFunds { .. }));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env, funds, msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::Payment(PaymentError::NonPayable {})
+        ));
+    }
+
+    #[test]
+    fn get_vote() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = QueryMsg::GetPoll { poll_id: 1 };
+
+        let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+
+        assert!(resp.poll.is_none());
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        assert_eq!(
+            result.attributes,
+            vec![attr("action", "create_poll"), attr("poll_id", "1")]
+        );
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        assert_eq!(
+            result.attributes,
+            vec![attr("action", "vote"), attr("weight", "1")]
+        );
+
+        let msg = QueryMsg::GetPoll { poll_id: 1 };
+
+        let rs_binary = query(deps.as_ref(), env, msg).unwrap();
+
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+
+        assert!(resp.poll.is_some());
+    }
+
+    #[test]
+    fn test_get_poll_by_question() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        for question in ["Do you love spark IBC", "Do you love cosmwasm"] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love cosmwasm".to_string(),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::PollAlreadyExists { .. }));
+
+        let msg = QueryMsg::GetPollByQuestion {
+            question: "Do you love cosmwasm".to_string(),
+        };
+        let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.poll.unwrap().id, 2);
+
+        let msg = QueryMsg::GetPollByQuestion {
+            question: "Do you love rust".to_string(),
+        };
+        let rs_binary = query(deps.as_ref(), env, msg).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert!(resp.poll.is_none());
+    }
+
+    #[test]
+    fn test_vote_after_expiration() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            expiration: Some(Expiration::AtHeight(env.block.height)),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidExpiration { .. }));
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            expiration: Some(Expiration::AtHeight(env.block.height + 10)),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let rs_binary =
+            query(deps.as_ref(), env.clone(), QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert!(resp.is_open);
+
+        env.block.height += 10;
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::PollExpired { .. }));
+
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert!(!resp.is_open);
+    }
+
+    #[test]
+    fn test_multiple_choice_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Favourite chain".to_string(),
+            options: Some(vec!["juno".to_string(), "juno".to_string()]),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOption { .. }));
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Favourite chain".to_string(),
+            options: Some(vec!["juno".to_string(), "j".repeat(MAX_OPTION_LEN + 1)]),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOption { .. }));
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Favourite chain".to_string(),
+            options: Some(vec![
+                "juno".to_string(),
+                "osmosis".to_string(),
+                "stargaze".to_string(),
+            ]),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidChoice { .. }));
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Option(1),
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        let votes: Vec<u128> = resp
+            .poll
+            .unwrap()
+            .options
+            .iter()
+            .map(|option| option.votes.u128())
+            .collect();
+        assert_eq!(votes, vec![0, 1, 0]);
+    }
+
+    #[test]
+    fn test_double_vote() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::No,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyVoted { poll_id: 1 }));
+
+        execute(deps.as_mut(), env, mock_info("addr2", &[]), msg).unwrap();
+    }
+
+    #[test]
+    fn test_list_polls() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        for i in 0..15 {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: format!("Question {}", i),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        let msg = QueryMsg::ListPolls {
+            start_after: None,
+            limit: None,
+        };
+        let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let resp: ListPollsResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.polls.len(), DEFAULT_LIMIT as usize);
+        assert_eq!(resp.polls[0].id, 1);
+
+        let msg = QueryMsg::ListPolls {
+            start_after: Some(10),
+            limit: Some(100),
+        };
+        let rs_binary = query(deps.as_ref(), env, msg).unwrap();
+        let resp: ListPollsResponse = from_binary(&rs_binary).unwrap();
+        let ids: Vec<u64> = resp.polls.iter().map(|poll| poll.id).collect();
+        assert_eq!(ids, vec![11, 12, 13, 14, 15]);
+    }
+
+    #[test]
+    fn test_invalid_choice_rejected_at_deserialization() {
+        let msg: ExecuteMsg = from_slice(br#"{"vote":{"poll_id":1,"choice":"yes"}}"#).unwrap();
+        assert_eq!(
+            msg,
+            ExecuteMsg::Vote {
+                poll_id: 1,
+                choice: Choice::Yes,
+                proof: None,
+                memo: None,
+            }
+        );
+
+        let msg: ExecuteMsg =
+            from_slice(br#"{"vote":{"poll_id":1,"choice":{"option":2}}}"#).unwrap();
+        assert_eq!(
+            msg,
+            ExecuteMsg::Vote {
+                poll_id: 1,
+                choice: Choice::Option(2),
+                proof: None,
+                memo: None,
+            }
+        );
+
+        assert!(from_slice::<ExecuteMsg>(br#"{"vote":{"poll_id":1,"choice":"yse"}}"#).is_err());
+    }
+
+    #[test]
+    fn test_two_step_admin_transfer() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::ProposeNewAdmin {
+            address: "addr2".to_string(),
+            expiry: Some(Expiration::AtHeight(env.block.height + 10)),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr3", &[]),
+            ExecuteMsg::AcceptAdmin {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            ExecuteMsg::AcceptAdmin {},
+        )
+        .unwrap();
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "accept_admin"),
+                attr("previous_admin", "addr1"),
+                attr("new_admin", "addr2")
+            ]
+        );
+
+        let rs_binary = query(deps.as_ref(), env.clone(), QueryMsg::GetConfig).unwrap();
+        let config: Config = from_binary(&rs_binary).unwrap();
+        assert_eq!(config.admin_address, Addr::unchecked("addr2"));
+
+        // an expired proposal can no longer be accepted
+        let msg = ExecuteMsg::ProposeNewAdmin {
+            address: "addr3".to_string(),
+            expiry: Some(Expiration::AtHeight(env.block.height + 10)),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap();
+        env.block.height += 10;
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info("addr3", &[]),
+            ExecuteMsg::AcceptAdmin {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::AdminProposalExpired { .. }));
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let result = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        assert_eq!(result.attributes[0], attr("action", "migrate"));
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "999.0.0").unwrap();
+        let err = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::CannotMigrateFromNewer { .. }));
+
+        set_contract_version(deps.as_mut().storage, "crates.io:other", CONTRACT_VERSION).unwrap();
+        let err = migrate(deps.as_mut(), env, MigrateMsg {}).unwrap_err();
+        assert!(matches!(err, ContractError::CannotMigrate { .. }));
+    }
+
+    #[test]
+    fn test_close_poll() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            expiration: Some(Expiration::AtHeight(env.block.height + 10)),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        for (voter, choice) in [
+            ("addr1", Choice::Yes),
+            ("addr2", Choice::Yes),
+            ("addr3", Choice::No),
+        ] {
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice,
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::PollNotExpired { .. }));
+
+        env.block.height += 10;
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(result.events[0].ty, "poll_closed");
+        assert!(result.events[0]
+            .attributes
+            .contains(&attr("status", "passed")));
+
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed { .. }));
+
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.poll.unwrap().status, PollStatus::Passed);
+        assert!(!resp.is_open);
+    }
+
+    #[test]
+    fn test_vote_on_closed_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Favourite chain".to_string(),
+            options: Some(vec!["juno".to_string(), "osmosis".to_string()]),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Option(1),
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert!(result.events[0]
+            .attributes
+            .contains(&attr("winner", "osmosis")));
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Option(0),
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env, mock_info("addr2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed { .. }));
+    }
+
+    #[test]
+    fn test_stake_weighted_vote() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            voting_power: Some(VotingPowerSourceMsg::NativeStake),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let validator = Validator {
+            address: "validator".to_string(),
+            commission: Default::default(),
+            max_commission: Default::default(),
+            max_change_rate: Default::default(),
+        };
+        let delegation = |amount: u128, denom: &str| FullDelegation {
+            delegator: Addr::unchecked("addr1"),
+            validator: "validator".to_string(),
+            amount: coin(amount, denom),
+            can_redelegate: coin(0, denom),
+            accumulated_rewards: vec![],
+        };
+        deps.querier.update_staking(
+            "ustake",
+            &[validator],
+            &[
+                delegation(100, "ustake"),
+                delegation(50, "ustake"),
+                delegation(7, "uother"),
+            ],
+        );
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NoVotingPower { .. }));
+
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(result.attributes[1], attr("weight", "150"));
+
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.poll.unwrap().options[0].votes, Uint128::new(150));
+    }
+
+    #[test]
+    fn test_cw20_vote() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::VoteWithTokens { .. }));
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr2".to_string(),
+            amount: Uint128::new(40),
+            msg: to_binary(&ReceiveMsg::Vote {
+                poll_id: 1,
+                choice: Choice::Yes,
+            })
+            .unwrap(),
+        });
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("fake", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::UnsupportedToken { .. }));
+
+        let result = execute(deps.as_mut(), env.clone(), mock_info("token", &[]), msg).unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            cw20_transfer(
+                &Addr::unchecked("token"),
+                &Addr::unchecked("addr2"),
+                Uint128::new(40)
+            )
+            .unwrap()
+            .into()
+        );
+
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.poll.unwrap().options[0].votes, Uint128::new(40));
+    }
+
+    #[test]
+    fn test_cw20_escrow() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            escrow_cw20: true,
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr2".to_string(),
+            amount: Uint128::new(40),
+            msg: to_binary(&ReceiveMsg::Vote {
+                poll_id: 1,
+                choice: Choice::No,
+            })
+            .unwrap(),
+        });
+        let result = execute(deps.as_mut(), env.clone(), mock_info("token", &[]), msg).unwrap();
+        assert!(result.messages.is_empty());
+
+        let withdraw = ExecuteMsg::WithdrawTokens { poll_id: 1 };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            withdraw.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::PollStillOpen { .. }));
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            withdraw.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            cw20_transfer(
+                &Addr::unchecked("token"),
+                &Addr::unchecked("addr2"),
+                Uint128::new(40)
+            )
+            .unwrap()
+            .into()
+        );
+
+        let err = execute(deps.as_mut(), env, mock_info("addr2", &[]), withdraw).unwrap_err();
+        assert!(matches!(err, ContractError::NothingToWithdraw { .. }));
+    }
+
+    #[test]
+    fn test_nft_gated_vote() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "collection" => {
+                let tokens = match from_binary(msg).unwrap() {
+                    Cw721QueryMsg::Tokens { owner, .. } if owner == "addr1" => {
+                        vec!["1".to_string(), "2".to_string(), "3".to_string()]
+                    }
+                    _ => vec![],
+                };
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&TokensResponse { tokens }).unwrap(),
+                ))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "unexpected query".to_string(),
+            }),
+        });
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            nft_gate: Some(NftGateMsg {
+                collection: "collection".to_string(),
+                weight_by_count: true,
+            }),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NftGateNotMet { .. }));
+
+        let result = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(result.attributes[1], attr("weight", "3"));
+    }
+
+    #[test]
+    fn test_commit_reveal_vote() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            expiration: Some(Expiration::AtHeight(env.block.height + 10)),
+            commit_reveal: Some(CommitRevealMsg {
+                reveal_window: Duration::Height(10),
+                deposit: Some(coin(5, "ujuno")),
+            }),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::CommitRevealRequired { .. }));
+
+        let voter = Addr::unchecked("addr2");
+        let msg = ExecuteMsg::CommitVote {
+            poll_id: 1,
+            hash: commitment_hash(&voter, &Choice::Yes, "pepper").unwrap(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFunds { .. }));
+        let deposit = [coin(5, "ujuno")];
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &deposit),
+            msg,
+        )
+        .unwrap();
+
+        let reveal = ExecuteMsg::RevealVote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            salt: "pepper".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            reveal.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotRevealPhase { .. }));
+
+        env.block.height += 10;
+        let msg = ExecuteMsg::RevealVote {
+            poll_id: 1,
+            choice: Choice::No,
+            salt: "pepper".to_string(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::CommitmentMismatch { .. }));
+
+        let result = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), reveal).unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            BankMsg::Send {
+                to_address: "addr2".to_string(),
+                amount: deposit.to_vec(),
+            }
+            .into()
+        );
+
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert!(resp.is_open);
+        assert_eq!(resp.poll.unwrap().options[0].votes, Uint128::one());
+    }
+
+    #[test]
+    fn test_ranked_choice_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Favourite chain".to_string(),
+            options: Some(vec![
+                "juno".to_string(),
+                "osmosis".to_string(),
+                "stargaze".to_string(),
+            ]),
+            kind: PollKind::RankedChoice,
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        for invalid in [
+            Choice::Option(0),
+            Choice::Ranking(vec![]),
+            Choice::Ranking(vec![1, 1]),
+        ] {
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice: invalid,
+                proof: None,
+                memo: None,
+            };
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidChoice { .. }));
+        }
+
+        let ballots = [
+            ("addr1", vec![0]),
+            ("addr2", vec![0, 1]),
+            ("addr3", vec![1]),
+            ("addr4", vec![1, 0]),
+            ("addr5", vec![2, 1]),
+        ];
+        for (voter, ranking) in ballots {
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice: Choice::Ranking(ranking),
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        let result = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert!(result.events[0]
+            .attributes
+            .contains(&attr("winner", "osmosis")));
+        assert!(result.events[0]
+            .attributes
+            .contains(&attr("status", "closed")));
+    }
+
+    #[test]
+    fn test_quadratic_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Fund which project".to_string(),
+            options: Some(vec!["wallet".to_string(), "explorer".to_string()]),
+            kind: PollKind::Quadratic {
+                credits: 10,
+                credit_denom: Some("ujuno".to_string()),
+            },
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let expensive = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Allocation(vec![
+                Allocation {
+                    option: 0,
+                    votes: 4,
+                },
+                Allocation {
+                    option: 1,
+                    votes: 1,
+                },
+            ]),
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), expensive.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientCredits { .. }));
+
+        let msg = ExecuteMsg::DepositCredits { poll_id: 1 };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr1", &[coin(7, "ujuno")]),
+            msg,
+        )
+        .unwrap();
+        execute(deps.as_mut(), env.clone(), info.clone(), expensive).unwrap();
+
+        let msg = QueryMsg::GetCredits {
+            poll_id: 1,
+            voter: "addr1".to_string(),
+        };
+        let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let resp: GetCreditsResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(
+            resp,
+            GetCreditsResponse {
+                total: 17,
+                spent: 17,
+                remaining: 0
+            }
+        );
+
+        let rs_binary =
+            query(deps.as_ref(), env.clone(), QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        let votes: Vec<u128> = resp
+            .poll
+            .unwrap()
+            .options
+            .iter()
+            .map(|option| option.votes.u128())
+            .collect();
+        assert_eq!(votes, vec![4, 1]);
+
+        // credits bought for a cancelled poll are paid back
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Fund which project next".to_string(),
+            options: Some(vec!["wallet".to_string(), "explorer".to_string()]),
+            kind: PollKind::Quadratic {
+                credits: 10,
+                credit_denom: Some("ujuno".to_string()),
+            },
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::DepositCredits { poll_id: 2 };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[coin(5, "ujuno")]),
+            msg,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::CancelPoll { poll_id: 2 };
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert!(result.messages.iter().any(|msg| msg.msg
+            == CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr2".to_string(),
+                amount: vec![coin(5, "ujuno")],
+            })));
+        assert!(!CREDITS.has(&deps.storage, (2, &Addr::unchecked("addr2"))));
+
+        // and those of a closed poll are collected as fees
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(FEES.load(&deps.storage, "ujuno").unwrap(), Uint128::new(7));
+        let msg = ExecuteMsg::RemovePoll {
+            poll_id: 1,
+            reason: "spam".to_string(),
+            slash_deposit: false,
+        };
+        let result = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert!(!result
+            .messages
+            .iter()
+            .any(|msg| matches!(msg.msg, CosmosMsg::Bank(_))));
+        assert!(!CREDITS.has(&deps.storage, (1, &Addr::unchecked("addr1"))));
+    }
+
+    #[test]
+    fn test_approval_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Which chains should we support".to_string(),
+            options: Some(vec![
+                "juno".to_string(),
+                "osmosis".to_string(),
+                "stargaze".to_string(),
+            ]),
+            kind: PollKind::Approval,
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Approval(vec![0, 2, 0]),
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidChoice { .. }));
+
+        for (voter, approved) in [("addr1", vec![0, 2]), ("addr2", vec![2])] {
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice: Choice::Approval(approved),
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        let votes: Vec<u128> = resp
+            .poll
+            .unwrap()
+            .options
+            .iter()
+            .map(|option| option.votes.u128())
+            .collect();
+        assert_eq!(votes, vec![1, 0, 2]);
+    }
+
+    #[test]
+    fn test_veto_overrides_passing_result() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        for question in ["Do you love spark IBC", "Do you love cosmwasm"] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                veto_threshold: Some(Decimal::percent(30)),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        let ballots = [
+            (1, "addr1", Choice::Yes),
+            (1, "addr2", Choice::Yes),
+            (1, "addr3", Choice::Yes),
+            (1, "addr4", Choice::Abstain),
+            (1, "addr5", Choice::NoWithVeto),
+            (2, "addr1", Choice::Yes),
+            (2, "addr2", Choice::Yes),
+            (2, "addr3", Choice::NoWithVeto),
+            (2, "addr4", Choice::NoWithVeto),
+        ];
+        for (poll_id, voter, choice) in ballots {
+            let msg = ExecuteMsg::Vote {
+                poll_id,
+                choice,
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+
+        for (poll_id, status) in [(1, "passed"), (2, "rejected")] {
+            let msg = ExecuteMsg::ClosePoll { poll_id };
+            let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            assert!(result.events[0]
+                .attributes
+                .contains(&attr("status", status)));
+        }
+
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let poll = from_binary::<GetPollResponse>(&rs_binary)
+            .unwrap()
+            .poll
+            .unwrap();
+        assert_eq!(poll.abstain_votes, Uint128::one());
+        assert_eq!(poll.veto_votes, Uint128::one());
+    }
+
+    #[test]
+    fn test_quorum_and_threshold() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            threshold: Some(Decimal::percent(101)),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidThreshold {}));
+
+        let polls = [
+            ("Do you love spark IBC", Some(Uint128::new(4)), None),
+            (
+                "Do you love cosmwasm",
+                None,
+                Some(Decimal::from_ratio(2u128, 3u128)),
+            ),
+            ("Do you love rust", None, Some(Decimal::percent(60))),
+            ("Do you love wasm", None, Some(Decimal::one())),
+            ("Do you love tendermint", None, Some(Decimal::one())),
+        ];
+        for (question, quorum, threshold) in polls {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                quorum,
+                threshold,
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        for poll_id in 1..=5 {
+            for (voter, choice) in [
+                ("addr1", Choice::Yes),
+                ("addr2", Choice::Yes),
+                ("addr3", Choice::No),
+            ] {
+                // the last poll is unanimous
+                if poll_id == 5 && choice == Choice::No {
+                    continue;
+                }
+                let msg = ExecuteMsg::Vote {
+                    poll_id,
+                    choice,
+                    proof: None,
+                    memo: None,
+                };
+                execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+            }
+        }
+
+        // short of quorum, exactly at the threshold, above it, and short of
+        // and at a unanimous one
+        let statuses = [
+            (1, "rejected"),
+            (2, "rejected"),
+            (3, "passed"),
+            (4, "rejected"),
+            (5, "passed"),
+        ];
+        for (poll_id, status) in statuses {
+            let msg = ExecuteMsg::ClosePoll { poll_id };
+            let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            assert!(result.events[0]
+                .attributes
+                .contains(&attr("status", status)));
+        }
+    }
+
+    #[test]
+    fn test_config_defaults() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            default_threshold: Some(Decimal::percent(60)),
+            default_voting_period: Some(Duration::Height(100)),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let update = ExecuteMsg::UpdateConfig {
+            default_quorum: Some(Uint128::new(5)),
+            default_threshold: None,
+            default_voting_period: Some(Duration::Height(0)),
+            execution_delay: None,
+            veto_period: None,
+            max_multicall: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), update).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidVotingPeriod {}));
+
+        let update = ExecuteMsg::UpdateConfig {
+            default_quorum: Some(Uint128::new(5)),
+            default_threshold: None,
+            default_voting_period: None,
+            execution_delay: None,
+            veto_period: None,
+            max_multicall: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            update.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), env.clone(), info.clone(), update).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love cosmwasm".to_string(),
+            start: Some(Scheduled::AtHeight(env.block.height + 10)),
+            threshold: Some(Decimal::percent(75)),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let get_poll = |poll_id| {
+            let rs_binary =
+                query(deps.as_ref(), env.clone(), QueryMsg::GetPoll { poll_id }).unwrap();
+            from_binary::<GetPollResponse>(&rs_binary)
+                .unwrap()
+                .poll
+                .unwrap()
+        };
+        let poll = get_poll(1);
+        assert_eq!(
+            poll.expiration,
+            Expiration::AtHeight(env.block.height + 100)
+        );
+        assert_eq!(poll.threshold, Some(Decimal::percent(60)));
+        assert_eq!(poll.quorum, None);
+
+        let poll = get_poll(2);
+        assert_eq!(
+            poll.expiration,
+            Expiration::AtHeight(env.block.height + 110)
+        );
+        assert_eq!(poll.threshold, Some(Decimal::percent(75)));
+        assert_eq!(poll.quorum, Some(Uint128::new(5)));
+    }
+
+    #[test]
+    fn test_execute_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let payout = CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr2".to_string(),
+            amount: vec![coin(100, "ucosm")],
+        });
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            msgs: vec![payout.clone()],
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let execute_poll = ExecuteMsg::ExecutePoll { poll_id: 1 };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr3", &[]),
+            execute_poll.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::PollNotPassed { poll_id: 1 }));
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr3", &[]),
+            execute_poll.clone(),
+        )
+        .unwrap();
+        assert_eq!(result.messages[0].msg, payout);
+        assert_eq!(
+            result.events,
+            vec![Event::new("poll_executed")
+                .add_attribute("poll_id", "1")
+                .add_attribute("executed_by", "addr3")
+                .add_attribute("msgs", "1")]
+        );
+
+        let err = execute(deps.as_mut(), env, mock_info("addr3", &[]), execute_poll).unwrap_err();
+        assert!(matches!(err, ContractError::PollNotPassed { poll_id: 1 }));
+    }
+
+    #[test]
+    fn test_execution_timelock() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            execution_delay: Some(Duration::Height(10)),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            msgs: vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr2".to_string(),
+                amount: vec![coin(100, "ucosm")],
+            })],
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let execute_poll = ExecuteMsg::ExecutePoll { poll_id: 1 };
+        env.block.height += 9;
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            execute_poll.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::TimelockNotExpired {
+                poll_id: 1,
+                executable_at: Expiration::AtHeight(height),
+            } if height == env.block.height + 1
+        ));
+
+        env.block.height += 1;
+        let result = execute(deps.as_mut(), env, info, execute_poll).unwrap();
+        assert_eq!(result.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_veto_poll() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            veto_period: Some(Duration::Height(10)),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::GrantRole {
+            address: "council".to_string(),
+            role: Role::VetoCouncil,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        for question in ["Do you love spark IBC", "Do you love cosmwasm"] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                msgs: vec![CosmosMsg::Bank(BankMsg::Send {
+                    to_address: "addr2".to_string(),
+                    amount: vec![coin(100, "ucosm")],
+                })],
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+        for poll_id in [1, 2] {
+            let msg = ExecuteMsg::Vote {
+                poll_id,
+                choice: Choice::Yes,
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            let msg = ExecuteMsg::ClosePoll { poll_id };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            ExecuteMsg::VetoPoll { poll_id: 1 },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::ExecutePoll { poll_id: 1 },
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::VetoPeriodOpen { poll_id: 1, .. }
+        ));
+
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("council", &[]),
+            ExecuteMsg::VetoPoll { poll_id: 1 },
+        )
+        .unwrap();
+        assert_eq!(
+            result.events,
+            vec![Event::new("poll_vetoed")
+                .add_attribute("poll_id", "1")
+                .add_attribute("question", "Do you love spark IBC")
+                .add_attribute("vetoed_by", "council")]
+        );
+
+        env.block.height += 10;
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::ExecutePoll { poll_id: 1 },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::PollNotPassed { poll_id: 1 }));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("council", &[]),
+            ExecuteMsg::VetoPoll { poll_id: 2 },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::VetoPeriodOver { poll_id: 2 }));
+        execute(
+            deps.as_mut(),
+            env,
+            info,
+            ExecuteMsg::ExecutePoll { poll_id: 2 },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_execute_poll_failure() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            msgs: vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr2".to_string(),
+                amount: vec![coin(100, "ucosm")],
+            })],
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::ExecutePoll { poll_id: 1 };
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(result.messages[0].id, EXECUTE_POLL_REPLY_ID);
+        assert_eq!(result.messages[0].reply_on, ReplyOn::Error);
+
+        let failure = Reply {
+            id: EXECUTE_POLL_REPLY_ID,
+            result: SubMsgResult::Err("insufficient funds".to_string()),
+        };
+        let result = reply(deps.as_mut(), env.clone(), failure).unwrap();
+        assert_eq!(
+            result.events,
+            vec![Event::new("poll_execution_failed")
+                .add_attribute("poll_id", "1")
+                .add_attribute("error", "insufficient funds")]
+        );
+
+        let rs_binary =
+            query(deps.as_ref(), env.clone(), QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let poll = from_binary::<GetPollResponse>(&rs_binary)
+            .unwrap()
+            .poll
+            .unwrap();
+        assert_eq!(poll.status, PollStatus::Failed);
+
+        let unknown = Reply {
+            id: 99,
+            result: SubMsgResult::Err("unknown".to_string()),
+        };
+        let err = reply(deps.as_mut(), env, unknown).unwrap_err();
+        assert!(matches!(err, ContractError::UnknownReplyId { id: 99 }));
+    }
+
+    #[test]
+    fn test_sudo() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        sudo(deps.as_mut(), env.clone(), SudoMsg::Pause {}).unwrap();
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Paused {}));
+        sudo(deps.as_mut(), env.clone(), SudoMsg::Unpause {}).unwrap();
+
+        let msg = SudoMsg::SetConfig {
+            admin_address: Some("gov".to_string()),
+            default_quorum: Some(Uint128::new(3)),
+            default_threshold: None,
+            default_voting_period: None,
+            execution_delay: None,
+            veto_period: None,
+            max_multicall: None,
+        };
+        sudo(deps.as_mut(), env.clone(), msg).unwrap();
+        let rs_binary = query(deps.as_ref(), env.clone(), QueryMsg::GetConfig).unwrap();
+        let config: Config = from_binary(&rs_binary).unwrap();
+        assert_eq!(config.admin_address, Addr::unchecked("gov"));
+        assert_eq!(config.default_quorum, Some(Uint128::new(3)));
+
+        let msg = SudoMsg::RemovePoll {
+            poll_id: 1,
+            reason: "spam".to_string(),
+            slash_deposit: false,
+        };
+        let result = sudo(deps.as_mut(), env.clone(), msg).unwrap();
+        assert_eq!(result.events[0].ty, "poll_removed");
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.poll, None);
+    }
+
+    #[test]
+    fn test_remote_power() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            remote_power: Some(RemotePowerConfig {
+                connection_id: "connection-0".to_string(),
+                update_period: 100,
+                source: RemotePowerSource::Balance {
+                    denom: "uatom".to_string(),
+                },
+            }),
+            ..Default::default()
+        };
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let voter = mock_info("addr2", &[]);
+        let msg = ExecuteMsg::RegisterRemotePower {};
+        let result = execute(deps.as_mut(), env.clone(), voter.clone(), msg).unwrap();
+        assert_eq!(result.messages[0].id, REGISTER_QUERY_REPLY_ID);
+        assert!(matches!(
+            &result.messages[0].msg,
+            CosmosMsg::Stargate { type_url, .. }
+                if type_url == "/neutron.interchainqueries.MsgRegisterInterchainQuery"
+        ));
+
+        let reply_msg = Reply {
+            id: REGISTER_QUERY_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary::from(vec![0x08, 0x07])),
+            }),
+        };
+        reply(deps.as_mut(), env.clone(), reply_msg).unwrap();
+        let msg = ExecuteMsg::RegisterRemotePower {};
+        let err = execute(deps.as_mut(), env.clone(), voter.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::RemotePowerRegistered {}));
+
+        let err = sudo(
+            deps.as_mut(),
+            env.clone(),
+            SudoMsg::KvQueryResult { query_id: 8 },
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::UnknownQuery { query_id: 8 }));
+
+        // "150" as a Cosmos SDK 0.47 balance.
+        deps.querier = MockQuerier::new(&[]).with_custom_handler(|_| {
+            let result = br#"{"result":{"kv_results":[{"storage_prefix":"bank","key":"","value":"MTUw"}],"height":42,"revision":1}}"#;
+            SystemResult::Ok(ContractResult::Ok(Binary::from(result.to_vec())))
+        });
+        sudo(
+            deps.as_mut(),
+            env.clone(),
+            SudoMsg::KvQueryResult { query_id: 7 },
+        )
+        .unwrap();
+        let msg = QueryMsg::GetRemotePower {
+            voter: "addr2".to_string(),
+        };
+        let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let resp: GetRemotePowerResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(
+            resp.remote_power,
+            Some(RemotePower {
+                query_id: 7,
+                power: Uint128::new(150),
+                height: 42,
+            })
+        );
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), voter, msg).unwrap();
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.poll.unwrap().options[0].votes, Uint128::new(150));
+    }
+
+    #[test]
+    fn test_hooks() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::AddHook {
+            address: "hook".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::HookAlreadyRegistered { .. }));
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let hook_msg = HookExecuteMsg::PollHook(PollHookMsg::PollCreated {
+            poll_id: 1,
+            creator: "addr1".to_string(),
+        });
+        assert_eq!(
+            result.messages,
+            vec![SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: "hook".to_string(),
+                    msg: to_binary(&hook_msg).unwrap(),
+                    funds: vec![],
+                },
+                HOOK_REPLY_ID,
+            )]
+        );
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(result.messages.len(), 1);
+
+        let reply_msg = Reply {
+            id: HOOK_REPLY_ID,
+            result: SubMsgResult::Err("hook panicked".to_string()),
+        };
+        let result = reply(deps.as_mut(), env.clone(), reply_msg).unwrap();
+        assert_eq!(result.events[0].ty, "hook_failed");
+
+        let rs_binary = query(deps.as_ref(), env.clone(), QueryMsg::ListHooks {}).unwrap();
+        let resp: ListHooksResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.hooks, vec![Addr::unchecked("hook")]);
+
+        let msg = ExecuteMsg::RemoveHook {
+            address: "hook".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::HookNotRegistered { .. }));
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        let result = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert!(result.messages.is_empty());
+    }
+
+    #[test]
+    fn test_cw4_group_voting() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            voting_power: Some(VotingPowerSourceMsg::Cw4Group {
+                group: "group".to_string(),
+            }),
+            ..Default::default()
+        };
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == "group" => {
+                let weight = match from_binary(msg).unwrap() {
+                    Cw4QueryMsg::Member { addr, .. } if addr == "addr1" => Some(7),
+                    _ => None,
+                };
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&MemberResponse { weight }).unwrap(),
+                ))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "unexpected query".to_string(),
+            }),
+        });
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotMember {}));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.poll.unwrap().options[0].votes, Uint128::new(7));
+    }
+
+    #[test]
+    fn test_member_changed_hook() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            voting_power: Some(VotingPowerSourceMsg::Cw4Group {
+                group: "group".to_string(),
+            }),
+            ..Default::default()
+        };
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // The group itself knows nobody, only the hook does.
+        deps.querier.update_wasm(|_| {
+            SystemResult::Ok(ContractResult::Ok(
+                to_binary(&MemberResponse { weight: None }).unwrap(),
+            ))
+        });
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let hook = ExecuteMsg::MemberChangedHook(MemberChangedHookMsg {
+            diffs: vec![
+                MemberDiff {
+                    key: "addr1".to_string(),
+                    old: None,
+                    new: Some(5),
+                },
+                MemberDiff {
+                    key: "addr2".to_string(),
+                    old: Some(3),
+                    new: None,
+                },
+            ],
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), hook.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap();
+        execute(deps.as_mut(), env.clone(), mock_info("group", &[]), hook).unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::Unpause {},
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::NotMember {}));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.poll.unwrap().options[0].votes, Uint128::new(5));
+    }
+
+    #[test]
+    fn test_poll_voting_power_source() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "token" => {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&cw20::BalanceResponse {
+                        balance: Uint128::new(42),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "unexpected query".to_string(),
+            }),
+        });
+
+        for (question, voting_power) in [
+            ("Do you love spark IBC", None),
+            (
+                "Do you love cw20",
+                Some(VotingPowerSourceMsg::Cw20Balance {
+                    token: "token".to_string(),
+                }),
+            ),
+        ] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                voting_power,
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        for (poll_id, weight) in [(1, 1), (2, 42)] {
+            let msg = ExecuteMsg::Vote {
+                poll_id,
+                choice: Choice::Yes,
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            let rs_binary =
+                query(deps.as_ref(), env.clone(), QueryMsg::GetPoll { poll_id }).unwrap();
+            let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+            assert_eq!(resp.poll.unwrap().options[0].votes, Uint128::new(weight));
+        }
+    }
+
+    #[test]
+    fn test_stake_validator_allowlist() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let validator = |address: &str| Validator {
+            address: address.to_string(),
+            commission: Default::default(),
+            max_commission: Default::default(),
+            max_change_rate: Default::default(),
+        };
+        let delegation = |validator: &str, amount: u128| FullDelegation {
+            delegator: Addr::unchecked("addr1"),
+            validator: validator.to_string(),
+            amount: coin(amount, "ustake"),
+            can_redelegate: coin(0, "ustake"),
+            accumulated_rewards: vec![],
+        };
+        deps.querier.update_staking(
+            "ustake",
+            &[validator("aligned"), validator("other")],
+            &[delegation("aligned", 100), delegation("other", 50)],
+        );
+
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            voting_power: Some(VotingPowerSourceMsg::NativeStake),
+            stake_validators: vec!["unknown".to_string()],
+            ..Default::default()
+        };
+        let err = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::UnknownValidator { .. }));
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            voting_power: Some(VotingPowerSourceMsg::NativeStake),
+            stake_validators: vec!["aligned".to_string()],
+            ..Default::default()
+        };
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(result.attributes[1], attr("weight", "100"));
+
+        let msg = ExecuteMsg::UpdateStakeValidators {
+            add: vec!["other".to_string()],
+            remove: vec![],
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetConfig).unwrap();
+        let config: Config = from_binary(&rs_binary).unwrap();
+        assert_eq!(config.stake_validators, vec!["aligned", "other"]);
+    }
+
+    #[test]
+    fn test_lp_share_voting_power() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let lp_share = |rate| VotingPowerSourceMsg::LpShare {
+            pair: "pair".to_string(),
+            lp_token: "lp_token".to_string(),
+            asset: "ugov".to_string(),
+            rate,
+        };
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            voting_power: Some(lp_share(Decimal::percent(150))),
+            ..Default::default()
+        };
+        let err = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRate {}));
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            voting_power: Some(lp_share(Decimal::percent(50))),
+            ..Default::default()
+        };
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // 10 of 100 shares in a pool holding 4000ugov.
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "pair" => {
+                let pool = br#"{"assets":[{"info":{"token":{"contract_addr":"other"}},"amount":"1000"},{"info":{"native_token":{"denom":"ugov"}},"amount":"4000"}],"total_share":"100"}"#;
+                SystemResult::Ok(ContractResult::Ok(Binary::from(pool.to_vec())))
+            }
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "lp_token" => {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&cw20::BalanceResponse {
+                        balance: Uint128::new(10),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "unexpected query".to_string(),
+            }),
+        });
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let result = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(result.attributes[1], attr("weight", "200"));
+    }
+
+    #[test]
+    fn test_vesting_voting_power() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            voting_power: Some(VotingPowerSourceMsg::Vesting {
+                base: Box::new(VotingPowerSourceMsg::Cw20Balance {
+                    token: "token".to_string(),
+                }),
+                vesting: "vesting".to_string(),
+                multiplier: Some(Decimal::percent(25)),
+            }),
+            ..Default::default()
+        };
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "vesting" => {
+                let locked = br#"{"user":"addr1","voting_power":"400"}"#;
+                SystemResult::Ok(ContractResult::Ok(Binary::from(locked.to_vec())))
+            }
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "token" => {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&cw20::BalanceResponse {
+                        balance: Uint128::new(50),
+                    })
+                    .unwrap(),
+                ))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "unexpected query".to_string(),
+            }),
+        });
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let result = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(result.attributes[1], attr("weight", "150"));
+    }
+
+    #[test]
+    fn test_vote_many() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        for question in ["Do you love spark IBC", "Do you love CosmWasm"] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        let msg = ExecuteMsg::VoteMany { votes: vec![] };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidBatchSize { max: 10 }));
+
+        let msg = ExecuteMsg::VoteMany {
+            votes: vec![
+                VoteItem {
+                    poll_id: 3,
+                    choice: Choice::Yes,
+                    proof: None,
+                    memo: None,
+                },
+                VoteItem {
+                    poll_id: 1,
+                    choice: Choice::Yes,
+                    proof: None,
+                    memo: None,
+                },
+            ],
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PollNotFound { poll_id: 3 }));
+
+        let msg = ExecuteMsg::VoteMany {
+            votes: vec![
+                VoteItem {
+                    poll_id: 1,
+                    choice: Choice::Yes,
+                    proof: None,
+                    memo: None,
+                },
+                VoteItem {
+                    poll_id: 2,
+                    choice: Choice::No,
+                    proof: None,
+                    memo: None,
+                },
+            ],
+        };
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(result.attributes[3], ("poll_ids", "1,2"));
+        assert_eq!(result.events.len(), 2);
+
+        let msg = QueryMsg::GetVote {
+            poll_id: 2,
+            voter: "addr1".to_string(),
+        };
+        let resp: GetVoteResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(resp.ballot.unwrap().choice, Choice::No);
+    }
+
+    #[test]
+    fn test_multicall() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            max_multicall: Some(2),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let create_poll = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        let vote = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let msg = ExecuteMsg::Multicall {
+            msgs: vec![create_poll.clone(), vote.clone(), ExecuteMsg::Pause {}],
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::InvalidMulticallSize { max: 2 }
+        ));
+
+        let msg = ExecuteMsg::Multicall {
+            msgs: vec![ExecuteMsg::ExecutePoll { poll_id: 1 }],
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NotMulticallable {}));
+
+        let msg = ExecuteMsg::Multicall {
+            msgs: vec![create_poll, vote],
+        };
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(result.attributes.last().unwrap(), ("msgs", "2"));
+
+        let msg = QueryMsg::GetVote {
+            poll_id: 1,
+            voter: "addr1".to_string(),
+        };
+        let resp: GetVoteResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(resp.ballot.unwrap().choice, Choice::Yes);
+    }
+
+    #[test]
+    fn test_vote_memo() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: Some("x".repeat(513)),
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::FieldTooLong { max: 512, .. }));
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: Some("Fast finality".to_string()),
+        };
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert!(result.events[0]
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "memo" && attr.value == "Fast finality"));
+
+        let msg = QueryMsg::ListVoters {
+            poll_id: 1,
+            start_after: None,
+            limit: None,
+        };
+        let resp: ListVotersResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(resp.voters[0].memo, Some("Fast finality".to_string()));
+    }
+
+    #[test]
+    fn test_endorse() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        for question in [
+            "Do you love spark IBC",
+            "Do you love CosmWasm",
+            "Do you love Rust",
+        ] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        for (endorser, poll_id) in [("addr1", 2), ("addr2", 2), ("addr1", 3)] {
+            let msg = ExecuteMsg::Endorse { poll_id };
+            execute(deps.as_mut(), env.clone(), mock_info(endorser, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::Endorse { poll_id: 2 };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyEndorsed { poll_id: 2 }));
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 3 };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let msg = ExecuteMsg::Endorse { poll_id: 3 };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed { poll_id: 3 }));
+
+        let msg = QueryMsg::ListTrendingPolls { limit: None };
+        let resp: ListPollsResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        let polls: Vec<_> = resp
+            .polls
+            .iter()
+            .map(|poll| (poll.id, poll.endorsements))
+            .collect();
+        assert_eq!(polls, vec![(2, 2), (1, 0)]);
+    }
+
+    #[test]
+    fn test_clone_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            question_max_len: Some(25),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Which chain next".to_string(),
+            options: Some(vec!["juno".to_string(), "osmosis".to_string()]),
+            quorum: Some(Uint128::new(3)),
+            voters: Some(vec!["addr2".to_string()]),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::ClonePoll {
+            poll_id: 1,
+            overrides: PollOverrides::default(),
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::ClonePoll {
+            poll_id: 1,
+            overrides: PollOverrides {
+                question: Some("Which chain after that".to_string()),
+                quorum: Some(Uint128::new(5)),
+                ..Default::default()
+            },
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Which chain comes up next".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::ClonePoll {
+            poll_id: 4,
+            overrides: PollOverrides::default(),
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let clone = polls().load(&deps.storage, 2).unwrap();
+        assert_eq!(clone.question, "Which chain next #2");
+        assert_eq!(clone.options[1].label, "osmosis");
+        assert_eq!(clone.quorum, Some(Uint128::new(3)));
+        assert!(POLL_VOTERS.has(&deps.storage, (2, &Addr::unchecked("addr2"))));
+        let clone = polls().load(&deps.storage, 3).unwrap();
+        assert_eq!(clone.question, "Which chain after that");
+        assert_eq!(clone.quorum, Some(Uint128::new(5)));
+        // a question at the length limit is shortened for the number
+        let clone = polls().load(&deps.storage, 5).unwrap();
+        assert_eq!(clone.question, "Which chain comes up n #5");
+    }
+
+    #[test]
+    fn test_recurring_poll() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Weekly check-in".to_string(),
+            recurrence: Some(RecurrenceMsg {
+                interval: Duration::Height(0),
+                count: 2,
+            }),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRecurrence {}));
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Weekly check-in".to_string(),
+            expiration: Some(Expiration::AtHeight(env.block.height + 10)),
+            recurrence: Some(RecurrenceMsg {
+                interval: Duration::Height(100),
+                count: 2,
+            }),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        env.block.height += 10;
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        let result = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap();
+        assert_eq!(result.attributes.last().unwrap(), ("next_poll_id", "2"));
+        let next = polls().load(&deps.storage, 2).unwrap();
+        assert_eq!(next.question, "Weekly check-in #2");
+        assert_eq!(next.creator, Addr::unchecked("addr1"));
+        assert_eq!(
+            next.expiration,
+            Expiration::AtHeight(env.block.height + 100)
+        );
+        assert_eq!(next.series.unwrap().previous, Some(1));
+
+        // the last instance does not open another one
+        env.block.height += 100;
+        let msg = ExecuteMsg::ClosePoll { poll_id: 2 };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(POLL_COUNT.load(&deps.storage).unwrap(), 2);
+
+        let msg = QueryMsg::ListSeries {
+            poll_id: 2,
+            start_after: None,
+            limit: None,
+        };
+        let resp: ListPollsResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        let ids: Vec<_> = resp.polls.iter().map(|poll| poll.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_petition() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let petition = |options| CreatePollMsg {
+            question: "List us on more exchanges".to_string(),
+            options,
+            kind: PollKind::Petition {
+                target: Uint128::new(2),
+                weighted: false,
+            },
+            ..Default::default()
+        };
+        let msg = ExecuteMsg::CreatePoll(petition(Some(vec!["yes".to_string(), "no".to_string()])));
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidPetition {}));
+        let msg = ExecuteMsg::CreatePoll(petition(None));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let sign = || ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let result = execute(deps.as_mut(), env.clone(), mock_info("addr1", &[]), sign()).unwrap();
+        assert!(!result
+            .events
+            .iter()
+            .any(|event| event.ty == "petition_threshold_reached"));
+        let result = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), sign()).unwrap();
+        assert!(result.events.iter().any(|event| {
+            event.ty == "petition_threshold_reached"
+                && event.attributes.contains(&attr("signatures", "2"))
+        }));
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr3", &[]), sign()).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed { poll_id: 1 }));
+
+        let poll = polls().load(&deps.storage, 1).unwrap();
+        assert_eq!(poll.status, PollStatus::Passed);
+        assert_eq!(poll.options[0].votes, Uint128::new(2));
+    }
+
+    #[test]
+    fn test_conviction_poll() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Where should the treasury go".to_string(),
+            options: Some(vec!["grants".to_string(), "audits".to_string()]),
+            kind: PollKind::Conviction { max_blocks: None },
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let vote = |option| ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Option(option),
+            proof: None,
+            memo: None,
+        };
+        for voter in ["addr2", "addr5"] {
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), vote(0)).unwrap();
+        }
+        env.block.height += 10;
+        for voter in ["addr3", "addr4"] {
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), vote(1)).unwrap();
+        }
+        // switching options starts the ballot's conviction over
+        env.block.height += 2;
+        let msg = ExecuteMsg::UpdateVote {
+            poll_id: 1,
+            choice: Choice::Option(1),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("addr5", &[]), msg).unwrap();
+        env.block.height += 2;
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        let result = execute(deps.as_mut(), env, info, msg).unwrap();
+        let poll = polls().load(&deps.storage, 1).unwrap();
+        assert_eq!(poll.options[0].votes, Uint128::new(14));
+        assert_eq!(poll.options[1].votes, Uint128::new(10));
+        assert!(result.events.iter().any(|event| {
+            event.ty == "poll_closed" && event.attributes.contains(&attr("winner", "grants"))
+        }));
+    }
+
+    #[test]
+    fn test_weight_decay() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let poll = |expiration| {
+            ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: "Do you love spark IBC".to_string(),
+                expiration: Some(expiration),
+                weight_decay: Some(WeightDecayMsg {
+                    initial: Decimal::percent(200),
+                    closing: Decimal::percent(100),
+                }),
+                start: Some(Scheduled::AtHeight(env.block.height + 10)),
+                ..Default::default()
+            })
+        };
+        let expires_at = env.block.time.plus_seconds(3600);
+        let msg = poll(Expiration::AtTime(expires_at));
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidWeightDecay {}));
+        // factors that void or overflow the weights are rejected
+        for (initial, closing) in [(200, 0), (1100, 100)] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: "Do you love spark IBC".to_string(),
+                expiration: Some(Expiration::AtHeight(env.block.height + 110)),
+                weight_decay: Some(WeightDecayMsg {
+                    initial: Decimal::percent(initial),
+                    closing: Decimal::percent(closing),
+                }),
+                ..Default::default()
+            });
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidWeightDecay {}));
+        }
+        let msg = poll(Expiration::AtHeight(env.block.height + 110));
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // votes open at +10 with double weight, halfway through they get 1.5x
+        let token = mock_info("token", &[]);
+        let vote = |voter: &str, choice| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: voter.to_string(),
+                amount: Uint128::new(100),
+                msg: to_binary(&ReceiveMsg::Vote { poll_id: 1, choice }).unwrap(),
+            })
+        };
+        env.block.height += 10;
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            token.clone(),
+            vote("addr2", Choice::Yes),
+        )
+        .unwrap();
+        env.block.height += 50;
+        execute(deps.as_mut(), env.clone(), token, vote("addr3", Choice::No)).unwrap();
+
+        let ballot = |voter: &str| {
+            BALLOTS
+                .load(&deps.storage, (1, &Addr::unchecked(voter)))
+                .unwrap()
+        };
+        assert_eq!(ballot("addr2").weight, Uint128::new(200));
+        assert_eq!(ballot("addr3").weight, Uint128::new(150));
+        let poll = polls().load(&deps.storage, 1).unwrap();
+        assert_eq!(poll.options[1].votes, Uint128::new(150));
+    }
+
+    #[test]
+    fn test_min_power() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            min_power: Some(Uint128::new(1_000_000)),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let token = mock_info("token", &[]);
+        let vote = |voter: &str, amount: u128| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: voter.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(&ReceiveMsg::Vote {
+                    poll_id: 1,
+                    choice: Choice::Yes,
+                })
+                .unwrap(),
+            })
+        };
+        let err =
+            execute(deps.as_mut(), env.clone(), token.clone(), vote("addr2", 10)).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::InsufficientVotingPower { required, actual }
+                if required == Uint128::new(1_000_000) && actual == Uint128::new(10)
+        ));
+        execute(deps.as_mut(), env, token, vote("addr3", 1_000_000)).unwrap();
+        let poll = polls().load(&deps.storage, 1).unwrap();
+        assert_eq!(poll.options[0].votes, Uint128::new(1_000_000));
+    }
+
+    #[test]
+    fn test_turnout() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, .. } if contract_addr == "token" => {
+                let info = cw20::TokenInfoResponse {
+                    name: "token".to_string(),
+                    symbol: "TKN".to_string(),
+                    decimals: 6,
+                    total_supply: Uint128::new(1000),
+                };
+                SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest {
+                kind: "unexpected query".to_string(),
+            }),
+        });
+
+        let poll = |question: &str, turnout| {
+            ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                turnout: Some(turnout),
+                ..Default::default()
+            })
+        };
+        let msg = poll("Do you love spark IBC", Decimal::percent(110));
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTurnout {}));
+
+        // 10% of the supply must vote: a landslide of 90 is not enough
+        let token = mock_info("token", &[]);
+        let questions = ["Do you love spark IBC", "Do you love cosmwasm"];
+        let votes = [vec![("addr2", 90)], vec![("addr2", 40), ("addr3", 60)]];
+        for (poll_id, (question, votes)) in (1..).zip(questions.into_iter().zip(votes)) {
+            let msg = poll(question, Decimal::percent(10));
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            for (voter, amount) in votes {
+                let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+                    sender: voter.to_string(),
+                    amount: Uint128::new(amount),
+                    msg: to_binary(&ReceiveMsg::Vote {
+                        poll_id,
+                        choice: Choice::Yes,
+                    })
+                    .unwrap(),
+                });
+                execute(deps.as_mut(), env.clone(), token.clone(), msg).unwrap();
+            }
+            let msg = ExecuteMsg::ClosePoll { poll_id };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+        let status = |poll_id| polls().load(&deps.storage, poll_id).unwrap().status;
+        assert_eq!(status(1), PollStatus::QuorumNotMet);
+        assert_eq!(status(2), PollStatus::Passed);
+        assert_eq!(
+            polls().load(&deps.storage, 2).unwrap().total_power,
+            Some(Uint128::new(1000))
+        );
+    }
+
+    #[test]
+    fn test_option_details() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let candidate = |description: &str| OptionDetails {
+            description: Some(description.to_string()),
+            image: Some("ipfs://QmCandidate".to_string()),
+        };
+        let mut msg = CreatePollMsg {
+            question: "Who should chair the council".to_string(),
+            options: Some(vec!["alice".to_string(), "bob".to_string()]),
+            option_details: vec![candidate("Ran the grants program")],
+            ..Default::default()
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::CreatePoll(msg.clone()),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOptionDetails {}));
+        msg.option_details.push(OptionDetails::default());
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::CreatePoll(msg),
+        )
+        .unwrap();
+
+        let msg = QueryMsg::GetPoll { poll_id: 1 };
+        let resp: GetPollResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        let options = resp.poll.unwrap().options;
+        assert_eq!(
+            options[0].description.as_deref(),
+            Some("Ran the grants program")
+        );
+        assert_eq!(options[0].image.as_deref(), Some("ipfs://QmCandidate"));
+        assert_eq!(
+            options[1],
+            PollOption {
+                label: "bob".to_string(),
+                votes: Uint128::zero(),
+                description: None,
+                image: None
+            }
+        );
+
+        // updated options keep the details of the labels they still have
+        let msg = ExecuteMsg::UpdatePoll {
+            poll_id: 1,
+            question: None,
+            options: Some(vec!["carol".to_string(), "alice".to_string()]),
+            description: None,
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let msg = QueryMsg::GetPoll { poll_id: 1 };
+        let resp: GetPollResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        let options = resp.poll.unwrap().options;
+        assert_eq!(options[0].description, None);
+        assert_eq!(
+            options[1].description.as_deref(),
+            Some("Ran the grants program")
+        );
+        assert_eq!(options[1].image.as_deref(), Some("ipfs://QmCandidate"));
+    }
+
+    #[test]
+    fn test_update_and_retract_vote() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            expiration: Some(Expiration::AtHeight(env.block.height + 10)),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::RetractVote { poll_id: 1 };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::NoVote { .. }));
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::UpdateVote {
+            poll_id: 1,
+            choice: Choice::No,
+        };
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert!(result.attributes.contains(&attr("previous_choice", "yes")));
+        assert!(result.attributes.contains(&attr("new_choice", "no")));
+
+        let rs_binary =
+            query(deps.as_ref(), env.clone(), QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let poll = from_binary::<GetPollResponse>(&rs_binary)
+            .unwrap()
+            .poll
+            .unwrap();
+        assert_eq!(poll.options[0].votes, Uint128::zero());
+        assert_eq!(poll.options[1].votes, Uint128::one());
+
+        let msg = ExecuteMsg::RetractVote { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let rs_binary =
+            query(deps.as_ref(), env.clone(), QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let poll = from_binary::<GetPollResponse>(&rs_binary)
+            .unwrap()
+            .poll
+            .unwrap();
+        assert_eq!(total_votes(&poll).unwrap(), Uint128::zero());
+        assert_eq!(STATS.load(&deps.storage).unwrap().total_votes, 0);
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // a counter out of sync errors rather than wrapping around
+        STATS
+            .update(&mut deps.storage, |mut stats| -> StdResult<_> {
+                stats.total_votes = 0;
+                Ok(stats)
+            })
+            .unwrap();
+        let msg = ExecuteMsg::RetractVote { poll_id: 1 };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
+
+        env.block.height += 10;
+        let msg = ExecuteMsg::UpdateVote {
+            poll_id: 1,
+            choice: Choice::No,
+        };
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::PollExpired { .. }));
+    }
+
+    #[test]
+    fn test_snapshot_voting_power() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            snapshot_power: true,
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let bond = |amount: u128| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "addr2".to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(&ReceiveMsg::Bond {}).unwrap(),
+            })
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("token", &[]),
+            bond(30),
+        )
+        .unwrap();
+
+        env.block.height += 1;
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        env.block.height += 1;
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("token", &[]),
+            bond(70),
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let result = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap();
+        assert_eq!(result.attributes[1], attr("weight", "30"));
+
+        let msg = ExecuteMsg::Unbond {
+            amount: Uint128::new(101),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientBond { .. }));
+
+        let msg = ExecuteMsg::Unbond {
+            amount: Uint128::new(100),
+        };
+        let result = execute(deps.as_mut(), env, mock_info("addr2", &[]), msg).unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            cw20_transfer(
+                &Addr::unchecked("token"),
+                &Addr::unchecked("addr2"),
+                Uint128::new(100)
+            )
+            .unwrap()
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_poll_deposit_refunded_on_close() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            poll_deposit: Some(coin(100, "ujuno")),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[coin(50, "ujuno")]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFunds { .. }));
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[coin(100, "ujuno")]),
+            msg,
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        let result = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            BankMsg::Send {
+                to_address: "addr2".to_string(),
+                amount: vec![coin(100, "ujuno")],
+            }
+            .into()
+        );
+        assert!(!DEPOSITS.has(deps.as_ref().storage, 1));
+    }
+
+    #[test]
+    fn test_update_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love sprak IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::UpdatePoll {
+            poll_id: 1,
+            question: Some("Do you love spark IBC".to_string()),
+            options: Some(vec!["osmosis".to_string(), "juno".to_string()]),
+            description: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+        execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+
+        let rs_binary =
+            query(deps.as_ref(), env.clone(), QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        let poll = resp.poll.unwrap();
+        assert_eq!(poll.question, "Do you love spark IBC");
+        assert_eq!(poll.options[1].label, "juno");
+
+        let long = ExecuteMsg::UpdatePoll {
+            poll_id: 1,
+            question: None,
+            options: Some(vec!["osmosis".to_string(), "j".repeat(MAX_OPTION_LEN + 1)]),
+            description: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), long).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOption { .. }));
+
+        let vote = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Option(0),
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), vote).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PollHasVotes { poll_id: 1 }));
+
+        // new options go through the same checks as the poll's creation
+        let chains = CreatePollMsg {
+            question: "Which chain do you love".to_string(),
+            options: Some(vec![
+                "juno".to_string(),
+                "osmosis".to_string(),
+                "stargaze".to_string(),
+            ]),
+            ..Default::default()
+        };
+        let polls = [
+            CreatePollMsg {
+                question: "Do you love juno".to_string(),
+                kind: PollKind::Petition {
+                    target: Uint128::new(10),
+                    weighted: false,
+                },
+                options: None,
+                ..Default::default()
+            },
+            CreatePollMsg {
+                question: "Will juno flip osmosis".to_string(),
+                kind: PollKind::Prediction {
+                    denom: "ujuno".to_string(),
+                },
+                ..Default::default()
+            },
+            CreatePollMsg {
+                question: "Do you love osmosis".to_string(),
+                tie_break: TieBreak::FavorYes,
+                ..Default::default()
+            },
+            CreatePollMsg {
+                runoff_period: Some(Duration::Height(100)),
+                ..chains.clone()
+            },
+            CreatePollMsg {
+                question: "Which chain do you love most".to_string(),
+                allow_write_ins: true,
+                ..chains
+            },
+        ];
+        for msg in polls {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                ExecuteMsg::CreatePoll(msg),
+            )
+            .unwrap();
+        }
+        let update = |poll_id, options: &[&str]| ExecuteMsg::UpdatePoll {
+            poll_id,
+            question: None,
+            options: Some(options.iter().map(|option| option.to_string()).collect()),
+            description: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            update(2, &["signatures", "votes"]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::OptionsFixed { poll_id: 2 }));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            update(3, &["yes", "no", "maybe"]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::OptionsFixed { poll_id: 3 }));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            update(4, &["yes", "no", "maybe"]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTieBreak {}));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            update(5, &["juno", "osmosis"]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRunoff {}));
+        let err = execute(deps.as_mut(), env, info, update(6, &["yes", "no"])).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidWriteIns {}));
+    }
+
+    #[test]
+    fn test_cancel_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            poll_deposit: Some(coin(100, "ujuno")),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[coin(100, "ujuno")]),
+            msg,
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::CancelPoll { poll_id: 1 };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr3", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+
+        let result = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            BankMsg::Send {
+                to_address: "addr2".to_string(),
+                amount: vec![coin(100, "ujuno")],
+            }
+            .into()
+        );
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr3", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed { poll_id: 1 }));
+
+        let rs_binary =
+            query(deps.as_ref(), env.clone(), QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.poll.unwrap().status, PollStatus::Cancelled);
+
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetStats {}).unwrap();
+        let resp: GetStatsResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.open_polls, 0);
+    }
+
+    #[test]
+    fn test_remove_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            poll_deposit: Some(coin(100, "ujuno")),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            tags: vec!["ibc".to_string()],
+            ..Default::default()
+        });
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[coin(100, "ujuno")]),
+            msg,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("addr3", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::RemovePoll {
+            poll_id: 1,
+            reason: "spam".to_string(),
+            slash_deposit: true,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert!(result.messages.is_empty());
+        assert!(result.events[0]
+            .attributes
+            .contains(&attr("reason", "spam")));
+
+        let rs_binary =
+            query(deps.as_ref(), env.clone(), QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        assert!(resp.poll.is_none());
+        assert!(!BALLOTS.has(deps.as_ref().storage, (1, &Addr::unchecked("addr3"))));
+        assert!(!POLL_TAGS.has(deps.as_ref().storage, ("ibc", 1)));
+
+        let rs_binary = query(deps.as_ref(), env.clone(), QueryMsg::GetStats {}).unwrap();
+        let resp: GetStatsResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.open_polls, 0);
+
+        let msg = ExecuteMsg::WithdrawFees {
+            recipient: "treasury".to_string(),
+        };
+        let result = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            BankMsg::Send {
+                to_address: "treasury".to_string(),
+                amount: vec![coin(100, "ujuno")],
+            }
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_creation_fee_withdrawal() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            poll_deposit: Some(coin(100, "ujuno")),
+            creation_fee: Some(coin(5, "ujuno")),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        for (i, funds) in [[coin(100, "ujuno")], [coin(105, "ujuno")]]
+            .iter()
+            .enumerate()
+        {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: "Do you love spark IBC".to_string(),
+                ..Default::default()
+            });
+            let result = execute(deps.as_mut(), env.clone(), mock_info("addr2", funds), msg);
+            assert_eq!(result.is_ok(), i == 1);
+        }
+
+        let msg = ExecuteMsg::WithdrawFees {
+            recipient: "treasury".to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            BankMsg::Send {
+                to_address: "treasury".to_string(),
+                amount: vec![coin(5, "ujuno")],
+            }
+            .into()
+        );
+
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::NothingToWithdraw { .. }));
+    }
+
+    #[test]
+    fn test_pause() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Paused { .. }));
+
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            ExecuteMsg::Unpause {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            ExecuteMsg::Unpause {},
+        )
+        .unwrap();
+        execute(deps.as_mut(), env, info, msg).unwrap();
+    }
+
+    #[test]
+    fn test_roles() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::GrantRole {
+            address: "moderator".to_string(),
+            role: Role::Moderator,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("moderator", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = QueryMsg::GetRole {
+            address: "moderator".to_string(),
+        };
+        let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let resp: GetRoleResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.role, Some(Role::Moderator));
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // moderators can close polls early but not pause the contract
+        let moderator = mock_info("moderator", &[]);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            moderator.clone(),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized { .. }));
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), moderator.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::RevokeRole {
+            address: "moderator".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let msg = QueryMsg::GetRole {
+            address: "moderator".to_string(),
+        };
+        let rs_binary = query(deps.as_ref(), env, msg).unwrap();
+        let resp: GetRoleResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.role, None);
+    }
+
+    #[test]
+    fn test_blocklist() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
 
-    let config = Config {
-        admin_address: validated_admin_address,
-    };
-    CONFIG.save(deps.storage, &config)?;
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    Ok(Response::new().add_attribute("action", "instantiate"))
-}
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    msg: ExecuteMsg,
-) -> Result<Response, ContractError> {
-    match msg {
-        ExecuteMsg::CreatePoll { question } => execute_create_poll(deps, env, info, question),
-        ExecuteMsg::Vote { question, choice } => execute_vote(deps, env, info, question, &choice),
+        let msg = ExecuteMsg::AddToBlocklist {
+            address: "addr2".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let blocked = mock_info("addr2", &[]);
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love cosmwasm".to_string(),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), blocked.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Blocklisted { .. }));
+
+        let vote = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), blocked.clone(), vote.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Blocklisted { .. }));
+
+        let msg = ExecuteMsg::RemoveFromBlocklist {
+            address: "addr2".to_string(),
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        execute(deps.as_mut(), env, blocked, vote).unwrap();
     }
-}
 
-fn execute_create_poll(
-    deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    question: String,
-) -> Result<Response, ContractError> {
-    if POLLS.has(deps.storage, question.clone()) {
-        return Err(ContractError::CustomError {
-            val: "key already taken".to_string(),
+    #[test]
+    fn test_creator_allowlist() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            restrict_creation: true,
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let create = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
         });
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            create.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::CreationNotAllowed { .. }));
+
+        let msg = ExecuteMsg::UpdateCreators {
+            add: vec!["addr2".to_string(), "addr3".to_string()],
+            remove: vec!["addr3".to_string()],
+        };
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(
+            result.attributes,
+            vec![
+                attr("action", "update_creators"),
+                attr("added", "addr2"),
+                attr("added", "addr3"),
+                attr("removed", "addr3"),
+            ]
+        );
+
+        execute(deps.as_mut(), env, mock_info("addr2", &[]), create).unwrap();
     }
 
-    let poll = Poll {
-        question: question.clone(),
-        yes_votes: 0,
-        no_votes: 0,
-    };
+    #[test]
+    fn test_private_poll() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
 
-    POLLS.save(deps.storage, question, &poll)?;
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-    Ok(Response::new().add_attribute("action", "create_poll"))
-}
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            voters: Some(vec!["addr2".to_string()]),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-fn execute_vote(
-    deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    question: String,
-    choice: &str,
-) -> Result<Response, ContractError> {
-    if !POLLS.has(deps.storage, question.clone()) {
-        return Err(ContractError::CustomError {
-            val: "poll doesn't exist!".to_string(),
+        for (voter, eligible) in [("addr2", true), ("addr3", false)] {
+            let msg = QueryMsg::IsEligible {
+                poll_id: 1,
+                voter: voter.to_string(),
+            };
+            let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+            let resp: IsEligibleResponse = from_binary(&rs_binary).unwrap();
+            assert_eq!(resp.eligible, eligible);
+
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice: Choice::Yes,
+                proof: None,
+                memo: None,
+            };
+            let result = execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg);
+            assert_eq!(result.is_ok(), eligible);
+        }
+    }
+
+    #[test]
+    fn test_merkle_allowlist() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let leaf = |voter: &str| Binary::from(Sha256::digest(voter.as_bytes()).as_slice());
+        let (a, b) = (leaf("addr2"), leaf("addr3"));
+        let (first, second) = if a.as_slice() <= b.as_slice() {
+            (&a, &b)
+        } else {
+            (&b, &a)
+        };
+        let root = Binary::from(
+            Sha256::new()
+                .chain_update(first.as_slice())
+                .chain_update(second.as_slice())
+                .finalize()
+                .as_slice(),
+        );
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            merkle_root: Some(root),
+            ..Default::default()
         });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let vote = |proof: Option<Vec<Binary>>| ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof,
+            memo: None,
+        };
+        let voter = mock_info("addr2", &[]);
+        let err = execute(deps.as_mut(), env.clone(), voter.clone(), vote(None)).unwrap_err();
+        assert!(matches!(err, ContractError::NotEligible {}));
+        let msg = vote(Some(vec![a.clone()]));
+        let err = execute(deps.as_mut(), env.clone(), voter.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidMerkleProof {}));
+        execute(deps.as_mut(), env.clone(), voter, vote(Some(vec![b]))).unwrap();
+
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info("addr4", &[]),
+            vote(Some(vec![a])),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidMerkleProof {}));
     }
 
-    let mut poll = POLLS.load(deps.storage, question.clone())?;
+    #[test]
+    fn test_list_polls_by_creator() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-    match choice {
-        "yes" => poll.yes_votes += 1,
-        "no" => poll.no_votes += 1,
-        _ => {
-            return Err(ContractError::CustomError {
-                val: "invalid choice".to_string(),
+        for (creator, question) in [
+            ("addr2", "Do you love spark IBC"),
+            ("addr3", "Do you love cosmwasm"),
+            ("addr2", "Do you love rust"),
+        ] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
             });
+            execute(deps.as_mut(), env.clone(), mock_info(creator, &[]), msg).unwrap();
         }
-    }
 
-    POLLS.save(deps.storage, question, &poll)?;
-    Ok(Response::new().add_attribute("action", "vote"))
-}
+        let msg = QueryMsg::ListPollsByCreator {
+            creator: "addr2".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let resp: ListPollsResponse = from_binary(&rs_binary).unwrap();
+        let ids: Vec<u64> = resp.polls.iter().map(|poll| poll.id).collect();
+        assert_eq!(ids, vec![1, 3]);
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetPoll { question } => query_get_poll(deps, env, question),
-        QueryMsg::GetConfig => to_binary(&CONFIG.load(deps.storage)?),
+        let msg = QueryMsg::ListPollsByCreator {
+            creator: "addr2".to_string(),
+            start_after: Some(1),
+            limit: None,
+        };
+        let rs_binary = query(deps.as_ref(), env, msg).unwrap();
+        let resp: ListPollsResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.polls[0].question, "Do you love rust");
     }
-}
 
-fn query_get_poll(deps: Deps, _env: Env, question: String) -> StdResult<Binary> {
-    let poll = POLLS.may_load(deps.storage, question)?;
-    to_binary(&GetPollResponse { poll })
-}
+    #[test]
+    fn test_list_voters() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
 
-#[cfg(test)]
-mod tests {
-    use cosmwasm_std::{
-        attr, from_binary,
-        testing::{mock_dependencies, mock_env, mock_info}, Addr,
-    };
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-    use crate::msg::InstantiateMsg;
+        for (voter, choice) in [
+            ("addr1", Choice::Yes),
+            ("addr2", Choice::No),
+            ("addr3", Choice::Yes),
+        ] {
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice,
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
 
-    use super::*;
+        let msg = QueryMsg::ListVoters {
+            poll_id: 1,
+            start_after: Some("addr1".to_string()),
+            limit: Some(1),
+        };
+        let rs_binary = query(deps.as_ref(), env, msg).unwrap();
+        let resp: ListVotersResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(
+            resp.voters,
+            vec![VoterInfo {
+                voter: Addr::unchecked("addr2"),
+                choice: Choice::No,
+                weight: Uint128::new(1),
+                memo: None,
+            }]
+        );
+    }
 
     #[test]
-    fn test_instantiate() {
+    fn test_get_vote() {
         let mut deps = mock_dependencies();
         let env = mock_env();
         let info = mock_info("addr1", &[]);
         let msg = InstantiateMsg {
             admin_address: "addr1".to_string(),
+            ..Default::default()
         };
 
-        let result = instantiate(deps.as_mut(), env, info, msg).unwrap();
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        assert_eq!(result.attributes, vec![attr("action", "instantiate")])
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::No,
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        for (voter, ballot) in [
+            (
+                "addr1",
+                Some(Ballot {
+                    choice: Choice::No,
+                    weight: Uint128::new(1),
+                    height: env.block.height,
+                    memo: None,
+                }),
+            ),
+            ("addr2", None),
+        ] {
+            let msg = QueryMsg::GetVote {
+                poll_id: 1,
+                voter: voter.to_string(),
+            };
+            let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+            let resp: GetVoteResponse = from_binary(&rs_binary).unwrap();
+            assert_eq!(resp.ballot, ballot);
+        }
     }
 
     #[test]
-    fn test_create_poll() {
+    fn test_list_polls_by_status() {
         let mut deps = mock_dependencies();
         let env = mock_env();
         let info = mock_info("addr1", &[]);
         let msg = InstantiateMsg {
             admin_address: "addr1".to_string(),
+            ..Default::default()
         };
 
         let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let msg = ExecuteMsg::CreatePoll {
-            question: "Do you love spark IBC".to_string(),
+        for question in [
+            "Do you love spark IBC",
+            "Do you love cosmwasm",
+            "Do you love rust",
+        ] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+        let msg = ExecuteMsg::ClosePoll { poll_id: 2 };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        for (status, ids) in [
+            (PollStatus::Open, vec![1, 3]),
+            (PollStatus::Rejected, vec![2]),
+            (PollStatus::Passed, vec![]),
+        ] {
+            let msg = QueryMsg::ListPollsByStatus {
+                status,
+                start_after: None,
+                limit: None,
+            };
+            let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+            let resp: ListPollsResponse = from_binary(&rs_binary).unwrap();
+            let found: Vec<u64> = resp.polls.iter().map(|poll| poll.id).collect();
+            assert_eq!(found, ids);
+        }
+    }
+
+    #[test]
+    fn test_scheduled_start() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
         };
 
-        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        assert_eq!(result.attributes, vec![attr("action", "create_poll")]);
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            start: Some(Scheduled::AtHeight(env.block.height + 100)),
+            expiration: Some(Expiration::AtHeight(env.block.height + 50)),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidStart {}));
 
-        let msg = QueryMsg::GetConfig;
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            start: Some(Scheduled::AtHeight(env.block.height + 10)),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let vote = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), vote.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::PollNotStarted { poll_id: 1 }));
 
-        let config: Config = from_binary(&rs_binary).unwrap();
+        let list = |env: &Env, status| {
+            let msg = QueryMsg::ListPollsByStatus {
+                status,
+                start_after: None,
+                limit: None,
+            };
+            let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+            let resp: ListPollsResponse = from_binary(&rs_binary).unwrap();
+            resp.polls.iter().map(|poll| poll.id).collect::<Vec<u64>>()
+        };
+        assert_eq!(list(&env, PollStatus::Pending), vec![1]);
+        assert!(list(&env, PollStatus::Open).is_empty());
 
-        assert_eq!(config.admin_address, Addr::unchecked("addr1"));
+        env.block.height += 10;
+        assert!(list(&env, PollStatus::Pending).is_empty());
+        assert_eq!(list(&env, PollStatus::Open), vec![1]);
+
+        execute(deps.as_mut(), env, info, vote).unwrap();
     }
 
     #[test]
-    fn test_vote() {
+    fn test_get_tally() {
         let mut deps = mock_dependencies();
         let env = mock_env();
         let info = mock_info("addr1", &[]);
         let msg = InstantiateMsg {
             admin_address: "addr1".to_string(),
+            ..Default::default()
         };
 
         let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let msg = ExecuteMsg::CreatePoll {
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
             question: "Do you love spark IBC".to_string(),
-        };
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        for (voter, choice) in [
+            ("addr1", Choice::Yes),
+            ("addr2", Choice::Yes),
+            ("addr3", Choice::No),
+            ("addr4", Choice::Abstain),
+        ] {
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice,
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
 
-        assert_eq!(result.attributes, vec![attr("action", "create_poll")]);
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetTally { poll_id: 1 }).unwrap();
+        let resp: GetTallyResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.total_votes, Uint128::new(4));
+        assert_eq!(resp.abstain_votes, Uint128::new(1));
+        assert_eq!(
+            resp.options,
+            vec![
+                OptionTally {
+                    label: "yes".to_string(),
+                    votes: Uint128::new(2),
+                    percentage: Decimal::percent(50),
+                },
+                OptionTally {
+                    label: "no".to_string(),
+                    votes: Uint128::new(1),
+                    percentage: Decimal::percent(25),
+                },
+            ]
+        );
+        assert_eq!(resp.winner, Some("yes".to_string()));
+    }
 
-        let msg = ExecuteMsg::Vote {
-            question: "Do you love spark IBC".to_string(),
-            choice: "yes".to_string(),
+    #[test]
+    fn test_get_stats() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
         };
 
-        let result = execute(deps.as_mut(), env, info, msg).unwrap();
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        for question in ["Do you love spark IBC", "Do you love cosmwasm"] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        for (voter, poll_id) in [("addr1", 1), ("addr2", 1), ("addr1", 2)] {
+            let msg = ExecuteMsg::Vote {
+                poll_id,
+                choice: Choice::Yes,
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
 
-        assert_eq!(result.attributes, vec![attr("action", "vote")]);
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetStats {}).unwrap();
+        let resp: GetStatsResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(
+            resp,
+            GetStatsResponse {
+                total_polls: 2,
+                total_votes: 3,
+                open_polls: 1,
+                unique_voters: 2,
+            }
+        );
     }
 
     #[test]
-    fn get_vote() {
+    fn test_poll_metadata() {
         let mut deps = mock_dependencies();
         let env = mock_env();
         let info = mock_info("addr1", &[]);
         let msg = InstantiateMsg {
             admin_address: "addr1".to_string(),
+            ..Default::default()
         };
 
         let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let msg = QueryMsg::GetPoll {
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            tags: vec!["ibc".to_string(), "ibc".to_string()],
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTag { .. }));
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            link: Some("x".repeat(MAX_LINK_LEN + 1)),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::FieldTooLong { .. }));
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
             question: "Do you love spark IBC".to_string(),
+            description: Some("Spark IBC is a crowdfunding platform".to_string()),
+            tags: vec!["ibc".to_string(), "funding".to_string()],
+            link: Some(
+                "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi".to_string(),
+            ),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let rs_binary = query(deps.as_ref(), env, QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let poll = from_binary::<GetPollResponse>(&rs_binary)
+            .unwrap()
+            .poll
+            .unwrap();
+        assert_eq!(
+            poll.description.as_deref(),
+            Some("Spark IBC is a crowdfunding platform")
+        );
+        assert_eq!(poll.tags, vec!["ibc", "funding"]);
+        assert!(poll.link.unwrap().starts_with("ipfs://"));
+    }
+
+    #[test]
+    fn test_list_polls_by_tag() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
         };
 
-        let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
+        for (question, tags) in [
+            ("Do you love spark IBC", vec!["ibc", "funding"]),
+            ("Do you love cosmwasm", vec!["contracts"]),
+            ("Do you love interchain accounts", vec!["ibc"]),
+        ] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                tags: tags.into_iter().map(String::from).collect(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
 
-        assert!(resp.poll.is_none());
+        let msg = QueryMsg::ListPollsByTag {
+            tag: "ibc".to_string(),
+            start_after: None,
+            limit: None,
+        };
+        let rs_binary = query(deps.as_ref(), env, msg).unwrap();
+        let resp: ListPollsResponse = from_binary(&rs_binary).unwrap();
+        let ids: Vec<u64> = resp.polls.iter().map(|poll| poll.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+    }
 
-        let msg = ExecuteMsg::CreatePoll {
-            question: "Do you love spark IBC".to_string(),
+    #[test]
+    fn test_search_polls() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
         };
 
-        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        assert_eq!(result.attributes, vec![attr("action", "create_poll")]);
+        for question in [
+            "Do you love spark IBC",
+            "Should we fund cosmwasm",
+            "Do you love cosmwasm",
+            "Do we need IBC",
+        ] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
 
-        let msg = ExecuteMsg::Vote {
-            question: "Do you love spark IBC".to_string(),
-            choice: "yes".to_string(),
+        let search = |start_after: Option<&str>| {
+            let msg = QueryMsg::SearchPolls {
+                prefix: "Do you".to_string(),
+                start_after: start_after.map(String::from),
+                limit: Some(1),
+            };
+            let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+            let resp: ListPollsResponse = from_binary(&rs_binary).unwrap();
+            resp.polls
+                .into_iter()
+                .map(|poll| poll.question)
+                .collect::<Vec<_>>()
         };
+        assert_eq!(search(None), vec!["Do you love cosmwasm"]);
+        assert_eq!(
+            search(Some("Do you love cosmwasm")),
+            vec!["Do you love spark IBC"]
+        );
+        assert!(search(Some("Do you love spark IBC")).is_empty());
+    }
 
-        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    #[test]
+    fn test_question_validation() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            question_min_len: Some(5),
+            question_max_len: Some(30),
+            ..Default::default()
+        };
 
-        assert_eq!(result.attributes, vec![attr("action", "vote")]);
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let msg = QueryMsg::GetPoll {
-            question: "Do you love spark IBC".to_string(),
+        let create = |question: &str| {
+            ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
+            })
         };
 
-        let rs_binary = query(deps.as_ref(), env, msg).unwrap();
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), create("  IBC  ")).unwrap_err();
+        assert!(matches!(err, ContractError::QuestionTooShort { min: 5 }));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create(&"IBC ".repeat(10)),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::QuestionTooLong { max: 30 }));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            create("Do you\nlove IBC"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::QuestionControlCharacters {}));
 
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            create("  Do you love spark IBC  "),
+        )
+        .unwrap();
+        let msg = QueryMsg::GetPollByQuestion {
+            question: "Do you love spark IBC".to_string(),
+        };
+        let rs_binary = query(deps.as_ref(), env, msg).unwrap();
         let resp: GetPollResponse = from_binary(&rs_binary).unwrap();
-
         assert!(resp.poll.is_some());
     }
+
+    #[test]
+    fn test_vote_overflow() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let vote = |voter: &str| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: voter.to_string(),
+                amount: Uint128::MAX,
+                msg: to_binary(&ReceiveMsg::Vote {
+                    poll_id: 1,
+                    choice: Choice::Yes,
+                })
+                .unwrap(),
+            })
+        };
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("token", &[]),
+            vote("addr2"),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), env, mock_info("token", &[]), vote("addr3")).unwrap_err();
+        assert!(matches!(err, ContractError::Overflow(_)));
+    }
 }
@@ -0,0 +1,229 @@
+//! Read-only cw3 view of polls, so multisig and DAO tooling can list them as
+//! proposals. Polls do not know the total voting power, so `total_weight` is
+//! the weight cast so far.
+
+use cosmwasm_std::{to_binary, Binary, BlockInfo, Decimal, Deps, Env, Order, StdResult, Uint128};
+use cw_storage_plus::Bound;
+use cw_utils::ThresholdResponse;
+
+use crate::contract::{current_status, DEFAULT_LIMIT, MAX_LIMIT};
+use crate::msg::{
+    Choice, Cw3Status, Cw3Vote, ProposalListResponse, ProposalResponse, VoteInfo, VoteListResponse,
+    VoteResponse,
+};
+use crate::state::{polls, Ballot, Poll, PollStatus, BALLOTS, CONFIG};
+use crate::tally::total_votes;
+
+pub fn query_proposal(deps: Deps, env: Env, proposal_id: u64) -> StdResult<Binary> {
+    let poll = polls().load(deps.storage, proposal_id)?;
+    to_binary(&proposal(&poll, &env.block)?)
+}
+
+pub fn query_list_proposals(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let proposals = polls()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.and_then(|(_, poll)| proposal(&poll, &env.block)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ProposalListResponse { proposals })
+}
+
+pub fn query_vote(deps: Deps, _env: Env, proposal_id: u64, voter: String) -> StdResult<Binary> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let poll = polls().load(deps.storage, proposal_id)?;
+    let vote = BALLOTS
+        .may_load(deps.storage, (proposal_id, &voter))?
+        .and_then(|ballot| vote_info(&poll, voter.into_string(), ballot));
+    to_binary(&VoteResponse { vote })
+}
+
+/// Ballots that are not a yes, no, abstain or veto are left out.
+pub fn query_list_votes(
+    deps: Deps,
+    _env: Env,
+    proposal_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after
+        .map(|voter| deps.api.addr_validate(&voter))
+        .transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let poll = polls().load(deps.storage, proposal_id)?;
+
+    let votes = BALLOTS
+        .prefix(proposal_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .filter_map(|item| match item {
+            Ok((voter, ballot)) => vote_info(&poll, voter.into_string(), ballot).map(Ok),
+            Err(err) => Some(Err(err)),
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&VoteListResponse { votes })
+}
+
+/// The threshold new polls get by default.
+pub fn query_threshold(deps: Deps, _env: Env) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    to_binary(&ThresholdResponse::AbsolutePercentage {
+        percentage: config.default_threshold.unwrap_or(Decimal::percent(50)),
+        total_weight: 0,
+    })
+}
+
+fn proposal(poll: &Poll, block: &BlockInfo) -> StdResult<ProposalResponse> {
+    Ok(ProposalResponse {
+        id: poll.id,
+        title: poll.question.clone(),
+        description: poll.description.clone().unwrap_or_default(),
+        msgs: poll.msgs.clone(),
+        status: cw3_status(current_status(poll, block)),
+        expires: poll.expiration,
+        threshold: ThresholdResponse::AbsolutePercentage {
+            percentage: poll.threshold.unwrap_or(Decimal::percent(50)),
+            total_weight: to_weight(total_votes(poll)?),
+        },
+    })
+}
+
+fn cw3_status(status: PollStatus) -> Cw3Status {
+    match status {
+        PollStatus::Pending => Cw3Status::Pending,
+        PollStatus::Open => Cw3Status::Open,
+        PollStatus::Passed => Cw3Status::Passed,
+        PollStatus::Executed => Cw3Status::Executed,
+        PollStatus::Rejected
+        | PollStatus::Closed
+        | PollStatus::Vetoed
+        | PollStatus::Failed
+        | PollStatus::Cancelled
+        | PollStatus::QuorumNotMet => Cw3Status::Rejected,
+    }
+}
+
+fn vote_info(poll: &Poll, voter: String, ballot: Ballot) -> Option<VoteInfo> {
+    let vote = match ballot.choice {
+        Choice::Yes => Cw3Vote::Yes,
+        Choice::No => Cw3Vote::No,
+        Choice::Abstain => Cw3Vote::Abstain,
+        Choice::NoWithVeto => Cw3Vote::Veto,
+        Choice::Option(index) => match poll.options.get(index as usize)?.label.as_str() {
+            "yes" => Cw3Vote::Yes,
+            "no" => Cw3Vote::No,
+            _ => return None,
+        },
+        Choice::Ranking(_) | Choice::Allocation(_) | Choice::Approval(_) => return None,
+    };
+    Some(VoteInfo {
+        proposal_id: poll.id,
+        voter,
+        vote,
+        weight: to_weight(ballot.weight),
+    })
+}
+
+fn to_weight(amount: Uint128) -> u64 {
+    u64::try_from(amount.u128()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::from_binary;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{CreatePollMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+
+    use super::*;
+
+    #[test]
+    fn test_cw3_queries() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        for (voter, choice) in [("addr1", Choice::Yes), ("addr2", Choice::NoWithVeto)] {
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice,
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+
+        let msg = QueryMsg::Proposal { proposal_id: 1 };
+        let resp: ProposalResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(resp.title, "Do you love spark IBC");
+        assert_eq!(resp.status, Cw3Status::Open);
+        assert_eq!(
+            resp.threshold,
+            ThresholdResponse::AbsolutePercentage {
+                percentage: Decimal::percent(50),
+                total_weight: 2,
+            }
+        );
+
+        let msg = QueryMsg::ListProposals {
+            start_after: None,
+            limit: None,
+        };
+        let resp: ProposalListResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(resp.proposals.len(), 1);
+
+        let msg = QueryMsg::Vote {
+            proposal_id: 1,
+            voter: "addr1".to_string(),
+        };
+        let resp: VoteResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(resp.vote.unwrap().vote, Cw3Vote::Yes);
+
+        let msg = QueryMsg::ListVotes {
+            proposal_id: 1,
+            start_after: Some("addr1".to_string()),
+            limit: None,
+        };
+        let resp: VoteListResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(
+            resp.votes,
+            vec![VoteInfo {
+                proposal_id: 1,
+                voter: "addr2".to_string(),
+                vote: Cw3Vote::Veto,
+                weight: 1,
+            }]
+        );
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let msg = QueryMsg::Proposal { proposal_id: 1 };
+        let resp: ProposalResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(resp.status, Cw3Status::Rejected);
+    }
+}
@@ -0,0 +1,137 @@
+//! DAO DAO voting module queries, so a DAO core can use bonded tokens or the
+//! cw4 group as its voting power.
+
+use cosmwasm_std::{to_binary, Addr, Binary, Deps, Env, StdError, StdResult, Uint128};
+use cw2::get_contract_version;
+
+use crate::msg::{
+    Cw4QueryMsg, InfoResponse, MemberResponse, TotalPowerAtHeightResponse, TotalWeightResponse,
+    VotingPowerAtHeightResponse,
+};
+use crate::state::{Config, VotingPowerSource, CONFIG, TOTAL_POWER, VOTING_POWER};
+
+pub fn query_voting_power_at_height(
+    deps: Deps,
+    env: Env,
+    address: String,
+    height: Option<u64>,
+) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let height = height.unwrap_or(env.block.height);
+    let config = CONFIG.load(deps.storage)?;
+
+    let power = if config.snapshot_power {
+        VOTING_POWER
+            .may_load_at_height(deps.storage, &address, height)?
+            .unwrap_or_default()
+    } else {
+        let member: MemberResponse = deps.querier.query_wasm_smart(
+            group(&config)?,
+            &Cw4QueryMsg::Member {
+                addr: address.into_string(),
+                at_height: Some(height),
+            },
+        )?;
+        Uint128::from(member.weight.unwrap_or_default())
+    };
+    to_binary(&VotingPowerAtHeightResponse { power, height })
+}
+
+pub fn query_total_power_at_height(deps: Deps, env: Env, height: Option<u64>) -> StdResult<Binary> {
+    let height = height.unwrap_or(env.block.height);
+    let config = CONFIG.load(deps.storage)?;
+
+    let power = if config.snapshot_power {
+        TOTAL_POWER
+            .may_load_at_height(deps.storage, height)?
+            .unwrap_or_default()
+    } else {
+        let total: TotalWeightResponse = deps.querier.query_wasm_smart(
+            group(&config)?,
+            &Cw4QueryMsg::TotalWeight {
+                at_height: Some(height),
+            },
+        )?;
+        Uint128::from(total.weight)
+    };
+    to_binary(&TotalPowerAtHeightResponse { power, height })
+}
+
+pub fn query_info(deps: Deps, _env: Env) -> StdResult<Binary> {
+    let info = get_contract_version(deps.storage)?;
+    to_binary(&InfoResponse { info })
+}
+
+/// Only bonded tokens and cw4 groups keep a history of voting power.
+fn group(config: &Config) -> StdResult<&Addr> {
+    match &config.voting_power {
+        VotingPowerSource::Cw4Group { group } => Ok(group),
+        _ => Err(StdError::generic_err(
+            "Voting power is only tracked for bonded tokens and cw4 groups",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, to_binary};
+    use cw20::Cw20ReceiveMsg;
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg};
+
+    use super::*;
+
+    #[test]
+    fn test_voting_module_queries() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            snapshot_power: true,
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), mock_info("addr1", &[]), msg).unwrap();
+
+        let bond_height = env.block.height;
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr2".to_string(),
+            amount: Uint128::new(100),
+            msg: to_binary(&ReceiveMsg::Bond {}).unwrap(),
+        });
+        execute(deps.as_mut(), env.clone(), mock_info("token", &[]), msg).unwrap();
+        env.block.height += 1;
+
+        let msg = QueryMsg::VotingPowerAtHeight {
+            address: "addr2".to_string(),
+            height: None,
+        };
+        let resp: VotingPowerAtHeightResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(resp.power, Uint128::new(100));
+        assert_eq!(resp.height, env.block.height);
+        let msg = QueryMsg::VotingPowerAtHeight {
+            address: "addr2".to_string(),
+            height: Some(bond_height),
+        };
+        let resp: VotingPowerAtHeightResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(resp.power, Uint128::zero());
+
+        let msg = ExecuteMsg::Unbond {
+            amount: Uint128::new(40),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap();
+        env.block.height += 1;
+        let msg = QueryMsg::TotalPowerAtHeight { height: None };
+        let resp: TotalPowerAtHeightResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(resp.power, Uint128::new(60));
+
+        let resp: InfoResponse =
+            from_binary(&query(deps.as_ref(), env, QueryMsg::Info {}).unwrap()).unwrap();
+        assert_eq!(resp.info.contract, "crates.io:mycosmwasm");
+    }
+}
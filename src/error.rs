@@ -1,4 +1,5 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use cw_utils::{Expiration, ParseReplyError, PaymentError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,6 +7,441 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
-    #[error("Custom Error val: {val:?}")]
-    CustomError { val: String },
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("{0}")]
+    ParseReply(#[from] ParseReplyError),
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Cannot migrate from contract {contract}")]
+    CannotMigrate { contract: String },
+
+    #[error("Cannot migrate from newer version {version}")]
+    CannotMigrateFromNewer { version: String },
+
+    #[error("Invalid question length bounds")]
+    InvalidQuestionBounds {},
+
+    #[error("Recurring polls need a non-zero interval and count")]
+    InvalidRecurrence {},
+
+    #[error("Voting period must not be zero")]
+    InvalidVotingPeriod {},
+
+    #[error("Snapshot voting power needs a cw20 token")]
+    SnapshotRequiresToken {},
+
+    #[error("No token configured")]
+    NoTokenConfigured {},
+
+    #[error("Unsupported token")]
+    UnsupportedToken {},
+
+    #[error("Admin proposal expired")]
+    AdminProposalExpired {},
+
+    #[error("Address {address} has no role")]
+    NoRole { address: String },
+
+    #[error("Address {address} is blocklisted")]
+    Blocklisted { address: String },
+
+    #[error("Hook {address} is already registered")]
+    HookAlreadyRegistered { address: String },
+
+    #[error("Hook {address} is not registered")]
+    HookNotRegistered { address: String },
+
+    #[error("Unknown validator {validator}")]
+    UnknownValidator { validator: String },
+
+    #[error("Not allowed to create polls")]
+    CreationNotAllowed {},
+
+    #[error("Poll {poll_id} does not exist")]
+    PollNotFound { poll_id: u64 },
+
+    #[error("Survey {survey_id} does not exist")]
+    SurveyNotFound { survey_id: u64 },
+
+    #[error("Survey {survey_id} is closed")]
+    SurveyClosed { survey_id: u64 },
+
+    #[error("A survey needs between 1 and {max} questions")]
+    InvalidQuestionCount { max: usize },
+
+    #[error("Expected one answer for each of the {expected} questions")]
+    AnswerCountMismatch { expected: usize },
+
+    #[error("Already answered survey {survey_id}")]
+    AlreadyAnswered { survey_id: u64 },
+
+    #[error("Round {round_id} does not exist")]
+    RoundNotFound { round_id: u64 },
+
+    #[error("Round {round_id} is closed")]
+    RoundClosed { round_id: u64 },
+
+    #[error("Round {round_id} is still open")]
+    RoundStillOpen { round_id: u64 },
+
+    #[error("Round {round_id} cannot list more projects")]
+    RoundFull { round_id: u64 },
+
+    #[error("Project {project} does not exist in round {round_id}")]
+    ProjectNotFound { round_id: u64, project: u32 },
+
+    #[error("Template {template_id} does not exist")]
+    TemplateNotFound { template_id: u64 },
+
+    #[error("Campaign {campaign_id} does not exist")]
+    CampaignNotFound { campaign_id: u64 },
+
+    #[error("Campaign {campaign_id} cannot hold more polls")]
+    CampaignFull { campaign_id: u64 },
+
+    #[error("Poll {poll_id} must be an open poll of yours, with no votes and in no campaign")]
+    InvalidCampaignPoll { poll_id: u64 },
+
+    #[error("Invalid name {name:?}")]
+    InvalidName { name: String },
+
+    #[error("Comment {comment_id} does not exist on poll {poll_id}")]
+    CommentNotFound { poll_id: u64, comment_id: u64 },
+
+    #[error("Comment is empty")]
+    EmptyComment {},
+
+    #[error("A poll with question {question:?} already exists")]
+    PollAlreadyExists { question: String },
+
+    #[error("Question must be at least {min} bytes")]
+    QuestionTooShort { min: u32 },
+
+    #[error("Question must be at most {max} bytes")]
+    QuestionTooLong { max: u32 },
+
+    #[error("Question contains control characters")]
+    QuestionControlCharacters {},
+
+    #[error("A poll needs between {min} and {max} options")]
+    InvalidOptionCount { min: usize, max: usize },
+
+    #[error("Petitions take a non-zero target and no options")]
+    InvalidPetition {},
+
+    #[error("Poll {poll_id} does not accept write-ins")]
+    WriteInsNotAllowed { poll_id: u64 },
+
+    #[error("Write-ins need a single choice poll without commit-reveal, a hub or an outcome tied to its options")]
+    InvalidWriteIns {},
+
+    #[error("Options cannot be added to this poll, its outcome depends on its options")]
+    InvalidNewOptions {},
+
+    #[error("Poll {poll_id} does not accept new options")]
+    NewOptionsNotAllowed { poll_id: u64 },
+
+    #[error("Option details must be given for every option or none")]
+    InvalidOptionDetails {},
+
+    #[error("A poll cannot have more than {max} options")]
+    TooManyOptions { max: usize },
+
+    #[error("Invalid option {label:?}")]
+    InvalidOption { label: String },
+
+    #[error("{field} is longer than {max} bytes")]
+    FieldTooLong { field: String, max: usize },
+
+    #[error("A poll can have at most {max} tags")]
+    TooManyTags { max: usize },
+
+    #[error("Invalid tag {tag:?}")]
+    InvalidTag { tag: String },
+
+    #[error("Veto threshold must be in (0, 1]")]
+    InvalidVetoThreshold {},
+
+    #[error("Turnout must be in (0, 1]")]
+    InvalidTurnout {},
+
+    #[error("This tie-break does not apply to the poll")]
+    InvalidTieBreak {},
+
+    #[error("Runoffs need a non-zero period and a single choice poll with more than two options")]
+    InvalidRunoff {},
+
+    #[error("Conditional polls open with their parent and cannot be scheduled or commit-reveal")]
+    InvalidCondition {},
+
+    #[error("Poll {poll_id} is waiting on randomness to break a tie")]
+    TieDrawPending { poll_id: u64 },
+
+    #[error("Threshold must be in (0, 1]")]
+    InvalidThreshold {},
+
+    #[error("Conversion rate must be in (0, 1]")]
+    InvalidRate {},
+
+    #[error("Merkle root must be a sha256 hash and cannot be combined with voters")]
+    InvalidMerkleRoot {},
+
+    #[error("Invalid merkle proof")]
+    InvalidMerkleProof {},
+
+    #[error("Expiration already passed")]
+    InvalidExpiration {},
+
+    #[error("Commit-reveal polls need an expiration")]
+    CommitRevealRequiresExpiration {},
+
+    #[error("Bounties need a non-zero amount sent with CreatePoll")]
+    InvalidBounty {},
+
+    #[error("Expected funds: {required}")]
+    InvalidFunds { required: String },
+
+    #[error("Poll {poll_id} is closed")]
+    PollClosed { poll_id: u64 },
+
+    #[error("Poll expired")]
+    PollExpired {},
+
+    #[error("Poll has not expired yet")]
+    PollNotExpired {},
+
+    #[error("Poll is still open")]
+    PollStillOpen {},
+
+    #[error("Poll does not use commit-reveal voting")]
+    NotCommitReveal {},
+
+    #[error("Poll requires commit-reveal voting")]
+    CommitRevealRequired {},
+
+    #[error("Commit phase is over")]
+    CommitPhaseOver {},
+
+    #[error("Poll is not in its reveal phase")]
+    NotRevealPhase {},
+
+    #[error("No vote committed")]
+    NoCommitment {},
+
+    #[error("Reveal does not match commitment")]
+    CommitmentMismatch {},
+
+    #[error("Revealed votes are final")]
+    RevealedVoteFinal {},
+
+    #[error("Invalid choice")]
+    InvalidChoice {},
+
+    #[error("Already voted on poll {poll_id}")]
+    AlreadyVoted { poll_id: u64 },
+
+    #[error("Already endorsed poll {poll_id}")]
+    AlreadyEndorsed { poll_id: u64 },
+
+    #[error("A batch needs between 1 and {max} votes")]
+    InvalidBatchSize { max: usize },
+
+    #[error("A multicall needs between 1 and {max} messages")]
+    InvalidMulticallSize { max: u32 },
+
+    #[error("Message cannot be part of a multicall")]
+    NotMulticallable {},
+
+    #[error("No vote on poll {poll_id}")]
+    NoVote { poll_id: u64 },
+
+    #[error("Poll {poll_id} has not started")]
+    PollNotStarted { poll_id: u64 },
+
+    #[error("Poll must start before it expires")]
+    InvalidStart {},
+
+    #[error("Weight decay needs factors in (0, 10] and an expiration of the same kind as the poll's start")]
+    InvalidWeightDecay {},
+
+    #[error("Poll {poll_id} has not passed")]
+    PollNotPassed { poll_id: u64 },
+
+    #[error("Poll {poll_id} cannot be executed before {executable_at}")]
+    TimelockNotExpired {
+        poll_id: u64,
+        executable_at: Expiration,
+    },
+
+    #[error("Poll {poll_id} can be vetoed until {veto_deadline}")]
+    VetoPeriodOpen {
+        poll_id: u64,
+        veto_deadline: Expiration,
+    },
+
+    #[error("Poll {poll_id} can no longer be vetoed")]
+    VetoPeriodOver { poll_id: u64 },
+
+    #[error("Only unordered channels are supported")]
+    OnlyUnorderedChannel {},
+
+    #[error("Expected IBC channel version {expected}, got {actual}")]
+    InvalidIbcVersion { expected: String, actual: String },
+
+    #[error("Remote votes are not supported with token, stake or remote power weighted voting")]
+    RemoteVotingUnsupported {},
+
+    #[error("Channel {channel_id} is not connected")]
+    UnknownChannel { channel_id: String },
+
+    #[error("Hub poll {poll_id} on {channel_id} already has a spoke poll")]
+    HubPollLinked { channel_id: String, poll_id: u64 },
+
+    #[error("Poll {poll_id} is not linked to a hub")]
+    NoHub { poll_id: u64 },
+
+    #[error("Remote tallies are not supported for ranked-choice polls")]
+    RemoteTallyUnsupported {},
+
+    #[error("Remote tally does not match the poll's options")]
+    RemoteTallyMismatch {},
+
+    #[error("Tally {sequence} is older than the last one received")]
+    StaleTally { sequence: u64 },
+
+    #[error("Remote voting power is not configured")]
+    NoRemotePower {},
+
+    #[error("Remote voting power is already registered")]
+    RemotePowerRegistered {},
+
+    #[error("Unknown interchain query {query_id}")]
+    UnknownQuery { query_id: u64 },
+
+    #[error("Unknown reply id {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Poll {poll_id} has no messages to execute")]
+    NothingToExecute { poll_id: u64 },
+
+    #[error("Poll {poll_id} already has votes")]
+    PollHasVotes { poll_id: u64 },
+
+    #[error("The options of poll {poll_id} cannot be changed")]
+    OptionsFixed { poll_id: u64 },
+
+    #[error("Not eligible to vote on this poll")]
+    NotEligible {},
+
+    #[error("Voter holds no tokens of the gating collection")]
+    NftGateNotMet {},
+
+    #[error("Not a member of the voting group")]
+    NotMember {},
+
+    #[error("No voting power")]
+    NoVotingPower {},
+
+    #[error("Votes must be cast by sending tokens")]
+    VoteWithTokens {},
+
+    #[error("Bond tokens to vote")]
+    BondToVote {},
+
+    #[error("Cannot unbond more than bonded")]
+    InsufficientBond {},
+
+    #[error("Poll does not sell credits")]
+    NoCreditSales {},
+
+    #[error("Deposit too large")]
+    DepositTooLarge {},
+
+    #[error("Allocation costs {cost} credits")]
+    InsufficientCredits { cost: u64 },
+
+    #[error("Poll {poll_id} is not a prediction market")]
+    NotPredictionMarket { poll_id: u64 },
+
+    #[error("Already staked on option {option} of poll {poll_id}")]
+    AlreadyStaked { poll_id: u64, option: u32 },
+
+    #[error("Poll {poll_id} has not been resolved")]
+    MarketNotResolved { poll_id: u64 },
+
+    #[error("Poll {poll_id} is already resolved")]
+    MarketResolved { poll_id: u64 },
+
+    #[error("Poll {poll_id} has no rewards")]
+    NoRewards { poll_id: u64 },
+
+    #[error("Rewards must be funded in the pool's asset")]
+    RewardAssetMismatch {},
+
+    #[error("Rewards of poll {poll_id} cannot be claimed")]
+    RewardsNotClaimable { poll_id: u64 },
+
+    #[error("Already claimed rewards of poll {poll_id}")]
+    AlreadyClaimed { poll_id: u64 },
+
+    #[error("Rewards of poll {poll_id} can still be claimed")]
+    ClaimPeriodOpen { poll_id: u64 },
+
+    #[error("No Nois proxy configured")]
+    NoisNotConfigured {},
+
+    #[error("The reputation bonus cannot multiply weights by less than one")]
+    InvalidReputationBonus {},
+
+    #[error("The streak bonus cannot multiply weights by less than one")]
+    InvalidStreakBonus {},
+
+    #[error("Weight caps must be non-zero, shares of supply at most one")]
+    InvalidWeightCap {},
+
+    #[error("The supply of this poll's voting power is unknown")]
+    UnknownSupply {},
+
+    #[error("Voting needs a voting power of at least {required}, got {actual}")]
+    InsufficientVotingPower { required: Uint128, actual: Uint128 },
+
+    #[error("A raffle needs at least one winner")]
+    InvalidRaffle {},
+
+    #[error("Poll {poll_id} already has a raffle")]
+    RaffleExists { poll_id: u64 },
+
+    #[error("Unknown randomness job {job_id}")]
+    UnknownJob { job_id: String },
+
+    #[error("Incentive {incentive_id} not found")]
+    IncentiveNotFound { incentive_id: u64 },
+
+    #[error("Incentives need a target, a non-zero amount per poll and a future expiration")]
+    InvalidIncentive {},
+
+    #[error("Incentive {incentive_id} has not expired")]
+    IncentiveActive { incentive_id: u64 },
+
+    #[error("Nothing to withdraw")]
+    NothingToWithdraw {},
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
 }
@@ -0,0 +1,368 @@
+//! Factory mode: large polls get a fresh instance of this contract each, so
+//! their ballots live in separate storage and do not slow down the others.
+//!
+//! The child is instantiated with the parent's own code id and voting settings,
+//! and the poll is created in it once the instantiation reply names its address.
+//! Each child's poll takes the next poll id here as well, and the child is
+//! instantiated with `Instantiate2` salted by it, so its address is known up
+//! front. Bonding is per contract, so children never take
+//! `snapshot_power`.
+
+use cosmwasm_std::{
+    instantiate2_address, to_binary, Binary, Deps, DepsMut, Env, Event, MessageInfo, Order, Reply,
+    Response, StdError, StdResult, SubMsg, WasmMsg,
+};
+use cw_storage_plus::Bound;
+use cw_utils::{nonpayable, parse_reply_instantiate_data};
+
+use crate::contract::{ensure_role, DEFAULT_LIMIT, INSTANTIATE_CHILD_REPLY_ID, MAX_LIMIT};
+use crate::error::ContractError;
+use crate::msg::{
+    ChildAddressResponse, CreatePollMsg, ExecuteMsg, InstantiateMsg, ListChildrenResponse,
+};
+use crate::state::{
+    ChildContract, Role, CHILDREN, CHILD_COUNT, CONFIG, PENDING_CHILD_POLL, POLL_COUNT,
+};
+
+pub fn execute_create_poll_contract(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll: CreatePollMsg,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+    if poll.bounty.is_some() {
+        return Err(ContractError::InvalidBounty {});
+    }
+
+    let code_id = deps
+        .querier
+        .query_wasm_contract_info(&env.contract.address)?
+        .code_id;
+    let child_id = CHILD_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    CHILD_COUNT.save(deps.storage, &child_id)?;
+    let poll_id = POLL_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    POLL_COUNT.save(deps.storage, &poll_id)?;
+    PENDING_CHILD_POLL.save(deps.storage, &(poll_id, poll))?;
+
+    let msg = InstantiateMsg {
+        admin_address: config.admin_address.to_string(),
+        voting_power: Some(config.voting_power.into()),
+        stake_validators: config.stake_validators,
+        cw20_token: config.cw20_token.map(String::from),
+        escrow_cw20: config.escrow_cw20,
+        question_min_len: Some(config.question_min_len),
+        question_max_len: Some(config.question_max_len),
+        max_multicall: Some(config.max_multicall),
+        default_quorum: config.default_quorum,
+        default_threshold: config.default_threshold,
+        default_voting_period: config.default_voting_period,
+        execution_delay: config.execution_delay,
+        veto_period: config.veto_period,
+        remote_power: config.remote_power,
+        nois_proxy: config.nois_proxy.map(String::from),
+        nois_fee: config.nois_fee,
+        receipt_minter: config.receipt_minter.map(String::from),
+        reputation_bonus: config.reputation_bonus,
+        streak_bonus: config.streak_bonus,
+        weight_cap: config.weight_cap,
+        ..Default::default()
+    };
+    let instantiate = WasmMsg::Instantiate2 {
+        admin: Some(config.admin_address.into_string()),
+        code_id,
+        label: format!("mycosmwasm poll {}", poll_id),
+        msg: to_binary(&msg)?,
+        funds: vec![],
+        salt: child_salt(poll_id),
+    };
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            instantiate,
+            INSTANTIATE_CHILD_REPLY_ID,
+        ))
+        .add_attribute("action", "create_poll_contract")
+        .add_attribute("child_id", child_id.to_string())
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("code_id", code_id.to_string()))
+}
+
+/// Records the new child and creates the pending poll in it.
+pub fn reply_instantiate_child(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
+    let response = parse_reply_instantiate_data(msg)?;
+    let address = deps.api.addr_validate(&response.contract_address)?;
+    let (poll_id, poll) = PENDING_CHILD_POLL.load(deps.storage)?;
+    PENDING_CHILD_POLL.remove(deps.storage);
+    let id = CHILD_COUNT.load(deps.storage)?;
+    CHILDREN.save(
+        deps.storage,
+        id,
+        &ChildContract {
+            id,
+            poll_id,
+            address: address.clone(),
+            question: poll.question.clone(),
+        },
+    )?;
+
+    let create_poll = WasmMsg::Execute {
+        contract_addr: address.to_string(),
+        msg: to_binary(&ExecuteMsg::CreatePoll(poll))?,
+        funds: vec![],
+    };
+    let event = Event::new("poll_contract_created")
+        .add_attribute("child_id", id.to_string())
+        .add_attribute("address", address);
+    Ok(Response::new()
+        .add_message(create_poll)
+        .add_attribute("action", "reply_instantiate_child")
+        .add_attribute("child_id", id.to_string())
+        .add_event(event))
+}
+
+/// Address the child for `poll_id` gets, whether or not it exists yet.
+pub fn query_child_address(deps: Deps, env: Env, poll_id: u64) -> StdResult<Binary> {
+    let code_id = deps
+        .querier
+        .query_wasm_contract_info(&env.contract.address)?
+        .code_id;
+    let checksum = deps.querier.query_wasm_code_info(code_id)?.checksum;
+    let creator = deps.api.addr_canonicalize(env.contract.address.as_str())?;
+    let address = instantiate2_address(checksum.as_slice(), &creator, &child_salt(poll_id))
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let address = deps.api.addr_humanize(&address)?;
+    to_binary(&ChildAddressResponse { address })
+}
+
+pub fn query_list_children(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let children = CHILDREN
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, child)| child))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ListChildrenResponse { children })
+}
+
+fn child_salt(poll_id: u64) -> Binary {
+    Binary::from(poll_id.to_be_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+        MOCK_CONTRACT_ADDR,
+    };
+    use cosmwasm_std::{
+        from_binary, Addr, Api, CanonicalAddr, CodeInfoResponse, ContractInfoResponse,
+        ContractResult, CosmosMsg, HexBinary, OwnedDeps, RecoverPubkeyError, SubMsgResponse,
+        SubMsgResult, SystemResult, VerificationError, WasmQuery,
+    };
+
+    use crate::contract::{execute, instantiate, query, reply};
+    use crate::msg::QueryMsg;
+
+    use super::*;
+
+    /// `MockApi` cannot humanize `Instantiate2` addresses, this one keeps
+    /// addresses as their bytes and shows canonical ones in hex.
+    struct BytesApi(MockApi);
+
+    impl Api for BytesApi {
+        fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+            self.0.addr_validate(human)
+        }
+
+        fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+            Ok(CanonicalAddr::from(human.as_bytes()))
+        }
+
+        fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+            Ok(Addr::unchecked(canonical.to_string()))
+        }
+
+        fn secp256k1_verify(
+            &self,
+            message_hash: &[u8],
+            signature: &[u8],
+            public_key: &[u8],
+        ) -> Result<bool, VerificationError> {
+            self.0.secp256k1_verify(message_hash, signature, public_key)
+        }
+
+        fn secp256k1_recover_pubkey(
+            &self,
+            message_hash: &[u8],
+            signature: &[u8],
+            recovery_param: u8,
+        ) -> Result<Vec<u8>, RecoverPubkeyError> {
+            self.0
+                .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+        }
+
+        fn ed25519_verify(
+            &self,
+            message: &[u8],
+            signature: &[u8],
+            public_key: &[u8],
+        ) -> Result<bool, VerificationError> {
+            self.0.ed25519_verify(message, signature, public_key)
+        }
+
+        fn ed25519_batch_verify(
+            &self,
+            messages: &[&[u8]],
+            signatures: &[&[u8]],
+            public_keys: &[&[u8]],
+        ) -> Result<bool, VerificationError> {
+            self.0
+                .ed25519_batch_verify(messages, signatures, public_keys)
+        }
+
+        fn debug(&self, message: &str) {
+            self.0.debug(message)
+        }
+    }
+
+    #[test]
+    fn test_child_address() {
+        let mut querier = MockQuerier::default();
+        querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { contract_addr } if contract_addr == MOCK_CONTRACT_ADDR => {
+                let mut info = ContractInfoResponse::default();
+                info.code_id = 7;
+                SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+            }
+            WasmQuery::CodeInfo { code_id: 7 } => {
+                let mut info = CodeInfoResponse::default();
+                info.code_id = 7;
+                info.checksum = HexBinary::from([7; 32]);
+                SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+        let mut deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: BytesApi(MockApi::default()),
+            querier,
+            custom_query_type: PhantomData,
+        };
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // the child's poll takes the next poll id, which salts its address
+        let msg = ExecuteMsg::CreatePollContract(CreatePollMsg {
+            question: "Do you love juno".to_string(),
+            ..Default::default()
+        });
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let salt = match &result.messages[0].msg {
+            CosmosMsg::Wasm(WasmMsg::Instantiate2 { salt, .. }) => salt.clone(),
+            msg => panic!("unexpected message {:?}", msg),
+        };
+        assert_eq!(salt, child_salt(2));
+        let creator = CanonicalAddr::from(MOCK_CONTRACT_ADDR.as_bytes());
+        let instantiated = instantiate2_address(&[7; 32], &creator, &salt).unwrap();
+
+        let msg = QueryMsg::ChildAddress { poll_id: 2 };
+        let resp: ChildAddressResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(resp.address, instantiated.to_string());
+    }
+
+    #[test]
+    fn test_create_poll_contract() {
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::ContractInfo { contract_addr } if contract_addr == MOCK_CONTRACT_ADDR => {
+                let mut info = ContractInfoResponse::default();
+                info.code_id = 7;
+                SystemResult::Ok(ContractResult::Ok(to_binary(&info).unwrap()))
+            }
+            _ => panic!("unexpected query"),
+        });
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let poll = CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        };
+        let msg = ExecuteMsg::CreatePollContract(poll.clone());
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let msg = ExecuteMsg::CreatePollContract(poll.clone());
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert_eq!(result.messages[0].id, INSTANTIATE_CHILD_REPLY_ID);
+        assert!(matches!(
+            &result.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Instantiate2 { code_id: 7, admin: Some(admin), salt, .. })
+                if admin == "addr1" && salt.as_slice() == [0, 0, 0, 0, 0, 0, 0, 1]
+        ));
+
+        // MsgInstantiateContractResponse { contract_address: "child" }
+        let mut data = vec![0x0a, 5];
+        data.extend_from_slice(b"child");
+        let reply_msg = Reply {
+            id: INSTANTIATE_CHILD_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(Binary::from(data)),
+            }),
+        };
+        let result = reply(deps.as_mut(), env.clone(), reply_msg).unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "child".to_string(),
+                msg: to_binary(&ExecuteMsg::CreatePoll(poll)).unwrap(),
+                funds: vec![],
+            })
+        );
+
+        let msg = QueryMsg::ListChildren {
+            start_after: None,
+            limit: None,
+        };
+        let resp: ListChildrenResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(
+            resp.children,
+            vec![ChildContract {
+                id: 1,
+                poll_id: 1,
+                address: Addr::unchecked("child"),
+                question: "Do you love spark IBC".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Quadratic funding rounds. Projects are listed in a round, contributions to
+//! them are escrowed in the round's denom, and when the round closes its
+//! matching pool is shared by the quadratic funding formula: a project is
+//! matched in proportion to the square of the sum of the square roots of its
+//! contributions, less the contributions themselves. Broad support therefore
+//! counts for more than a few large contributions.
+
+use cosmwasm_std::{
+    coins, to_binary, Addr, BankMsg, Binary, Decimal, Deps, DepsMut, Env, Event, MessageInfo,
+    Response, StdError, StdResult, Uint128,
+};
+use cw_utils::{must_pay, nonpayable, Expiration};
+
+use crate::contract::ensure_not_blocked;
+use crate::error::ContractError;
+use crate::msg::GetRoundResponse;
+use crate::state::{Project, Round, CONFIG, CONTRIBUTIONS, CREATORS, ROUNDS, ROUND_COUNT};
+
+const MAX_ROUND_PROJECTS: usize = 50;
+const MAX_NAME_LEN: usize = 128;
+
+/// Opens a round whose matching pool is the coins sent in `denom`.
+pub fn execute_create_round(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    denom: String,
+    expiration: Expiration,
+) -> Result<Response, ContractError> {
+    ensure_not_blocked(deps.storage, &info.sender)?;
+    let config = CONFIG.load(deps.storage)?;
+    if config.restrict_creation && !CREATORS.has(deps.storage, &info.sender) {
+        return Err(ContractError::CreationNotAllowed {});
+    }
+    let matching_pool = must_pay(&info, &denom)?;
+    if expiration.is_expired(&env.block) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+    ensure_name(&name)?;
+
+    let id = ROUND_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    ROUND_COUNT.save(deps.storage, &id)?;
+    let round = Round {
+        id,
+        creator: info.sender,
+        name,
+        denom,
+        matching_pool,
+        expiration,
+        closed: false,
+        projects: vec![],
+    };
+    ROUNDS.save(deps.storage, id, &round)?;
+
+    let event = Event::new("round_created")
+        .add_attribute("round_id", id.to_string())
+        .add_attribute("name", round.name)
+        .add_attribute("matching_pool", matching_pool);
+    Ok(Response::new()
+        .add_attribute("action", "create_round")
+        .add_attribute("round_id", id.to_string())
+        .add_event(event))
+}
+
+/// Adds the coins sent to an open round's matching pool.
+pub fn execute_fund_round(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    round_id: u64,
+) -> Result<Response, ContractError> {
+    let mut round = load_open_round(deps.as_ref(), &env, round_id)?;
+    let amount = must_pay(&info, &round.denom)?;
+    round.matching_pool = round.matching_pool.checked_add(amount)?;
+    ROUNDS.save(deps.storage, round_id, &round)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "fund_round")
+        .add_attribute("round_id", round_id.to_string())
+        .add_attribute("matching_pool", round.matching_pool))
+}
+
+/// Lists a project in an open round. Its share of the round is paid to
+/// `recipient`, the sender by default.
+pub fn execute_list_project(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    round_id: u64,
+    name: String,
+    recipient: Option<String>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ensure_not_blocked(deps.storage, &info.sender)?;
+    let mut round = load_open_round(deps.as_ref(), &env, round_id)?;
+    ensure_name(&name)?;
+    if round.projects.len() >= MAX_ROUND_PROJECTS {
+        return Err(ContractError::RoundFull { round_id });
+    }
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender,
+    };
+    let project = round.projects.len() as u32;
+    round.projects.push(Project {
+        name,
+        recipient,
+        contributions: Uint128::zero(),
+        sqrt_sum: Decimal::zero(),
+        contributors: 0,
+    });
+    ROUNDS.save(deps.storage, round_id, &round)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "list_project")
+        .add_attribute("round_id", round_id.to_string())
+        .add_attribute("project", project.to_string()))
+}
+
+/// Escrows the coins sent as the sender's contribution to a project.
+pub fn execute_contribute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    round_id: u64,
+    project: u32,
+) -> Result<Response, ContractError> {
+    ensure_not_blocked(deps.storage, &info.sender)?;
+    let mut round = load_open_round(deps.as_ref(), &env, round_id)?;
+    let amount = must_pay(&info, &round.denom)?;
+    let listing = round
+        .projects
+        .get_mut(project as usize)
+        .ok_or(ContractError::ProjectNotFound { round_id, project })?;
+
+    // the sender's contributions count as one, so only its total is rooted
+    let key = (round_id, project, &info.sender);
+    let previous = CONTRIBUTIONS.may_load(deps.storage, key)?;
+    let total = previous.unwrap_or_default().checked_add(amount)?;
+    match previous {
+        Some(previous) => listing.sqrt_sum -= sqrt(previous)?,
+        None => listing.contributors += 1,
+    }
+    listing.sqrt_sum += sqrt(total)?;
+    listing.contributions = listing.contributions.checked_add(amount)?;
+    CONTRIBUTIONS.save(deps.storage, key, &total)?;
+    ROUNDS.save(deps.storage, round_id, &round)?;
+
+    let event = Event::new("contribution_made")
+        .add_attribute("round_id", round_id.to_string())
+        .add_attribute("project", project.to_string())
+        .add_attribute("contributor", info.sender)
+        .add_attribute("amount", amount);
+    Ok(Response::new()
+        .add_attribute("action", "contribute")
+        .add_attribute("round_id", round_id.to_string())
+        .add_event(event))
+}
+
+/// Pays every project its contributions and its match once the round is over.
+/// What the formula leaves of the pool goes back to the round's creator.
+pub fn execute_close_round(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    round_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut round = load_round(deps.as_ref(), round_id)?;
+    if round.closed {
+        return Err(ContractError::RoundClosed { round_id });
+    }
+    if !round.expiration.is_expired(&env.block) {
+        return Err(ContractError::RoundStillOpen { round_id });
+    }
+    round.closed = true;
+    ROUNDS.save(deps.storage, round_id, &round)?;
+
+    let matches = matches(&round)?;
+    let send = |to: &Addr, amount: Uint128| BankMsg::Send {
+        to_address: to.to_string(),
+        amount: coins(amount.u128(), &round.denom),
+    };
+    let mut msgs = vec![];
+    let mut matched = Uint128::zero();
+    for (project, matching) in round.projects.iter().zip(matches) {
+        let payout = project.contributions + matching;
+        if !payout.is_zero() {
+            msgs.push(send(&project.recipient, payout));
+        }
+        matched += matching;
+    }
+    let rest = round.matching_pool - matched;
+    if !rest.is_zero() {
+        msgs.push(send(&round.creator, rest));
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "close_round")
+        .add_attribute("round_id", round_id.to_string())
+        .add_attribute("matched", matched))
+}
+
+/// The round, with what each project would be matched if it closed now.
+pub fn query_get_round(deps: Deps, env: Env, round_id: u64) -> StdResult<Binary> {
+    let round = ROUNDS.load(deps.storage, round_id)?;
+    let estimated_matches = matches(&round)?;
+    let is_open = !round.closed && !round.expiration.is_expired(&env.block);
+    to_binary(&GetRoundResponse {
+        round,
+        is_open,
+        estimated_matches,
+    })
+}
+
+/// Splits the matching pool in proportion to `sqrt_sum² - contributions`.
+fn matches(round: &Round) -> StdResult<Vec<Uint128>> {
+    let weights = round
+        .projects
+        .iter()
+        .map(|project| {
+            let squared = project.sqrt_sum.checked_pow(2)?.to_uint_floor();
+            Ok(squared.saturating_sub(project.contributions))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    let total = weights.iter().sum::<Uint128>();
+    if total.is_zero() {
+        return Ok(vec![Uint128::zero(); weights.len()]);
+    }
+    Ok(weights
+        .into_iter()
+        .map(|weight| round.matching_pool.multiply_ratio(weight, total))
+        .collect())
+}
+
+fn sqrt(amount: Uint128) -> StdResult<Decimal> {
+    Ok(Decimal::from_atomics(amount, 0)
+        .map_err(|err| StdError::generic_err(err.to_string()))?
+        .sqrt())
+}
+
+pub(crate) fn ensure_name(name: &str) -> Result<(), ContractError> {
+    if name.trim().is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(ContractError::InvalidName {
+            name: name.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn load_round(deps: Deps, round_id: u64) -> Result<Round, ContractError> {
+    ROUNDS
+        .may_load(deps.storage, round_id)?
+        .ok_or(ContractError::RoundNotFound { round_id })
+}
+
+fn load_open_round(deps: Deps, env: &Env, round_id: u64) -> Result<Round, ContractError> {
+    let round = load_round(deps, round_id)?;
+    if round.closed || round.expiration.is_expired(&env.block) {
+        return Err(ContractError::RoundClosed { round_id });
+    }
+    Ok(round)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, from_binary, CosmosMsg};
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+
+    use super::*;
+
+    #[test]
+    fn test_quadratic_funding_round() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let msg = ExecuteMsg::CreateRound {
+            name: "Grants".to_string(),
+            denom: "ujuno".to_string(),
+            expiration: Expiration::AtHeight(env.block.height + 10),
+        };
+        let sponsor = mock_info("addr1", &[coin(101, "ujuno")]);
+        execute(deps.as_mut(), env.clone(), sponsor, msg).unwrap();
+        for (owner, name) in [("proj1", "Relayer"), ("proj2", "Explorer")] {
+            let msg = ExecuteMsg::ListProject {
+                round_id: 1,
+                name: name.to_string(),
+                recipient: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(owner, &[]), msg).unwrap();
+        }
+
+        // four contributors of 1 against two of 4, one of them in two goes
+        let contributions = [
+            ("addr2", 0, 1),
+            ("addr3", 0, 1),
+            ("addr4", 0, 1),
+            ("addr5", 0, 1),
+            ("addr6", 1, 1),
+            ("addr6", 1, 3),
+            ("addr7", 1, 4),
+        ];
+        for (contributor, project, amount) in contributions {
+            let msg = ExecuteMsg::Contribute {
+                round_id: 1,
+                project,
+            };
+            let funds = [coin(amount, "ujuno")];
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(contributor, &funds),
+                msg,
+            )
+            .unwrap();
+        }
+
+        let msg = ExecuteMsg::CloseRound { round_id: 1 };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::RoundStillOpen { round_id: 1 }));
+        env.block.height += 10;
+        let resp: GetRoundResponse = from_binary(
+            &query(
+                deps.as_ref(),
+                env.clone(),
+                QueryMsg::GetRound { round_id: 1 },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(!resp.is_open);
+        assert_eq!(resp.round.projects[1].contributors, 2);
+        assert_eq!(
+            resp.estimated_matches,
+            vec![Uint128::new(60), Uint128::new(40)]
+        );
+
+        let result = execute(deps.as_mut(), env, mock_info("addr2", &[]), msg).unwrap();
+        let payouts: Vec<_> = result
+            .messages
+            .into_iter()
+            .map(|msg| match msg.msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                    (to_address, amount[0].amount.u128())
+                }
+                _ => panic!("unexpected message"),
+            })
+            .collect();
+        assert_eq!(
+            payouts,
+            vec![
+                ("proj1".to_string(), 64),
+                ("proj2".to_string(), 48),
+                ("addr1".to_string(), 1),
+            ]
+        );
+    }
+}
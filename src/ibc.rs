@@ -0,0 +1,631 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_slice, to_binary, Addr, DepsMut, Env, Event, IbcBasicResponse, IbcChannel,
+    IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcChannelOpenResponse, IbcMsg,
+    IbcOrder, IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, Order,
+    StdResult, Storage, Uint128, Uint64,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::bounty::pay_bounty;
+use crate::contract::{cast_vote, ensure_open, hook_msgs, load_poll, refund_poll_deposit};
+use crate::error::ContractError;
+use crate::msg::{Choice, PollHookMsg};
+use crate::rewards::open_reward_claims;
+use crate::state::{
+    polls, HubLink, Poll, PollKind, PollStatus, RemoteTally, VotingPowerSource, CHANNELS, CONFIG,
+    HUB_POLLS, REMOTE_TALLIES, STATS,
+};
+
+pub const IBC_VERSION: &str = "mycosmwasm-vote-1";
+/// Seconds before packets sent by this contract time out.
+const PACKET_LIFETIME: u64 = 60 * 60;
+
+/// Packets sent by a counterparty contract on behalf of its users.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VotePacket {
+    /// Casts `voter`'s ballot on a poll hosted here. Remote voters are recorded
+    /// as `<channel>/<voter>` and every ballot weighs one.
+    Vote {
+        poll_id: u64,
+        voter: String,
+        choice: Choice,
+    },
+    /// Sent by a spoke: its cumulative tally for a poll hosted here, replacing
+    /// any earlier one with a lower `sequence` from the same channel.
+    Tally {
+        poll_id: u64,
+        sequence: u64,
+        options: Vec<Uint128>,
+        abstain_votes: Uint128,
+        veto_votes: Uint128,
+    },
+    /// Sent by the hub to every spoke that synced a tally once the poll closes.
+    Finalize {
+        poll_id: u64,
+        status: PollStatus,
+        winner: Option<String>,
+    },
+}
+
+/// Acknowledgement written for every `VotePacket`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteAck {
+    Accepted {},
+    Rejected { error: String },
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_open(
+    _deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    validate_channel(msg.channel(), msg.counterparty_version())?;
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    validate_channel(channel, msg.counterparty_version())?;
+    CHANNELS.save(
+        deps.storage,
+        &channel.endpoint.channel_id,
+        &channel.counterparty_endpoint,
+    )?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("action", "ibc_channel_connect")
+        .add_attribute("channel_id", &channel.endpoint.channel_id))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_close(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    CHANNELS.remove(deps.storage, &channel.endpoint.channel_id);
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("action", "ibc_channel_close")
+        .add_attribute("channel_id", &channel.endpoint.channel_id))
+}
+
+/// Failed votes are reported in the acknowledgement rather than as an error, so
+/// the counterparty learns why its packet was rejected.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let channel_id = msg.packet.dest.channel_id;
+    let response = from_slice(&msg.packet.data)
+        .map_err(ContractError::from)
+        .and_then(|packet| receive_vote_packet(deps, &env, &channel_id, packet));
+    match response {
+        Ok(response) => Ok(response.set_ack(to_binary(&VoteAck::Accepted {})?)),
+        Err(err) => {
+            let error = err.to_string();
+            Ok(IbcReceiveResponse::new()
+                .add_attribute("action", "ibc_packet_receive")
+                .add_attribute("channel_id", channel_id)
+                .add_attribute("error", &error)
+                .set_ack(to_binary(&VoteAck::Rejected { error })?))
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_ack(
+    _deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let mut response = IbcBasicResponse::new().add_attribute("action", "ibc_packet_ack");
+    if let Ok(VoteAck::Rejected { error }) = from_slice(&msg.acknowledgement.data) {
+        response = response.add_attribute("error", error);
+    }
+    Ok(response)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_timeout(
+    _deps: DepsMut,
+    _env: Env,
+    _msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    Ok(IbcBasicResponse::new().add_attribute("action", "ibc_packet_timeout"))
+}
+
+fn validate_channel(
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    if channel.order != IbcOrder::Unordered {
+        return Err(ContractError::OnlyUnorderedChannel {});
+    }
+    for version in [Some(channel.version.as_str()), counterparty_version]
+        .into_iter()
+        .flatten()
+    {
+        if version != IBC_VERSION {
+            return Err(ContractError::InvalidIbcVersion {
+                expected: IBC_VERSION.to_string(),
+                actual: version.to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn receive_vote_packet(
+    deps: DepsMut,
+    env: &Env,
+    channel_id: &str,
+    packet: VotePacket,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.paused {
+        return Err(ContractError::Paused {});
+    }
+
+    match packet {
+        VotePacket::Vote {
+            poll_id,
+            voter,
+            choice,
+        } => {
+            let poll = load_poll(deps.storage, poll_id)?;
+            let source = poll.voting_power.as_ref().unwrap_or(&config.voting_power);
+            if *source != VotingPowerSource::FixedOnePerAddress
+                || config.snapshot_power
+                || config.cw20_token.is_some()
+                || config.remote_power.is_some()
+            {
+                return Err(ContractError::RemoteVotingUnsupported {});
+            }
+            let voter = Addr::unchecked(format!("{}/{}", channel_id, voter));
+            let response = cast_vote(deps, env, &voter, poll_id, choice, Uint128::one(), None)?;
+            Ok(IbcReceiveResponse::new()
+                .add_attribute("action", "ibc_vote")
+                .add_attribute("channel_id", channel_id)
+                .add_submessages(response.messages)
+                .add_attributes(response.attributes)
+                .add_events(response.events))
+        }
+        VotePacket::Tally {
+            poll_id,
+            sequence,
+            options,
+            abstain_votes,
+            veto_votes,
+        } => {
+            let tally = RemoteTally {
+                sequence,
+                options,
+                abstain_votes,
+                veto_votes,
+            };
+            receive_tally(deps.storage, channel_id, poll_id, tally)
+        }
+        VotePacket::Finalize {
+            poll_id,
+            status,
+            winner,
+        } => receive_finalize(deps.storage, env, channel_id, poll_id, status, winner),
+    }
+}
+
+/// Swaps the channel's previous tally for `tally` in the hub poll's counts.
+fn receive_tally(
+    storage: &mut dyn Storage,
+    channel_id: &str,
+    poll_id: u64,
+    tally: RemoteTally,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let mut poll = load_poll(storage, poll_id)?;
+    ensure_open(&poll)?;
+    if poll.kind == PollKind::RankedChoice {
+        return Err(ContractError::RemoteTallyUnsupported {});
+    }
+    if tally.options.len() != poll.options.len() {
+        return Err(ContractError::RemoteTallyMismatch {});
+    }
+    let previous = REMOTE_TALLIES
+        .may_load(storage, (poll_id, channel_id))?
+        .unwrap_or_default();
+    if tally.sequence <= previous.sequence {
+        return Err(ContractError::StaleTally {
+            sequence: tally.sequence,
+        });
+    }
+
+    for (i, option) in poll.options.iter_mut().enumerate() {
+        let old = previous.options.get(i).copied().unwrap_or_default();
+        option.votes = option
+            .votes
+            .checked_sub(old)?
+            .checked_add(tally.options[i])?;
+    }
+    poll.abstain_votes = poll
+        .abstain_votes
+        .checked_sub(previous.abstain_votes)?
+        .checked_add(tally.abstain_votes)?;
+    poll.veto_votes = poll
+        .veto_votes
+        .checked_sub(previous.veto_votes)?
+        .checked_add(tally.veto_votes)?;
+    polls().save(storage, poll_id, &poll)?;
+    REMOTE_TALLIES.save(storage, (poll_id, channel_id), &tally)?;
+
+    let event = Event::new("remote_tally_received")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("sequence", tally.sequence.to_string());
+    Ok(IbcReceiveResponse::new()
+        .add_attribute("action", "ibc_tally")
+        .add_event(event))
+}
+
+/// Records the hub's outcome on the spoke poll, closing it if still open.
+fn receive_finalize(
+    storage: &mut dyn Storage,
+    env: &Env,
+    channel_id: &str,
+    hub_poll_id: u64,
+    status: PollStatus,
+    winner: Option<String>,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let poll_id = HUB_POLLS
+        .may_load(storage, (channel_id, hub_poll_id))?
+        .ok_or(ContractError::PollNotFound {
+            poll_id: hub_poll_id,
+        })?;
+    let mut poll = load_poll(storage, poll_id)?;
+    let hub = poll.hub.as_mut().ok_or(ContractError::NoHub { poll_id })?;
+    hub.result = Some(status);
+
+    let mut response = IbcReceiveResponse::new().add_attribute("action", "ibc_finalize");
+    if poll.status == PollStatus::Open {
+        poll.status = status;
+        let hook = PollHookMsg::PollClosed {
+            poll_id,
+            status,
+            winner: winner.clone(),
+        };
+        response = response.add_submessages(hook_msgs(storage, &hook)?);
+        STATS.update(storage, |mut stats| -> StdResult<_> {
+            stats.open_polls = Uint64::new(stats.open_polls)
+                .checked_sub(Uint64::one())?
+                .u64();
+            Ok(stats)
+        })?;
+        if let Some(refund) = refund_poll_deposit(storage, poll_id)? {
+            response = response.add_message(refund);
+        }
+        let winner = winner.as_ref().and_then(|winner| {
+            poll.options
+                .iter()
+                .position(|option| option.label == *winner)
+        });
+        response = response.add_messages(pay_bounty(storage, &poll, winner)?);
+        open_reward_claims(storage, &env.block, &poll)?;
+    }
+    polls().save(storage, poll_id, &poll)?;
+
+    let mut event = Event::new("poll_finalized")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("channel_id", channel_id)
+        .add_attribute("hub_poll_id", hub_poll_id.to_string())
+        .add_attribute("status", status.to_string());
+    if let Some(winner) = winner {
+        event = event.add_attribute("winner", winner);
+    }
+    Ok(response.add_event(event))
+}
+
+/// Packet sending the spoke poll's current tally to its hub.
+pub(crate) fn tally_packet(env: &Env, poll: &Poll, hub: &HubLink) -> StdResult<IbcMsg> {
+    let packet = VotePacket::Tally {
+        poll_id: hub.poll_id,
+        sequence: hub.sequence,
+        options: poll.options.iter().map(|option| option.votes).collect(),
+        abstain_votes: poll.abstain_votes,
+        veto_votes: poll.veto_votes,
+    };
+    Ok(IbcMsg::SendPacket {
+        channel_id: hub.channel_id.clone(),
+        data: to_binary(&packet)?,
+        timeout: env.block.time.plus_seconds(PACKET_LIFETIME).into(),
+    })
+}
+
+/// Packets telling every still connected spoke of the hub poll how it ended.
+pub(crate) fn finalize_packets(
+    storage: &dyn Storage,
+    env: &Env,
+    poll: &Poll,
+    winner: Option<String>,
+) -> StdResult<Vec<IbcMsg>> {
+    let data = to_binary(&VotePacket::Finalize {
+        poll_id: poll.id,
+        status: poll.status,
+        winner,
+    })?;
+    REMOTE_TALLIES
+        .prefix(poll.id)
+        .keys(storage, None, None, Order::Ascending)
+        .filter(|channel_id| {
+            channel_id
+                .as_ref()
+                .map_or(true, |channel_id| CHANNELS.has(storage, channel_id))
+        })
+        .map(|channel_id| {
+            Ok(IbcMsg::SendPacket {
+                channel_id: channel_id?,
+                data: data.clone(),
+                timeout: env.block.time.plus_seconds(PACKET_LIFETIME).into(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::from_binary;
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_ibc_channel_close_init, mock_ibc_channel_connect_ack,
+        mock_ibc_channel_open_init, mock_ibc_packet_recv, mock_info,
+    };
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{
+        CreatePollMsg, ExecuteMsg, GetPollResponse, HubLinkMsg, InstantiateMsg,
+        ListRemoteTalliesResponse, QueryMsg,
+    };
+
+    use super::*;
+
+    fn setup(deps: DepsMut) {
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps, mock_env(), mock_info("addr1", &[]), msg).unwrap();
+    }
+
+    fn connect(deps: DepsMut, channel_id: &str) {
+        let msg = mock_ibc_channel_connect_ack(channel_id, IbcOrder::Unordered, IBC_VERSION);
+        ibc_channel_connect(deps, mock_env(), msg).unwrap();
+    }
+
+    fn receive(deps: DepsMut, channel_id: &str, packet: &VotePacket) -> IbcReceiveResponse {
+        let msg = mock_ibc_packet_recv(channel_id, packet).unwrap();
+        ibc_packet_receive(deps, mock_env(), msg).unwrap()
+    }
+
+    #[test]
+    fn test_channel_handshake() {
+        let mut deps = mock_dependencies();
+
+        let msg = mock_ibc_channel_open_init("channel-1", IbcOrder::Ordered, IBC_VERSION);
+        let err = ibc_channel_open(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::OnlyUnorderedChannel {}));
+        let msg = mock_ibc_channel_open_init("channel-1", IbcOrder::Unordered, "ics20-1");
+        let err = ibc_channel_open(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidIbcVersion { .. }));
+        let msg = mock_ibc_channel_open_init("channel-1", IbcOrder::Unordered, IBC_VERSION);
+        ibc_channel_open(deps.as_mut(), mock_env(), msg).unwrap();
+
+        let msg = mock_ibc_channel_connect_ack("channel-1", IbcOrder::Unordered, IBC_VERSION);
+        ibc_channel_connect(deps.as_mut(), mock_env(), msg).unwrap();
+        assert!(CHANNELS.has(&deps.storage, "channel-1"));
+
+        let msg = mock_ibc_channel_close_init("channel-1", IbcOrder::Unordered, IBC_VERSION);
+        ibc_channel_close(deps.as_mut(), mock_env(), msg).unwrap();
+        assert!(!CHANNELS.has(&deps.storage, "channel-1"));
+    }
+
+    #[test]
+    fn test_remote_vote() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+
+        let _result = instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        let packet = VotePacket::Vote {
+            poll_id: 1,
+            voter: "remote1".to_string(),
+            choice: Choice::Yes,
+        };
+        let msg = mock_ibc_packet_recv("channel-1", &packet).unwrap();
+        let result = ibc_packet_receive(deps.as_mut(), env.clone(), msg).unwrap();
+        let ack: VoteAck = from_binary(&result.acknowledgement).unwrap();
+        assert_eq!(ack, VoteAck::Accepted {});
+        assert_eq!(result.events[0].ty, "vote_cast");
+        assert!(result.events[0]
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "voter" && attr.value == "channel-1/remote1"));
+
+        // the same remote voter cannot vote twice
+        let msg = mock_ibc_packet_recv("channel-1", &packet).unwrap();
+        let result = ibc_packet_receive(deps.as_mut(), env, msg).unwrap();
+        let ack: VoteAck = from_binary(&result.acknowledgement).unwrap();
+        assert_eq!(
+            ack,
+            VoteAck::Rejected {
+                error: "Already voted on poll 1".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_hub_aggregates_tallies() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        setup(deps.as_mut());
+        connect(deps.as_mut(), "channel-1");
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let tally = |sequence, yes: u128, no: u128| VotePacket::Tally {
+            poll_id: 1,
+            sequence,
+            options: vec![Uint128::new(yes), Uint128::new(no)],
+            abstain_votes: Uint128::zero(),
+            veto_votes: Uint128::zero(),
+        };
+        let result = receive(deps.as_mut(), "channel-1", &tally(2, 1, 3));
+        assert_eq!(result.events[0].ty, "remote_tally_received");
+
+        // a retransmitted or older tally is dropped
+        let result = receive(deps.as_mut(), "channel-1", &tally(1, 9, 9));
+        let ack: VoteAck = from_binary(&result.acknowledgement).unwrap();
+        assert_eq!(
+            ack,
+            VoteAck::Rejected {
+                error: "Tally 1 is older than the last one received".to_string()
+            }
+        );
+        receive(deps.as_mut(), "channel-1", &tally(3, 2, 1));
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::Yes,
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let rs_binary =
+            query(deps.as_ref(), env.clone(), QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let poll = from_binary::<GetPollResponse>(&rs_binary)
+            .unwrap()
+            .poll
+            .unwrap();
+        assert_eq!(poll.options[0].votes, Uint128::new(3));
+        assert_eq!(poll.options[1].votes, Uint128::new(1));
+
+        let msg = QueryMsg::ListRemoteTallies { poll_id: 1 };
+        let rs_binary = query(deps.as_ref(), env.clone(), msg).unwrap();
+        let resp: ListRemoteTalliesResponse = from_binary(&rs_binary).unwrap();
+        assert_eq!(resp.tallies.len(), 1);
+        assert_eq!(resp.tallies[0].channel_id, "channel-1");
+        assert_eq!(resp.tallies[0].tally.sequence, 3);
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let finalize = VotePacket::Finalize {
+            poll_id: 1,
+            status: PollStatus::Passed,
+            winner: Some("yes".to_string()),
+        };
+        assert_eq!(
+            result.messages[0].msg,
+            IbcMsg::SendPacket {
+                channel_id: "channel-1".to_string(),
+                data: to_binary(&finalize).unwrap(),
+                timeout: env.block.time.plus_seconds(PACKET_LIFETIME).into(),
+            }
+            .into()
+        );
+    }
+
+    #[test]
+    fn test_spoke_syncs_to_hub() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        setup(deps.as_mut());
+
+        let create = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            hub: Some(HubLinkMsg {
+                channel_id: "channel-1".to_string(),
+                poll_id: 7,
+            }),
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), create.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::UnknownChannel { .. }));
+        connect(deps.as_mut(), "channel-1");
+        execute(deps.as_mut(), env.clone(), info.clone(), create).unwrap();
+
+        let msg = ExecuteMsg::Vote {
+            poll_id: 1,
+            choice: Choice::No,
+            proof: None,
+            memo: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::SyncTally { poll_id: 1 };
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let tally = VotePacket::Tally {
+            poll_id: 7,
+            sequence: 1,
+            options: vec![Uint128::zero(), Uint128::one()],
+            abstain_votes: Uint128::zero(),
+            veto_votes: Uint128::zero(),
+        };
+        assert_eq!(
+            result.messages[0].msg,
+            IbcMsg::SendPacket {
+                channel_id: "channel-1".to_string(),
+                data: to_binary(&tally).unwrap(),
+                timeout: env.block.time.plus_seconds(PACKET_LIFETIME).into(),
+            }
+            .into()
+        );
+
+        let finalize = VotePacket::Finalize {
+            poll_id: 7,
+            status: PollStatus::Rejected,
+            winner: Some("no".to_string()),
+        };
+        let result = receive(deps.as_mut(), "channel-1", &finalize);
+        assert_eq!(result.events[0].ty, "poll_finalized");
+
+        let rs_binary =
+            query(deps.as_ref(), env.clone(), QueryMsg::GetPoll { poll_id: 1 }).unwrap();
+        let poll = from_binary::<GetPollResponse>(&rs_binary)
+            .unwrap()
+            .poll
+            .unwrap();
+        assert_eq!(poll.status, PollStatus::Rejected);
+        assert_eq!(poll.hub.unwrap().result, Some(PollStatus::Rejected));
+
+        let msg = ExecuteMsg::SyncTally { poll_id: 1 };
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::PollClosed { poll_id: 1 }));
+    }
+}
@@ -0,0 +1,252 @@
+//! Voting power proven by Neutron interchain queries.
+//!
+//! Registration is sent as a stargate `MsgRegisterInterchainQuery` and results
+//! are read back through Neutron's custom `interchain_query_result` query, so
+//! the contract keeps its `Empty` custom types. The remote account is the
+//! voter's own address bytes, which only hold the same keys on chains sharing
+//! the coin type.
+
+use cosmwasm_std::{
+    from_binary, to_vec, Addr, Binary, ContractResult, CosmosMsg, Deps, StdError, StdResult,
+    SystemResult, Uint128,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::state::{RemotePowerConfig, RemotePowerSource};
+
+const REGISTER_QUERY_TYPE_URL: &str = "/neutron.interchainqueries.MsgRegisterInterchainQuery";
+const BALANCES_PREFIX: u8 = 0x02;
+const DELEGATION_KEY: u8 = 0x31;
+/// Cosmos SDK `Dec` values are stored as integers with this many decimals.
+const DEC_PRECISION: u128 = 1_000_000_000_000_000_000;
+
+/// Stargate message registering a KV query over `account`'s power on the
+/// configured chain.
+pub(crate) fn register_query_msg(
+    contract: &Addr,
+    config: &RemotePowerConfig,
+    account: &[u8],
+) -> CosmosMsg {
+    let (path, key) = match &config.source {
+        RemotePowerSource::Balance { denom } => {
+            let mut key = vec![BALANCES_PREFIX];
+            push_length_prefixed(&mut key, account);
+            key.extend_from_slice(denom.as_bytes());
+            ("bank", key)
+        }
+        RemotePowerSource::Delegation { validator } => {
+            let mut key = vec![DELEGATION_KEY];
+            push_length_prefixed(&mut key, account);
+            push_length_prefixed(&mut key, validator);
+            ("staking", key)
+        }
+    };
+
+    let mut kv_key = vec![];
+    encode_bytes(&mut kv_key, 1, path.as_bytes());
+    encode_bytes(&mut kv_key, 2, &key);
+
+    let mut value = vec![];
+    encode_bytes(&mut value, 1, b"kv");
+    encode_bytes(&mut value, 2, &kv_key);
+    encode_bytes(&mut value, 4, config.connection_id.as_bytes());
+    encode_varint_field(&mut value, 5, config.update_period);
+    encode_bytes(&mut value, 6, contract.as_bytes());
+
+    CosmosMsg::Stargate {
+        type_url: REGISTER_QUERY_TYPE_URL.to_string(),
+        value: Binary(value),
+    }
+}
+
+/// Reads the query id out of a `MsgRegisterInterchainQueryResponse`.
+pub(crate) fn registered_query_id(data: Option<Binary>) -> StdResult<u64> {
+    let data = data.ok_or_else(|| StdError::generic_err("Missing query registration data"))?;
+    decode_fields(&data)?
+        .into_iter()
+        .find_map(|field| match field {
+            (1, Field::Varint(id)) => Some(id),
+            _ => None,
+        })
+        .ok_or_else(|| StdError::generic_err("Missing registered query id"))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum NeutronQuery {
+    InterchainQueryResult { query_id: u64 },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+enum CustomQuery {
+    Custom(NeutronQuery),
+}
+
+#[derive(Deserialize)]
+struct QueryResultResponse {
+    result: QueryResult,
+}
+
+#[derive(Deserialize)]
+struct QueryResult {
+    kv_results: Vec<StorageValue>,
+    height: u64,
+}
+
+#[derive(Deserialize)]
+struct StorageValue {
+    value: Binary,
+}
+
+/// Latest proven power behind `query_id` and the remote height it was proven at.
+pub(crate) fn query_remote_power(
+    deps: Deps,
+    query_id: u64,
+    source: &RemotePowerSource,
+) -> StdResult<(Uint128, u64)> {
+    let request = to_vec(&CustomQuery::Custom(NeutronQuery::InterchainQueryResult {
+        query_id,
+    }))?;
+    let response: QueryResultResponse = match deps.querier.raw_query(&request) {
+        SystemResult::Ok(ContractResult::Ok(value)) => from_binary(&value)?,
+        SystemResult::Ok(ContractResult::Err(error)) => {
+            return Err(StdError::generic_err(format!(
+                "Querier contract error: {}",
+                error
+            )))
+        }
+        SystemResult::Err(error) => {
+            return Err(StdError::generic_err(format!(
+                "Querier system error: {}",
+                error
+            )))
+        }
+    };
+
+    let value = match response.result.kv_results.first() {
+        Some(storage_value) if !storage_value.value.is_empty() => &storage_value.value,
+        _ => return Ok((Uint128::zero(), response.result.height)),
+    };
+    let power = match source {
+        RemotePowerSource::Balance { .. } => decode_balance(value)?,
+        RemotePowerSource::Delegation { .. } => decode_delegation(value)?,
+    };
+    Ok((power, response.result.height))
+}
+
+/// Balances are stored as a bare amount since Cosmos SDK 0.47 and as a `Coin`
+/// before that.
+fn decode_balance(value: &[u8]) -> StdResult<Uint128> {
+    if value.iter().all(u8::is_ascii_digit) {
+        return parse_amount(value);
+    }
+    let amount = decode_fields(value)?
+        .into_iter()
+        .find_map(|field| match field {
+            (2, Field::Bytes(amount)) => Some(amount),
+            _ => None,
+        })
+        .unwrap_or(b"0");
+    parse_amount(amount)
+}
+
+/// Delegated shares, counted one-for-one as tokens.
+fn decode_delegation(value: &[u8]) -> StdResult<Uint128> {
+    let shares = decode_fields(value)?
+        .into_iter()
+        .find_map(|field| match field {
+            (3, Field::Bytes(shares)) => Some(shares),
+            _ => None,
+        })
+        .unwrap_or(b"0");
+    Ok(parse_amount(shares)? / Uint128::new(DEC_PRECISION))
+}
+
+fn parse_amount(amount: &[u8]) -> StdResult<Uint128> {
+    let amount = std::str::from_utf8(amount)
+        .map_err(|_| StdError::generic_err("Amount is not valid UTF-8"))?;
+    amount.parse()
+}
+
+fn push_length_prefixed(key: &mut Vec<u8>, bytes: &[u8]) {
+    key.push(bytes.len() as u8);
+    key.extend_from_slice(bytes);
+}
+
+fn encode_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn encode_varint_field(buf: &mut Vec<u8>, field: u64, value: u64) {
+    encode_varint(buf, field << 3);
+    encode_varint(buf, value);
+}
+
+fn encode_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    encode_varint(buf, field << 3 | 2);
+    encode_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+enum Field<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+}
+
+fn decode_varint(bytes: &[u8], pos: &mut usize) -> StdResult<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or_else(|| StdError::parse_err("protobuf", "truncated varint"))?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            return Ok(value);
+        }
+    }
+    Err(StdError::parse_err("protobuf", "varint too long"))
+}
+
+/// Top-level fields of a protobuf message, skipping fixed-width ones.
+fn decode_fields(bytes: &[u8]) -> StdResult<Vec<(u64, Field<'_>)>> {
+    let mut fields = vec![];
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let key = decode_varint(bytes, &mut pos)?;
+        let field = match key & 7 {
+            0 => Field::Varint(decode_varint(bytes, &mut pos)?),
+            2 => {
+                let len = decode_varint(bytes, &mut pos)? as usize;
+                let end = pos
+                    .checked_add(len)
+                    .filter(|end| *end <= bytes.len())
+                    .ok_or_else(|| StdError::parse_err("protobuf", "truncated field"))?;
+                let value = &bytes[pos..end];
+                pos = end;
+                Field::Bytes(value)
+            }
+            1 => {
+                pos += 8;
+                continue;
+            }
+            5 => {
+                pos += 4;
+                continue;
+            }
+            wire_type => {
+                return Err(StdError::parse_err(
+                    "protobuf",
+                    format!("unsupported wire type {}", wire_type),
+                ))
+            }
+        };
+        fields.push((key >> 3, field));
+    }
+    Ok(fields)
+}
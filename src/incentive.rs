@@ -0,0 +1,321 @@
+//! Incentives: a sponsor deposits the configured cw20 token against a tag or a
+//! set of polls. Whenever one of those polls closes it allots up to `per_poll`
+//! to the poll's voters in proportion to their ballots' weight, which they can
+//! claim at any time. Once the incentive expires its sponsor withdraws what was
+//! never allotted.
+
+use cosmwasm_std::{
+    to_binary, Addr, Binary, BlockInfo, Deps, DepsMut, Empty, Env, Event, MessageInfo, Order,
+    Response, StdResult, Storage, Uint128,
+};
+use cw_utils::{nonpayable, Expiration};
+
+use crate::contract::{cw20_transfer, load_poll};
+use crate::error::ContractError;
+use crate::msg::{GetAccruedIncentivesResponse, GetIncentiveResponse};
+use crate::state::{
+    Incentive, IncentiveTarget, Poll, BALLOTS, CONFIG, INCENTIVES, INCENTIVE_ACCRUALS,
+    INCENTIVE_COUNT, POLL_INCENTIVES, TAG_INCENTIVES,
+};
+
+/// Opens an incentive with the `amount` of cw20 tokens `sponsor` sent.
+pub(crate) fn create_incentive(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    sponsor: &Addr,
+    amount: Uint128,
+    target: IncentiveTarget,
+    per_poll: Uint128,
+    expiration: Expiration,
+) -> Result<Response, ContractError> {
+    let valid_target = match &target {
+        IncentiveTarget::Tag { tag } => !tag.is_empty(),
+        IncentiveTarget::Polls { poll_ids } => !poll_ids.is_empty(),
+    };
+    if !valid_target || per_poll.is_zero() || amount.is_zero() || expiration.is_expired(block) {
+        return Err(ContractError::InvalidIncentive {});
+    }
+
+    let id = INCENTIVE_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    INCENTIVE_COUNT.save(storage, &id)?;
+    match &target {
+        IncentiveTarget::Tag { tag } => TAG_INCENTIVES.save(storage, (tag, id), &Empty {})?,
+        IncentiveTarget::Polls { poll_ids } => {
+            for poll_id in poll_ids {
+                load_poll(storage, *poll_id)?;
+                POLL_INCENTIVES.save(storage, (*poll_id, id), &Empty {})?;
+            }
+        }
+    }
+    INCENTIVES.save(
+        storage,
+        id,
+        &Incentive {
+            id,
+            sponsor: sponsor.clone(),
+            target,
+            per_poll,
+            amount,
+            allotted: Uint128::zero(),
+            expiration,
+        },
+    )?;
+
+    let event = Event::new("incentive_created")
+        .add_attribute("incentive_id", id.to_string())
+        .add_attribute("sponsor", sponsor)
+        .add_attribute("amount", amount);
+    Ok(Response::new()
+        .add_attribute("action", "create_incentive")
+        .add_attribute("incentive_id", id.to_string())
+        .add_event(event))
+}
+
+/// Allots the incentives targeting a poll that just closed to its voters.
+pub(crate) fn accrue_incentives(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    poll: &Poll,
+) -> StdResult<()> {
+    let mut ids = POLL_INCENTIVES
+        .prefix(poll.id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for tag in &poll.tags {
+        for id in TAG_INCENTIVES
+            .prefix(tag)
+            .keys(storage, None, None, Order::Ascending)
+        {
+            ids.push(id?);
+        }
+    }
+    if ids.is_empty() {
+        return Ok(());
+    }
+    ids.sort_unstable();
+    ids.dedup();
+
+    let ballots = BALLOTS
+        .prefix(poll.id)
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(voter, ballot)| (voter, ballot.weight)))
+        .collect::<StdResult<Vec<_>>>()?;
+    let total_weight = ballots
+        .iter()
+        .try_fold(Uint128::zero(), |total, (_, weight)| {
+            total.checked_add(*weight)
+        })?;
+    if total_weight.is_zero() {
+        return Ok(());
+    }
+
+    for id in ids {
+        let mut incentive = INCENTIVES.load(storage, id)?;
+        if incentive.expiration.is_expired(block) {
+            continue;
+        }
+        let grant = incentive
+            .per_poll
+            .min(incentive.amount - incentive.allotted);
+        for (voter, weight) in &ballots {
+            let share = grant.multiply_ratio(*weight, total_weight);
+            if share.is_zero() {
+                continue;
+            }
+            INCENTIVE_ACCRUALS.update(storage, voter, |accrued| -> StdResult<_> {
+                Ok(accrued.unwrap_or_default().checked_add(share)?)
+            })?;
+            incentive.allotted += share;
+        }
+        INCENTIVES.save(storage, id, &incentive)?;
+    }
+    Ok(())
+}
+
+/// Pays the sender everything incentives allotted them so far.
+pub fn execute_claim_incentives(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let token = CONFIG
+        .load(deps.storage)?
+        .cw20_token
+        .ok_or(ContractError::UnsupportedToken {})?;
+    let amount = INCENTIVE_ACCRUALS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if amount.is_zero() {
+        return Err(ContractError::NothingToWithdraw {});
+    }
+    INCENTIVE_ACCRUALS.remove(deps.storage, &info.sender);
+
+    Ok(Response::new()
+        .add_message(cw20_transfer(&token, &info.sender, amount)?)
+        .add_attribute("action", "claim_incentives")
+        .add_attribute("voter", info.sender)
+        .add_attribute("amount", amount))
+}
+
+/// Returns what an expired incentive did not allot to its sponsor and deletes
+/// it. Sponsors only.
+pub fn execute_withdraw_incentive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    incentive_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let incentive = INCENTIVES
+        .may_load(deps.storage, incentive_id)?
+        .ok_or(ContractError::IncentiveNotFound { incentive_id })?;
+    if info.sender != incentive.sponsor {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !incentive.expiration.is_expired(&env.block) {
+        return Err(ContractError::IncentiveActive { incentive_id });
+    }
+    INCENTIVES.remove(deps.storage, incentive_id);
+    match &incentive.target {
+        IncentiveTarget::Tag { tag } => TAG_INCENTIVES.remove(deps.storage, (tag, incentive_id)),
+        IncentiveTarget::Polls { poll_ids } => {
+            for poll_id in poll_ids {
+                POLL_INCENTIVES.remove(deps.storage, (*poll_id, incentive_id));
+            }
+        }
+    }
+
+    let rest = incentive.amount - incentive.allotted;
+    let mut response = Response::new()
+        .add_attribute("action", "withdraw_incentive")
+        .add_attribute("incentive_id", incentive_id.to_string())
+        .add_attribute("amount", rest);
+    if !rest.is_zero() {
+        let token = CONFIG
+            .load(deps.storage)?
+            .cw20_token
+            .ok_or(ContractError::UnsupportedToken {})?;
+        response = response.add_message(cw20_transfer(&token, &incentive.sponsor, rest)?);
+    }
+    Ok(response)
+}
+
+pub fn query_get_incentive(deps: Deps, _env: Env, incentive_id: u64) -> StdResult<Binary> {
+    let incentive = INCENTIVES.may_load(deps.storage, incentive_id)?;
+    to_binary(&GetIncentiveResponse { incentive })
+}
+
+pub fn query_get_accrued_incentives(deps: Deps, _env: Env, voter: String) -> StdResult<Binary> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let amount = INCENTIVE_ACCRUALS
+        .may_load(deps.storage, &voter)?
+        .unwrap_or_default();
+    to_binary(&GetAccruedIncentivesResponse { amount })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, CosmosMsg};
+    use cw20::Cw20ReceiveMsg;
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{Choice, CreatePollMsg, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg};
+
+    use super::*;
+
+    fn receive(sender: &str, amount: u128, msg: &ReceiveMsg) -> ExecuteMsg {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_binary(msg).unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_incentive() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let token = mock_info("token", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = receive(
+            "addr9",
+            170,
+            &ReceiveMsg::CreateIncentive {
+                target: IncentiveTarget::Tag {
+                    tag: "defi".to_string(),
+                },
+                per_poll: Uint128::new(100),
+                expiration: Expiration::AtHeight(env.block.height + 100),
+            },
+        );
+        execute(deps.as_mut(), env.clone(), token.clone(), msg).unwrap();
+
+        let votes = [vec![("addr2", 1), ("addr3", 3)], vec![("addr2", 10)]];
+        let questions = ["Do you love spark IBC", "Do you love cosmwasm"];
+        for (poll_id, (question, votes)) in (1..).zip(questions.into_iter().zip(votes)) {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                tags: vec!["defi".to_string()],
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            for (voter, amount) in votes {
+                let choice = Choice::Yes;
+                let msg = receive(voter, amount, &ReceiveMsg::Vote { poll_id, choice });
+                execute(deps.as_mut(), env.clone(), token.clone(), msg).unwrap();
+            }
+            let msg = ExecuteMsg::ClosePoll { poll_id };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+
+        // the second poll only gets what the first one left
+        let accrued = |voter: &str| -> Uint128 {
+            let msg = QueryMsg::GetAccruedIncentives {
+                voter: voter.to_string(),
+            };
+            let resp: GetAccruedIncentivesResponse =
+                from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+            resp.amount
+        };
+        assert_eq!(accrued("addr2"), Uint128::new(95));
+        assert_eq!(accrued("addr3"), Uint128::new(75));
+
+        let voter = mock_info("addr2", &[]);
+        let msg = ExecuteMsg::ClaimIncentives {};
+        let result = execute(deps.as_mut(), env.clone(), voter.clone(), msg.clone()).unwrap();
+        let token = Addr::unchecked("token");
+        assert_eq!(
+            result.messages[0].msg,
+            CosmosMsg::from(cw20_transfer(&token, &voter.sender, Uint128::new(95)).unwrap())
+        );
+        let err = execute(deps.as_mut(), env.clone(), voter, msg).unwrap_err();
+        assert!(matches!(err, ContractError::NothingToWithdraw {}));
+
+        let withdraw = ExecuteMsg::WithdrawIncentive { incentive_id: 1 };
+        let sponsor = mock_info("addr9", &[]);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            sponsor.clone(),
+            withdraw.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::IncentiveActive { incentive_id: 1 }
+        ));
+        env.block.height += 100;
+        let result = execute(deps.as_mut(), env, sponsor, withdraw).unwrap();
+        assert!(result.messages.is_empty());
+        assert!(!INCENTIVES.has(&deps.storage, 1));
+    }
+}
@@ -1,6 +1,31 @@
+mod bounty;
+mod campaign;
+mod cap;
+mod comment;
+mod condition;
 pub mod contract;
+mod cw3;
+mod dao;
 mod error;
+mod factory;
+mod funding;
+pub mod ibc;
+mod icq;
+mod incentive;
+mod market;
 pub mod msg;
+mod power;
+mod raffle;
+mod receipt;
+mod reputation;
+mod rewards;
+mod runoff;
 pub mod state;
+mod streak;
+mod survey;
+mod tally;
+mod template;
+mod tiebreak;
+mod writein;
 
 pub use crate::error::ContractError;
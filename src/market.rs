@@ -0,0 +1,354 @@
+//! Prediction markets: polls of kind `Prediction` where bettors stake coins on an
+//! option instead of voting. Once the poll is closed an oracle resolves the
+//! true outcome, and bettors who picked it share the whole pool in proportion
+//! to their stakes. Stakes are refunded when the poll is cancelled or nobody
+//! backed the outcome.
+
+use cosmwasm_std::{
+    coins, to_binary, BankMsg, Binary, Deps, DepsMut, Env, Event, MessageInfo, Order, Response,
+    StdResult, Storage, Uint128,
+};
+use cw_utils::{must_pay, nonpayable};
+
+use crate::contract::{ensure_not_blocked, ensure_open, ensure_role, ensure_started, load_poll};
+use crate::error::ContractError;
+use crate::msg::GetStakeResponse;
+use crate::state::{polls, Poll, PollKind, PollStatus, Role, Stake, CONFIG, OUTCOMES, STAKES};
+
+pub fn execute_place_bet(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    option: u32,
+) -> Result<Response, ContractError> {
+    ensure_not_blocked(deps.storage, &info.sender)?;
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    let denom = market_denom(&poll)?.to_string();
+    ensure_open(&poll)?;
+    if poll.expiration.is_expired(&env.block) {
+        return Err(ContractError::PollExpired {});
+    }
+    ensure_started(&poll, &env.block)?;
+    let amount = must_pay(&info, &denom)?;
+
+    let pool = poll
+        .options
+        .get_mut(option as usize)
+        .ok_or(ContractError::InvalidChoice {})?;
+    pool.votes = pool.votes.checked_add(amount)?;
+    let mut stake = STAKES
+        .may_load(deps.storage, (poll_id, &info.sender))?
+        .unwrap_or(Stake {
+            option,
+            amount: Uint128::zero(),
+        });
+    if stake.option != option {
+        return Err(ContractError::AlreadyStaked {
+            poll_id,
+            option: stake.option,
+        });
+    }
+    stake.amount = stake.amount.checked_add(amount)?;
+    STAKES.save(deps.storage, (poll_id, &info.sender), &stake)?;
+    polls().save(deps.storage, poll_id, &poll)?;
+
+    let event = Event::new("bet_placed")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("bettor", info.sender.clone())
+        .add_attribute("option", option.to_string())
+        .add_attribute("amount", amount);
+    Ok(Response::new()
+        .add_attribute("action", "place_bet")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("stake", stake.amount)
+        .add_event(event))
+}
+
+/// Records the true outcome of a closed market. Owners and oracles only.
+pub fn execute_resolve_poll(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    outcome: u32,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(
+        deps.storage,
+        &config,
+        &info.sender,
+        &[Role::Owner, Role::Oracle],
+    )?;
+    let poll = load_poll(deps.storage, poll_id)?;
+    market_denom(&poll)?;
+    match poll.status {
+        PollStatus::Open => return Err(ContractError::PollStillOpen {}),
+        PollStatus::Cancelled => return Err(ContractError::PollClosed { poll_id }),
+        _ => {}
+    }
+    if OUTCOMES.has(deps.storage, poll_id) {
+        return Err(ContractError::MarketResolved { poll_id });
+    }
+    let label = &poll
+        .options
+        .get(outcome as usize)
+        .ok_or(ContractError::InvalidChoice {})?
+        .label;
+    OUTCOMES.save(deps.storage, poll_id, &outcome)?;
+
+    let event = Event::new("market_resolved")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("outcome", label)
+        .add_attribute("resolved_by", info.sender);
+    Ok(Response::new()
+        .add_attribute("action", "resolve_poll")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("outcome", outcome.to_string())
+        .add_event(event))
+}
+
+pub fn execute_claim_payout(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let poll = load_poll(deps.storage, poll_id)?;
+    let denom = market_denom(&poll)?;
+    let stake = STAKES
+        .may_load(deps.storage, (poll_id, &info.sender))?
+        .ok_or(ContractError::NothingToWithdraw {})?;
+    if poll.status != PollStatus::Cancelled && !OUTCOMES.has(deps.storage, poll_id) {
+        return Err(ContractError::MarketNotResolved { poll_id });
+    }
+    let amount = payout(deps.storage, &poll, &stake)?;
+    if amount.is_zero() {
+        return Err(ContractError::NothingToWithdraw {});
+    }
+    STAKES.remove(deps.storage, (poll_id, &info.sender));
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: coins(amount.u128(), denom),
+        })
+        .add_attribute("action", "claim_payout")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("bettor", info.sender)
+        .add_attribute("amount", amount))
+}
+
+/// Pays out or refunds every stake left on a market that is being removed.
+pub(crate) fn settle_stakes(storage: &mut dyn Storage, poll: &Poll) -> StdResult<Vec<BankMsg>> {
+    let denom = match market_denom(poll) {
+        Ok(denom) => denom,
+        Err(_) => return Ok(vec![]),
+    };
+    let stakes = STAKES
+        .prefix(poll.id)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut msgs = vec![];
+    for (bettor, stake) in stakes {
+        let amount = payout(storage, poll, &stake)?;
+        if !amount.is_zero() {
+            msgs.push(BankMsg::Send {
+                to_address: bettor.to_string(),
+                amount: coins(amount.u128(), denom),
+            });
+        }
+        STAKES.remove(storage, (poll.id, &bettor));
+    }
+    OUTCOMES.remove(storage, poll.id);
+    Ok(msgs)
+}
+
+pub fn query_get_stake(deps: Deps, _env: Env, poll_id: u64, bettor: String) -> StdResult<Binary> {
+    let bettor = deps.api.addr_validate(&bettor)?;
+    let poll = polls().load(deps.storage, poll_id)?;
+    let stake = STAKES.may_load(deps.storage, (poll_id, &bettor))?;
+    let outcome = OUTCOMES.may_load(deps.storage, poll_id)?;
+    let payout = match &stake {
+        Some(stake) if outcome.is_some() || poll.status == PollStatus::Cancelled => {
+            payout(deps.storage, &poll, stake)?
+        }
+        _ => Uint128::zero(),
+    };
+    to_binary(&GetStakeResponse {
+        stake,
+        outcome,
+        payout,
+    })
+}
+
+/// What `stake` is worth: its share of the whole pool when it backed the
+/// outcome, nothing when it did not, and the stake itself when the market was
+/// cancelled, is unresolved or nobody backed the outcome.
+fn payout(storage: &dyn Storage, poll: &Poll, stake: &Stake) -> StdResult<Uint128> {
+    let outcome = match OUTCOMES.may_load(storage, poll.id)? {
+        Some(outcome) if poll.status != PollStatus::Cancelled => outcome,
+        _ => return Ok(stake.amount),
+    };
+    let winning = poll.options[outcome as usize].votes;
+    if winning.is_zero() {
+        return Ok(stake.amount);
+    }
+    if stake.option != outcome {
+        return Ok(Uint128::zero());
+    }
+    let total = poll
+        .options
+        .iter()
+        .try_fold(Uint128::zero(), |total, option| {
+            total.checked_add(option.votes)
+        })?;
+    Ok(stake.amount.multiply_ratio(total, winning))
+}
+
+fn market_denom(poll: &Poll) -> Result<&str, ContractError> {
+    match &poll.kind {
+        PollKind::Prediction { denom } => Ok(denom),
+        _ => Err(ContractError::NotPredictionMarket { poll_id: poll.id }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, from_binary, Addr, CosmosMsg};
+    use cw_utils::Expiration;
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{CreatePollMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+
+    use super::*;
+
+    #[test]
+    fn test_prediction_market() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::GrantRole {
+            address: "oracle".to_string(),
+            role: Role::Oracle,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Will ATOM flip OSMO".to_string(),
+            kind: PollKind::Prediction {
+                denom: "uatom".to_string(),
+            },
+            expiration: Some(Expiration::AtHeight(env.block.height + 10)),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let bet = |option| ExecuteMsg::PlaceBet { poll_id: 1, option };
+        for (bettor, option, amount) in [("addr1", 0, 30), ("addr2", 0, 10), ("addr3", 1, 60)] {
+            let info = mock_info(bettor, &[coin(amount, "uatom")]);
+            execute(deps.as_mut(), env.clone(), info, bet(option)).unwrap();
+        }
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr3", &[coin(5, "uatom")]),
+            bet(0),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::AlreadyStaked {
+                poll_id: 1,
+                option: 1
+            }
+        ));
+
+        let resolve = ExecuteMsg::ResolvePoll {
+            poll_id: 1,
+            outcome: 0,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("oracle", &[]),
+            resolve.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::PollStillOpen {}));
+
+        env.block.height += 10;
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let claim = ExecuteMsg::ClaimPayout { poll_id: 1 };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr1", &[]),
+            claim.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::MarketNotResolved { poll_id: 1 }
+        ));
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            resolve.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("oracle", &[]),
+            resolve,
+        )
+        .unwrap();
+
+        // the 60 lost by addr3 is shared 3:1 between addr1 and addr2
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr1", &[]),
+            claim.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr1".to_string(),
+                amount: coins(75, "uatom"),
+            })
+        );
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr3", &[]), claim).unwrap_err();
+        assert!(matches!(err, ContractError::NothingToWithdraw {}));
+
+        let msg = QueryMsg::GetStake {
+            poll_id: 1,
+            bettor: "addr2".to_string(),
+        };
+        let resp: GetStakeResponse = from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(
+            resp,
+            GetStakeResponse {
+                stake: Some(Stake {
+                    option: 0,
+                    amount: Uint128::new(10),
+                }),
+                outcome: Some(0),
+                payout: Uint128::new(25),
+            }
+        );
+        assert!(!STAKES.has(&deps.storage, (1, &Addr::unchecked("addr1"))));
+    }
+}
@@ -1,34 +1,1329 @@
+use std::fmt;
+
+use cosmwasm_std::{Addr, Binary, Coin, CosmosMsg, Decimal, HexBinary, Timestamp, Uint128};
+use cw2::ContractVersion;
+use cw20::Cw20ReceiveMsg;
+use cw_utils::{Duration, Expiration, Scheduled, ThresholdResponse};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::Poll;
+use crate::state::{
+    Ballot, Bounty, Campaign, CampaignSettings, ChildContract, Comment, Incentive, IncentiveTarget,
+    Poll, PollKind, PollStatus, PollTemplate, Raffle, RemotePower, RemotePowerConfig, RemoteTally,
+    Reputation, ReputationBonus, RewardPool, Role, Round, Stake, StreakBonus, Survey, TieBreak,
+    VotingPowerSource, WeightCap,
+};
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct InstantiateMsg {
     pub admin_address: String,
+    /// One vote per address when unset.
+    pub voting_power: Option<VotingPowerSourceMsg>,
+    #[serde(default)]
+    pub stake_validators: Vec<String>,
+    pub cw20_token: Option<String>,
+    #[serde(default)]
+    pub escrow_cw20: bool,
+    #[serde(default)]
+    pub snapshot_power: bool,
+    pub poll_deposit: Option<Coin>,
+    pub creation_fee: Option<Coin>,
+    #[serde(default)]
+    pub restrict_creation: bool,
+    pub question_min_len: Option<u32>,
+    pub question_max_len: Option<u32>,
+    /// Most messages a `Multicall` may carry, 10 when unset.
+    pub max_multicall: Option<u32>,
+    pub default_quorum: Option<Uint128>,
+    pub default_threshold: Option<Decimal>,
+    pub default_voting_period: Option<Duration>,
+    pub execution_delay: Option<Duration>,
+    pub veto_period: Option<Duration>,
+    pub remote_power: Option<RemotePowerConfig>,
+    /// Nois proxy to request raffle randomness from.
+    pub nois_proxy: Option<String>,
+    /// Fee the Nois proxy charges for the randomness of a tie draw, attached
+    /// to the `ClosePoll` that requests it.
+    pub nois_fee: Option<Coin>,
+    /// cw721 contract to mint vote receipts with. This contract must be its
+    /// minter.
+    pub receipt_minter: Option<String>,
+    pub reputation_bonus: Option<ReputationBonus>,
+    pub streak_bonus: Option<StreakBonus>,
+    pub weight_cap: Option<WeightCap>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
+pub enum VotingPowerSourceMsg {
+    FixedOnePerAddress,
+    Cw20Balance {
+        token: String,
+    },
+    Cw721Count {
+        collection: String,
+    },
+    NativeStake,
+    Cw4Group {
+        group: String,
+    },
+    LpShare {
+        pair: String,
+        lp_token: String,
+        asset: String,
+        rate: Decimal,
+    },
+    /// Locked tokens count fully unless a lower `multiplier` is given.
+    Vesting {
+        base: Box<VotingPowerSourceMsg>,
+        vesting: String,
+        multiplier: Option<Decimal>,
+    },
+}
+
+impl From<VotingPowerSource> for VotingPowerSourceMsg {
+    fn from(source: VotingPowerSource) -> Self {
+        match source {
+            VotingPowerSource::FixedOnePerAddress => VotingPowerSourceMsg::FixedOnePerAddress,
+            VotingPowerSource::Cw20Balance { token } => VotingPowerSourceMsg::Cw20Balance {
+                token: token.into_string(),
+            },
+            VotingPowerSource::Cw721Count { collection } => VotingPowerSourceMsg::Cw721Count {
+                collection: collection.into_string(),
+            },
+            VotingPowerSource::NativeStake => VotingPowerSourceMsg::NativeStake,
+            VotingPowerSource::Cw4Group { group } => VotingPowerSourceMsg::Cw4Group {
+                group: group.into_string(),
+            },
+            VotingPowerSource::LpShare {
+                pair,
+                lp_token,
+                asset,
+                rate,
+            } => VotingPowerSourceMsg::LpShare {
+                pair: pair.into_string(),
+                lp_token: lp_token.into_string(),
+                asset,
+                rate,
+            },
+            VotingPowerSource::Vesting {
+                base,
+                vesting,
+                multiplier,
+            } => VotingPowerSourceMsg::Vesting {
+                base: Box::new((*base).into()),
+                vesting: vesting.into_string(),
+                multiplier: Some(multiplier),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::large_enum_variant)]
 pub enum ExecuteMsg {
-    CreatePoll { question: String },
-    Vote { question: String, choice: String },
+    CreatePoll(CreatePollMsg),
+    /// Instantiates a new copy of this contract and creates the poll in it,
+    /// keeping a large poll's storage apart. Owners only.
+    CreatePollContract(CreatePollMsg),
+    Vote {
+        poll_id: u64,
+        choice: Choice,
+        /// Merkle proof of eligibility, needed on the first vote in a poll
+        /// created with a `merkle_root`.
+        proof: Option<Vec<Binary>>,
+        /// The voter's reasoning, kept with the ballot.
+        memo: Option<String>,
+    },
+    /// Casts several ballots at once. Each is checked like a `Vote`, and the
+    /// whole batch fails if any of them does.
+    /// Votes for the option labelled `label`, adding it to the poll first if
+    /// it has none. Polls created with `allow_write_ins` only.
+    VoteWriteIn {
+        poll_id: u64,
+        label: String,
+        proof: Option<Vec<Binary>>,
+        memo: Option<String>,
+    },
+    /// Adds an option to an open poll created with `allow_new_options`.
+    /// Creators only.
+    AddOption {
+        poll_id: u64,
+        label: String,
+    },
+    VoteMany {
+        votes: Vec<VoteItem>,
+    },
+    /// Creates a new poll with the settings of an existing one and a fresh
+    /// voting window. The question gets the new poll's number appended unless
+    /// overridden, as questions are unique.
+    ClonePoll {
+        poll_id: u64,
+        #[serde(default)]
+        overrides: PollOverrides,
+    },
+    /// Saves a template polls can be created from with
+    /// `CreatePollFromTemplate`. Owners only.
+    RegisterTemplate(TemplateMsg),
+    /// Owners only.
+    RemoveTemplate {
+        template_id: u64,
+    },
+    /// Creates a poll with the settings of a template. Its voting period runs
+    /// from the current block.
+    CreatePollFromTemplate {
+        template_id: u64,
+        question: String,
+        description: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+    /// Endorses an open poll, once per address. Endorsements do not count as
+    /// votes.
+    Endorse {
+        poll_id: u64,
+    },
+    /// Posts a comment on a poll, optionally in reply to an earlier one.
+    Comment {
+        poll_id: u64,
+        body: String,
+        reply_to: Option<u64>,
+    },
+    DeleteComment {
+        poll_id: u64,
+        comment_id: u64,
+    },
+    /// Opens a survey: several questions answered together, one count per
+    /// respondent. Never expires unless `expiration` is given.
+    CreateSurvey {
+        title: String,
+        questions: Vec<SurveyQuestionMsg>,
+        expiration: Option<Expiration>,
+    },
+    /// Answers every question of a survey at once, by option index.
+    SubmitSurvey {
+        survey_id: u64,
+        answers: Vec<u32>,
+    },
+    /// Opens a quadratic funding round with the coins sent, in `denom`, as its
+    /// matching pool.
+    CreateRound {
+        name: String,
+        denom: String,
+        expiration: Expiration,
+    },
+    /// Adds the coins sent to an open round's matching pool.
+    FundRound {
+        round_id: u64,
+    },
+    /// Lists a project in an open round, paid to `recipient` or the sender.
+    ListProject {
+        round_id: u64,
+        name: String,
+        recipient: Option<String>,
+    },
+    /// Contributes the coins sent to a project, by its index in the round.
+    Contribute {
+        round_id: u64,
+        project: u32,
+    },
+    /// Pays out an expired round: every project gets its contributions and
+    /// its share of the matching pool.
+    CloseRound {
+        round_id: u64,
+    },
+    /// Opens a campaign, a set of polls run as one ballot, owned by the
+    /// sender. `settings` are applied to every poll added to it.
+    CreateCampaign {
+        name: String,
+        #[serde(default)]
+        settings: CampaignSettings,
+    },
+    /// Adds one of the sender's open polls, with no votes yet, to a campaign
+    /// they own.
+    AddPollToCampaign {
+        campaign_id: u64,
+        poll_id: u64,
+    },
+    /// Runs several of this contract's messages in order as the sender, failing
+    /// together. Funds cannot be attached, and messages that wait on a reply
+    /// cannot be included.
+    Multicall {
+        msgs: Vec<ExecuteMsg>,
+    },
+    /// Nominates a new admin, who takes over once they send `AcceptAdmin`
+    /// (before `expiry`, if set).
+    ProposeNewAdmin {
+        address: String,
+        expiry: Option<Expiration>,
+    },
+    AcceptAdmin {},
+    GrantRole {
+        address: String,
+        role: Role,
+    },
+    RevokeRole {
+        address: String,
+    },
+    AddToBlocklist {
+        address: String,
+    },
+    RemoveFromBlocklist {
+        address: String,
+    },
+    /// Updates the validators whose delegations count as stake.
+    UpdateStakeValidators {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    UpdateCreators {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// Sent by the configured cw4 group whenever its membership changes.
+    MemberChangedHook(MemberChangedHookMsg),
+    /// Registers a contract to be sent a `PollHookMsg` on poll events.
+    AddHook {
+        address: String,
+    },
+    RemoveHook {
+        address: String,
+    },
+    /// Takes no funds, except for the configured `nois_fee` when the poll is
+    /// tied and draws its winner.
+    ClosePoll {
+        poll_id: u64,
+    },
+    /// Dispatches the messages attached to a poll that closed as passed. Anyone
+    /// may trigger it, but only once. The poll ends up `Failed` if any of the
+    /// messages errors; the others still go through.
+    ExecutePoll {
+        poll_id: u64,
+    },
+    /// Stops a passed poll from being executed. Only the admin or the veto
+    /// council may veto, and only within the configured veto period.
+    VetoPoll {
+        poll_id: u64,
+    },
+    /// Sends the poll's current tally to its hub. Anyone may trigger it; the hub
+    /// replaces the previous tally from this chain with the new one.
+    SyncTally {
+        poll_id: u64,
+    },
+    /// Registers an interchain query over the sender's power on the configured
+    /// chain. Attach the deposit Neutron charges for registering queries.
+    RegisterRemotePower {},
+    /// Lets the creator fix up a poll before anyone has voted on it. Fields left
+    /// as `None` are kept.
+    UpdatePoll {
+        poll_id: u64,
+        question: Option<String>,
+        options: Option<Vec<String>>,
+        description: Option<String>,
+    },
+    /// Stops an open poll without an outcome and refunds its deposits. Only the
+    /// poll's creator or the admin may cancel it.
+    CancelPoll {
+        poll_id: u64,
+    },
+    /// Deletes a poll along with its ballots, for abuse cases. Escrowed tokens and
+    /// commit deposits go back to voters; the creation deposit is refunded unless
+    /// `slash_deposit` is set, in which case it is kept with the collected fees.
+    /// Bounties and stakes are refunded, and unclaimed rewards go to the admin.
+    RemovePoll {
+        poll_id: u64,
+        reason: String,
+        #[serde(default)]
+        slash_deposit: bool,
+    },
+    Receive(Cw20ReceiveMsg),
+    WithdrawTokens {
+        poll_id: u64,
+    },
+    CommitVote {
+        poll_id: u64,
+        hash: Binary,
+    },
+    RevealVote {
+        poll_id: u64,
+        choice: Choice,
+        salt: String,
+    },
+    DepositCredits {
+        poll_id: u64,
+    },
+    /// Stakes the coins sent on an option of a prediction market. Bets can be
+    /// topped up but not moved to another option.
+    PlaceBet {
+        poll_id: u64,
+        option: u32,
+    },
+    /// Settles a closed prediction market on `outcome`. Owners and oracles only.
+    ResolvePoll {
+        poll_id: u64,
+        outcome: u32,
+    },
+    /// Pays out the sender's winnings, or refunds their stake when the market
+    /// was cancelled or nobody backed the outcome.
+    ClaimPayout {
+        poll_id: u64,
+    },
+    /// Adds the coin sent to the poll's reward pool, see `ReceiveMsg::FundRewards`
+    /// for the cw20 token.
+    FundRewards {
+        poll_id: u64,
+    },
+    /// Pays the sender their share of a closed poll's reward pool, in proportion
+    /// to the weight of their ballot.
+    ClaimRewards {
+        poll_id: u64,
+    },
+    /// Requests randomness to draw `winners` voters of a closed poll. The coins
+    /// sent pay the `prize`, split among the winners, and the Nois fee. Poll
+    /// creators and owners only.
+    StartRaffle {
+        poll_id: u64,
+        winners: u32,
+        prize: Option<Coin>,
+    },
+    /// Nois callback delivering the randomness of a raffle or of a tie draw.
+    NoisReceive {
+        callback: NoisCallback,
+    },
+    /// Pays the sender the cw20 tokens incentives allotted them.
+    ClaimIncentives {},
+    /// Returns what an expired incentive did not allot to its sponsor.
+    /// Sponsors only.
+    WithdrawIncentive {
+        incentive_id: u64,
+    },
+    /// Sends the rewards left unclaimed after the claim period to `recipient`.
+    /// Owners only.
+    SweepRewards {
+        poll_id: u64,
+        recipient: String,
+    },
+    UpdateVote {
+        poll_id: u64,
+        choice: Choice,
+    },
+    RetractVote {
+        poll_id: u64,
+    },
+    Unbond {
+        amount: Uint128,
+    },
+    WithdrawFees {
+        recipient: String,
+    },
+    /// Tunes the defaults applied to new polls. Fields left as `None` are kept.
+    UpdateConfig {
+        default_quorum: Option<Uint128>,
+        default_threshold: Option<Decimal>,
+        default_voting_period: Option<Duration>,
+        execution_delay: Option<Duration>,
+        veto_period: Option<Duration>,
+        max_multicall: Option<u32>,
+    },
+    Pause {},
+    Unpause {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CreatePollMsg {
+    pub question: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub link: Option<String>,
+    pub options: Option<Vec<String>>,
+    /// Description and image of each option, in the order of `options`. Empty,
+    /// or one entry per option.
+    #[serde(default)]
+    pub option_details: Vec<OptionDetails>,
+    #[serde(default)]
+    pub kind: PollKind,
+    /// Announces the poll ahead of time, voting opens once this is reached.
+    pub start: Option<Scheduled>,
+    pub expiration: Option<Expiration>,
+    pub nft_gate: Option<NftGateMsg>,
+    pub commit_reveal: Option<CommitRevealMsg>,
+    pub veto_threshold: Option<Decimal>,
+    pub quorum: Option<Uint128>,
+    pub threshold: Option<Decimal>,
+    /// Makes the poll private to these voters.
+    pub voters: Option<Vec<String>>,
+    /// Makes the poll private to voters that can prove membership in this tree,
+    /// see [`crate::contract::verify_merkle_proof`].
+    pub merkle_root: Option<Binary>,
+    /// Turns the poll into a proposal that dispatches these messages once it
+    /// passes, see `ExecuteMsg::ExecutePoll`.
+    #[serde(default)]
+    pub msgs: Vec<CosmosMsg>,
+    /// Syncs the poll's tally to a poll hosted on the other end of a channel,
+    /// see `ExecuteMsg::SyncTally`.
+    pub hub: Option<HubLinkMsg>,
+    /// Weighs votes on this poll instead of the configured source.
+    pub voting_power: Option<VotingPowerSourceMsg>,
+    /// Opens a new instance of the poll each time it closes.
+    pub recurrence: Option<RecurrenceMsg>,
+    /// Escrows a bounty, sent along with the creation funds, that is paid out
+    /// when the poll closes with a winner.
+    pub bounty: Option<BountyMsg>,
+    /// Weighs votes by when they are cast, see `WeightDecayMsg`.
+    pub weight_decay: Option<WeightDecayMsg>,
+    /// Caps the weight of each ballot instead of the configured cap.
+    pub weight_cap: Option<WeightCap>,
+    /// Rejects ballots of voters with less voting power than this.
+    pub min_power: Option<Uint128>,
+    /// Share of the total voting power at creation that must vote, or the poll
+    /// closes as `QuorumNotMet`.
+    pub turnout: Option<Decimal>,
+    #[serde(default)]
+    pub tie_break: TieBreak,
+    /// Opens a runoff between the two leading options, lasting this long, when
+    /// none gets more than the pass threshold of the option votes. Polls with
+    /// more than two options only.
+    pub runoff_period: Option<Duration>,
+    /// Lets voters add options with `VoteWriteIn`. Single choice polls without
+    /// commit-reveal or a hub only.
+    #[serde(default)]
+    pub allow_write_ins: bool,
+    /// Lets the creator add options with `AddOption` while the poll is open.
+    /// Not accepted on petitions, prediction markets and polls with a hub.
+    #[serde(default)]
+    pub allow_new_options: bool,
+    /// Keeps the poll pending until this open poll closes. It then opens, for
+    /// as long as it had left until its expiration, if the parent passed and
+    /// is cancelled otherwise. Cannot be combined with `start` or commit-reveal.
+    pub opens_if_passed: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct OptionDetails {
+    pub description: Option<String>,
+    /// External URL or IPFS hash of an image of the option.
+    pub image: Option<String>,
+}
+
+/// Multiplies vote weights by a factor going linearly from `initial`, when
+/// voting opens, to `closing` at expiration. Early votes count more when
+/// `initial` is the larger. Both factors must be above zero and at most 10.
+/// The poll needs an expiration, of the same kind as
+/// its start when it has one.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct WeightDecayMsg {
+    pub initial: Decimal,
+    pub closing: Decimal,
+}
+
+/// Paid to `recipient`, or split evenly among the voters who backed the winning
+/// option when it is not set. Refunded if the poll ends without a winner.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BountyMsg {
+    pub amount: Coin,
+    pub recipient: Option<String>,
+}
+
+/// Runs a poll `count` times in total. Each instance after the first is opened
+/// when its predecessor closes and stays open for `interval`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RecurrenceMsg {
+    pub interval: Duration,
+    pub count: u32,
+}
+
+/// Randomness published by Nois for a job this contract requested.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct NoisCallback {
+    pub job_id: String,
+    pub published: Timestamp,
+    pub randomness: HexBinary,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SurveyQuestionMsg {
+    pub question: String,
+    pub options: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HubLinkMsg {
+    pub channel_id: String,
+    pub poll_id: u64,
+}
+
+/// Settings of a poll template. Unset fields fall back to the configured
+/// defaults, as with `CreatePoll`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TemplateMsg {
+    pub name: String,
+    pub options: Option<Vec<String>>,
+    #[serde(default)]
+    pub kind: PollKind,
+    pub voting_period: Option<Duration>,
+    pub veto_threshold: Option<Decimal>,
+    pub quorum: Option<Uint128>,
+    pub threshold: Option<Decimal>,
+}
+
+/// Fields replacing the source poll's in `ClonePoll`. Expiration falls back to
+/// the default voting period, as with `CreatePoll`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PollOverrides {
+    pub question: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub options: Option<Vec<String>>,
+    pub start: Option<Scheduled>,
+    pub expiration: Option<Expiration>,
+    pub quorum: Option<Uint128>,
+    pub threshold: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct NftGateMsg {
+    pub collection: String,
+    #[serde(default)]
+    pub weight_by_count: bool,
+}
+
+/// Votes are committed as `sha256(voter || to_vec(choice) || salt)` until the poll
+/// expires, then revealed within `reveal_window`. A `deposit`, when set, must be
+/// attached to every commit and is only refunded on reveal.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CommitRevealMsg {
+    pub reveal_window: Duration,
+    pub deposit: Option<Coin>,
+}
+
+/// Sent to every registered hook as `{"poll_hook": ...}`. A hook that fails
+/// is reported in a `hook_failed` event without reverting the poll event.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollHookMsg {
+    PollCreated {
+        poll_id: u64,
+        creator: String,
+    },
+    VoteCast {
+        poll_id: u64,
+        voter: String,
+        choice: Choice,
+        weight: Uint128,
+    },
+    PollClosed {
+        poll_id: u64,
+        status: PollStatus,
+        winner: Option<String>,
+    },
+}
+
+/// Execute message wrapping `PollHookMsg` for hook contracts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HookExecuteMsg {
+    PollHook(PollHookMsg),
+}
+
+/// Queries understood by cw4 group contracts.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw4QueryMsg {
+    Member {
+        addr: String,
+        at_height: Option<u64>,
+    },
+    TotalWeight {
+        at_height: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MemberResponse {
+    pub weight: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TotalWeightResponse {
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MemberChangedHookMsg {
+    pub diffs: Vec<MemberDiff>,
+}
+
+/// A member's weight before and after a change, `None` when not a member.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MemberDiff {
+    pub key: String,
+    pub old: Option<u64>,
+    pub new: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveMsg {
+    Vote {
+        poll_id: u64,
+        choice: Choice,
+    },
+    Bond {},
+    FundRewards {
+        poll_id: u64,
+    },
+    /// Sets the tokens sent aside for voters on the polls `target` picks, see
+    /// `Incentive`.
+    CreateIncentive {
+        target: IncentiveTarget,
+        per_poll: Uint128,
+        expiration: Expiration,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VoteItem {
+    pub poll_id: u64,
+    pub choice: Choice,
+    pub proof: Option<Vec<Binary>>,
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Choice {
+    Yes,
+    No,
+    Abstain,
+    NoWithVeto,
+    Option(u32),
+    /// Option indices in order of preference, used by ranked-choice polls.
+    Ranking(Vec<u32>),
+    /// Votes spread across options, used by quadratic polls.
+    Allocation(Vec<Allocation>),
+    /// Every option the voter approves of, used by approval polls.
+    Approval(Vec<u32>),
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let join = |indices: &[u32]| {
+            indices
+                .iter()
+                .map(|index| index.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        match self {
+            Choice::Yes => write!(f, "yes"),
+            Choice::No => write!(f, "no"),
+            Choice::Abstain => write!(f, "abstain"),
+            Choice::NoWithVeto => write!(f, "no_with_veto"),
+            Choice::Option(index) => write!(f, "option:{}", index),
+            Choice::Ranking(ranking) => write!(f, "ranking:{}", join(ranking)),
+            Choice::Allocation(allocations) => {
+                let allocations = allocations
+                    .iter()
+                    .map(|allocation| format!("{}={}", allocation.option, allocation.votes))
+                    .collect::<Vec<_>>();
+                write!(f, "allocation:{}", allocations.join(","))
+            }
+            Choice::Approval(approved) => write!(f, "approval:{}", join(approved)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct Allocation {
+    pub option: u32,
+    pub votes: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    GetPoll { question: String },
+    GetPoll {
+        poll_id: u64,
+    },
+    GetPollByQuestion {
+        question: String,
+    },
+    ListPolls {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     GetConfig,
+    GetCredits {
+        poll_id: u64,
+        voter: String,
+    },
+    GetRaffle {
+        poll_id: u64,
+    },
+    GetReputation {
+        address: String,
+    },
+    GetStreak {
+        address: String,
+    },
+    /// The addresses that cast the most ballots, most active first.
+    Leaderboard {
+        limit: Option<u32>,
+    },
+    /// The vote receipt minted for `voter`'s ballot, if any.
+    GetReceipt {
+        poll_id: u64,
+        voter: String,
+    },
+    GetRewardPool {
+        poll_id: u64,
+    },
+    GetIncentive {
+        incentive_id: u64,
+    },
+    /// Incentive tokens `voter` can claim.
+    GetAccruedIncentives {
+        voter: String,
+    },
+    GetBounty {
+        poll_id: u64,
+    },
+    /// A bettor's stake on a prediction market and what it currently pays out.
+    GetStake {
+        poll_id: u64,
+        bettor: String,
+    },
+    GetRole {
+        address: String,
+    },
+    IsEligible {
+        poll_id: u64,
+        voter: String,
+    },
+    ListVoters {
+        poll_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    GetVote {
+        poll_id: u64,
+        voter: String,
+    },
+    ListPollsByStatus {
+        status: PollStatus,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    ListPollsByCreator {
+        creator: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GetTally {
+        poll_id: u64,
+    },
+    GetStats {},
+    ListPollsByTag {
+        tag: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Tallies received from each spoke for a hub poll.
+    ListRemoteTallies {
+        poll_id: u64,
+    },
+    GetRemotePower {
+        voter: String,
+    },
+    /// Polls whose question starts with `prefix`, in question order. Paginate by
+    /// passing the last question seen as `start_after`.
+    SearchPolls {
+        prefix: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    ListHooks {},
+    /// Instances of the recurring poll `poll_id` belongs to, in order.
+    ListSeries {
+        poll_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Every round of the runoff chain `poll_id` is part of, first round first.
+    ListRunoffRounds {
+        poll_id: u64,
+    },
+    /// Open polls with the most endorsements first.
+    ListTrendingPolls {
+        limit: Option<u32>,
+    },
+    /// cw3: the poll as a proposal.
+    Proposal {
+        proposal_id: u64,
+    },
+    /// cw3: polls as proposals, in id order.
+    ListProposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// cw3: a voter's ballot, if it is a yes, no, abstain or veto.
+    Vote {
+        proposal_id: u64,
+        voter: String,
+    },
+    /// cw3: yes, no, abstain and veto ballots in voter order.
+    ListVotes {
+        proposal_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// cw3: the default pass threshold.
+    Threshold {},
+    /// DAO DAO: bonded or group voting power of `address`, at the current
+    /// height by default.
+    VotingPowerAtHeight {
+        address: String,
+        height: Option<u64>,
+    },
+    /// DAO DAO: total bonded or group voting power.
+    TotalPowerAtHeight {
+        height: Option<u64>,
+    },
+    /// DAO DAO: contract name and version.
+    Info {},
+    /// Comments on a poll, oldest first.
+    ListComments {
+        poll_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// A funding round with what each project would be matched so far.
+    GetRound {
+        round_id: u64,
+    },
+    /// Registered poll templates, by id.
+    ListTemplates {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// The tally of every poll of a campaign, in the order they were added.
+    GetCampaignTally {
+        campaign_id: u64,
+    },
+    /// How many voters took part in a campaign, and how many voted on all of
+    /// its polls.
+    GetCampaignTurnout {
+        campaign_id: u64,
+    },
+    /// A survey with the answer counts of each question.
+    GetSurvey {
+        survey_id: u64,
+    },
+    GetSurveyAnswers {
+        survey_id: u64,
+        respondent: String,
+    },
+    /// Predicted address of the contract `CreatePollContract` spawns for
+    /// `poll_id`.
+    ChildAddress {
+        poll_id: u64,
+    },
+    /// Contracts spawned by `CreatePollContract`, in creation order.
+    ListChildren {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct GetPollResponse {
     pub poll: Option<Poll>,
+    pub is_open: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PollSummary {
+    pub id: u64,
+    pub question: String,
+    pub total_votes: Uint128,
+    pub expiration: Expiration,
+    pub status: PollStatus,
+    pub is_open: bool,
+    pub endorsements: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ListPollsResponse {
+    pub polls: Vec<PollSummary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetRaffleResponse {
+    pub raffle: Option<Raffle>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetReputationResponse {
+    pub reputation: Reputation,
+    /// Applied to the address's votes, when a reputation bonus is configured.
+    pub multiplier: Option<Decimal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetStreakResponse {
+    /// Polls in a row, up to the last one closed, the address voted on.
+    pub current: u64,
+    pub longest: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LeaderboardEntry {
+    pub address: Addr,
+    pub votes_cast: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct LeaderboardResponse {
+    pub voters: Vec<LeaderboardEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetReceiptResponse {
+    pub token_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetRewardPoolResponse {
+    pub pool: Option<RewardPool>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetIncentiveResponse {
+    pub incentive: Option<Incentive>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetAccruedIncentivesResponse {
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetBountyResponse {
+    pub bounty: Option<Bounty>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetStakeResponse {
+    pub stake: Option<Stake>,
+    pub outcome: Option<u32>,
+    /// Claimable now: zero until the market is resolved or cancelled.
+    pub payout: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetCreditsResponse {
+    pub total: u64,
+    pub spent: u64,
+    pub remaining: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetRoleResponse {
+    pub role: Option<Role>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct IsEligibleResponse {
+    pub eligible: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw3Status {
+    Pending,
+    Open,
+    Rejected,
+    Passed,
+    Executed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Cw3Vote {
+    Yes,
+    No,
+    Abstain,
+    Veto,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProposalResponse {
+    pub id: u64,
+    pub title: String,
+    pub description: String,
+    pub msgs: Vec<CosmosMsg>,
+    pub status: Cw3Status,
+    pub expires: Expiration,
+    pub threshold: ThresholdResponse,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProposalListResponse {
+    pub proposals: Vec<ProposalResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VoteInfo {
+    pub proposal_id: u64,
+    pub voter: String,
+    pub vote: Cw3Vote,
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VoteResponse {
+    pub vote: Option<VoteInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VoteListResponse {
+    pub votes: Vec<VoteInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VotingPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TotalPowerAtHeightResponse {
+    pub power: Uint128,
+    pub height: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct InfoResponse {
+    pub info: ContractVersion,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ListHooksResponse {
+    pub hooks: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ListCommentsResponse {
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetSurveyResponse {
+    pub survey: Survey,
+    pub is_open: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ListTemplatesResponse {
+    pub templates: Vec<PollTemplate>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetCampaignTallyResponse {
+    pub campaign: Campaign,
+    pub polls: Vec<GetTallyResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetCampaignTurnoutResponse {
+    pub campaign_id: u64,
+    /// Ballots cast across the campaign's polls.
+    pub ballots: u64,
+    /// Addresses that voted on at least one of the polls.
+    pub voters: u64,
+    /// Addresses that voted on every poll.
+    pub complete_ballots: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetRoundResponse {
+    pub round: Round,
+    pub is_open: bool,
+    /// What each project would be matched if the round closed now.
+    pub estimated_matches: Vec<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetSurveyAnswersResponse {
+    pub answers: Option<Vec<u32>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ListChildrenResponse {
+    pub children: Vec<ChildContract>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ChildAddressResponse {
+    pub address: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VoterInfo {
+    pub voter: Addr,
+    pub choice: Choice,
+    pub weight: Uint128,
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ChannelTally {
+    pub channel_id: String,
+    pub tally: RemoteTally,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ListRemoteTalliesResponse {
+    pub tallies: Vec<ChannelTally>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetRemotePowerResponse {
+    pub remote_power: Option<RemotePower>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ListVotersResponse {
+    pub voters: Vec<VoterInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetVoteResponse {
+    pub ballot: Option<Ballot>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct OptionTally {
+    pub label: String,
+    pub votes: Uint128,
+    /// Share of `total_votes`, abstentions and vetoes included.
+    pub percentage: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetTallyResponse {
+    pub poll_id: u64,
+    pub status: PollStatus,
+    pub options: Vec<OptionTally>,
+    pub abstain_votes: Uint128,
+    pub veto_votes: Uint128,
+    pub total_votes: Uint128,
+    /// Label of the leading option while the poll is open, or the winner once
+    /// it is closed. `None` on ties or when nobody voted.
+    pub winner: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GetStatsResponse {
+    pub total_polls: u64,
+    pub total_votes: u64,
+    pub open_polls: u64,
+    pub unique_voters: u64,
+}
+
+/// Messages the chain sends through native governance, bypassing roles.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMsg {
+    Pause {},
+    Unpause {},
+    /// Same as `UpdateConfig`, and can also hand the contract to a new admin.
+    SetConfig {
+        admin_address: Option<String>,
+        default_quorum: Option<Uint128>,
+        default_threshold: Option<Decimal>,
+        default_voting_period: Option<Duration>,
+        execution_delay: Option<Duration>,
+        veto_period: Option<Duration>,
+        max_multicall: Option<u32>,
+    },
+    RemovePoll {
+        poll_id: u64,
+        reason: String,
+        #[serde(default)]
+        slash_deposit: bool,
+    },
+    /// Sent by Neutron once a relayer submits a new result for an interchain query.
+    KvQueryResult {
+        query_id: u64,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub enum MigrateMsg {}
+pub struct MigrateMsg {}
@@ -0,0 +1,183 @@
+//! Voting weight lookups for each `VotingPowerSource`.
+
+use cosmwasm_std::{Addr, Deps, StdResult, Uint128};
+use cw20::{BalanceResponse, Cw20QueryMsg};
+use cw721::{Cw721QueryMsg, TokensResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+use crate::msg::{Cw4QueryMsg, MemberResponse};
+use crate::state::{Config, VotingPowerSource, GROUP_WEIGHTS};
+
+impl VotingPowerSource {
+    /// Current weight of `voter`. Weights pushed by the configured cw4 group's
+    /// hook take precedence over querying it.
+    pub(crate) fn weight(
+        &self,
+        deps: Deps,
+        config: &Config,
+        voter: &Addr,
+    ) -> Result<Uint128, ContractError> {
+        match self {
+            VotingPowerSource::FixedOnePerAddress => Ok(Uint128::one()),
+            VotingPowerSource::Cw20Balance { token } => Ok(cw20_balance(deps, token, voter)?),
+            VotingPowerSource::Cw721Count { collection } => {
+                Ok(Uint128::from(nft_count(deps, collection, voter)?))
+            }
+            VotingPowerSource::NativeStake => {
+                Ok(staked_weight(deps, &config.stake_validators, voter)?)
+            }
+            VotingPowerSource::Cw4Group { group } => {
+                let cached = if config.voting_power == *self {
+                    GROUP_WEIGHTS.may_load(deps.storage, voter)?
+                } else {
+                    None
+                };
+                let weight = match cached {
+                    Some(weight) => weight,
+                    None => group_weight(deps, group, voter)?,
+                };
+                Ok(Uint128::from(weight))
+            }
+            VotingPowerSource::LpShare {
+                pair,
+                lp_token,
+                asset,
+                rate,
+            } => {
+                let pool: PoolResponse = deps
+                    .querier
+                    .query_wasm_smart(pair, &PairQueryMsg::Pool {})?;
+                if pool.total_share.is_zero() {
+                    return Ok(Uint128::zero());
+                }
+                let reserve = pool
+                    .assets
+                    .iter()
+                    .find(|pool_asset| pool_asset.info.id() == asset)
+                    .map(|pool_asset| pool_asset.amount)
+                    .unwrap_or_default();
+                let shares = cw20_balance(deps, lp_token, voter)?;
+                Ok(shares.multiply_ratio(reserve, pool.total_share) * *rate)
+            }
+            VotingPowerSource::Vesting {
+                base,
+                vesting,
+                multiplier,
+            } => {
+                let locked: VestingPowerResponse = deps.querier.query_wasm_smart(
+                    vesting,
+                    &VestingQueryMsg::VotingPower {
+                        user: voter.to_string(),
+                    },
+                )?;
+                let base = base.weight(deps, config, voter)?;
+                Ok(base.checked_add(locked.voting_power * *multiplier)?)
+            }
+        }
+    }
+}
+
+/// Query understood by Terraswap-style pairs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum PairQueryMsg {
+    Pool {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct PoolResponse {
+    assets: Vec<PoolAsset>,
+    total_share: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct PoolAsset {
+    info: AssetInfo,
+    amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum AssetInfo {
+    Token { contract_addr: String },
+    NativeToken { denom: String },
+}
+
+impl AssetInfo {
+    fn id(&self) -> &str {
+        match self {
+            AssetInfo::Token { contract_addr } => contract_addr,
+            AssetInfo::NativeToken { denom } => denom,
+        }
+    }
+}
+
+/// Query understood by Mars-style vesting contracts, which report the tokens
+/// a user still has locked as their voting power.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum VestingQueryMsg {
+    VotingPower { user: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct VestingPowerResponse {
+    voting_power: Uint128,
+}
+
+fn cw20_balance(deps: Deps, token: &Addr, owner: &Addr) -> StdResult<Uint128> {
+    let balance: BalanceResponse = deps.querier.query_wasm_smart(
+        token,
+        &Cw20QueryMsg::Balance {
+            address: owner.to_string(),
+        },
+    )?;
+    Ok(balance.balance)
+}
+
+fn group_weight(deps: Deps, group: &Addr, voter: &Addr) -> Result<u64, ContractError> {
+    let member: MemberResponse = deps.querier.query_wasm_smart(
+        group,
+        &Cw4QueryMsg::Member {
+            addr: voter.to_string(),
+            at_height: None,
+        },
+    )?;
+    member.weight.ok_or(ContractError::NotMember {})
+}
+
+fn staked_weight(deps: Deps, validators: &[String], voter: &Addr) -> StdResult<Uint128> {
+    let bonded_denom = deps.querier.query_bonded_denom()?;
+    let weight = deps
+        .querier
+        .query_all_delegations(voter)?
+        .into_iter()
+        .filter(|delegation| delegation.amount.denom == bonded_denom)
+        .filter(|delegation| validators.is_empty() || validators.contains(&delegation.validator))
+        .map(|delegation| delegation.amount.amount)
+        .sum();
+    Ok(weight)
+}
+
+pub(crate) fn nft_count(deps: Deps, collection: &Addr, owner: &Addr) -> StdResult<u64> {
+    const PAGE_SIZE: u32 = 100;
+
+    let mut count = 0;
+    let mut start_after = None;
+    loop {
+        let page: TokensResponse = deps.querier.query_wasm_smart(
+            collection,
+            &Cw721QueryMsg::Tokens {
+                owner: owner.to_string(),
+                start_after,
+                limit: Some(PAGE_SIZE),
+            },
+        )?;
+        count += page.tokens.len() as u64;
+        if page.tokens.len() < PAGE_SIZE as usize {
+            return Ok(count);
+        }
+        start_after = page.tokens.last().cloned();
+    }
+}
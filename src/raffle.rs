@@ -0,0 +1,337 @@
+//! Raffles among the voters of a closed poll, drawn with randomness from the
+//! Nois proxy configured as `nois_proxy`.
+//!
+//! `StartRaffle` forwards the coins sent, less the prize, to the proxy as its
+//! fee and asks it for the next beacon. The proxy answers with `NoisReceive`,
+//! which draws the winners and splits the prize among them.
+
+use cosmwasm_std::{
+    coins, to_binary, Addr, BankMsg, Binary, Coin, Deps, DepsMut, Env, Event, HexBinary,
+    MessageInfo, Order, Response, StdResult, Storage, Uint128, WasmMsg,
+};
+use cw_utils::nonpayable;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::contract::{ensure_role, load_poll};
+use crate::error::ContractError;
+use crate::msg::{GetRaffleResponse, NoisCallback};
+use crate::state::{PollStatus, Raffle, Role, BALLOTS, CONFIG, RAFFLES};
+use crate::tiebreak::{receive_draw, TIE_JOB_PREFIX};
+
+const JOB_PREFIX: &str = "raffle-";
+
+/// Execute messages of the Nois proxy.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum NoisProxyExecuteMsg {
+    GetNextRandomness { job_id: String },
+}
+
+/// Asks for randomness to draw `winners` voters of a closed poll. Poll creators
+/// and owners only.
+pub fn execute_start_raffle(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    winners: u32,
+    prize: Option<Coin>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let proxy = config
+        .nois_proxy
+        .clone()
+        .ok_or(ContractError::NoisNotConfigured {})?;
+    let poll = load_poll(deps.storage, poll_id)?;
+    if info.sender != poll.creator {
+        ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+    }
+    match poll.status {
+        PollStatus::Open => return Err(ContractError::PollStillOpen {}),
+        PollStatus::Cancelled => return Err(ContractError::PollClosed { poll_id }),
+        _ => {}
+    }
+    if winners == 0 {
+        return Err(ContractError::InvalidRaffle {});
+    }
+    if RAFFLES.has(deps.storage, poll_id) {
+        return Err(ContractError::RaffleExists { poll_id });
+    }
+
+    let mut fee = info.funds;
+    if let Some(prize) = &prize {
+        let paid = fee
+            .iter_mut()
+            .find(|coin| coin.denom == prize.denom && coin.amount >= prize.amount)
+            .ok_or_else(|| ContractError::InvalidFunds {
+                required: prize.to_string(),
+            })?;
+        paid.amount -= prize.amount;
+        fee.retain(|coin| !coin.amount.is_zero());
+    }
+    RAFFLES.save(
+        deps.storage,
+        poll_id,
+        &Raffle {
+            requested_by: info.sender.clone(),
+            winners,
+            prize,
+            randomness: None,
+            drawn: vec![],
+        },
+    )?;
+
+    let request = WasmMsg::Execute {
+        contract_addr: proxy.into_string(),
+        msg: to_binary(&NoisProxyExecuteMsg::GetNextRandomness {
+            job_id: format!("{}{}", JOB_PREFIX, poll_id),
+        })?,
+        funds: fee,
+    };
+    Ok(Response::new()
+        .add_message(request)
+        .add_attribute("action", "start_raffle")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("winners", winners.to_string()))
+}
+
+/// Draws the winners of the raffle the proxy answered, or breaks the tie of
+/// the poll it answered for.
+pub fn execute_nois_receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    callback: NoisCallback,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    if config.nois_proxy.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(poll_id) = callback
+        .job_id
+        .strip_prefix(TIE_JOB_PREFIX)
+        .and_then(|id| id.parse::<u64>().ok())
+    {
+        return receive_draw(deps, &env, &info.sender, poll_id, &callback.randomness);
+    }
+    let poll_id = callback
+        .job_id
+        .strip_prefix(JOB_PREFIX)
+        .and_then(|id| id.parse::<u64>().ok())
+        .ok_or_else(|| ContractError::UnknownJob {
+            job_id: callback.job_id.clone(),
+        })?;
+    let mut raffle = RAFFLES
+        .may_load(deps.storage, poll_id)?
+        .filter(|raffle| raffle.randomness.is_none())
+        .ok_or_else(|| ContractError::UnknownJob {
+            job_id: callback.job_id.clone(),
+        })?;
+
+    raffle.drawn = draw(deps.storage, poll_id, &callback.randomness, raffle.winners)?;
+    raffle.randomness = Some(callback.randomness);
+    RAFFLES.save(deps.storage, poll_id, &raffle)?;
+
+    let drawn = raffle
+        .drawn
+        .iter()
+        .map(|winner| winner.as_str())
+        .collect::<Vec<_>>();
+    let event = Event::new("raffle_drawn")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("winners", drawn.join(","));
+    Ok(Response::new()
+        .add_messages(prize_msgs(&raffle))
+        .add_attribute("action", "nois_receive")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_event(event))
+}
+
+/// Returns the prize of a raffle that was never drawn to whoever started it.
+pub(crate) fn cancel_raffle(storage: &mut dyn Storage, poll_id: u64) -> StdResult<Option<BankMsg>> {
+    let raffle = RAFFLES.may_load(storage, poll_id)?;
+    RAFFLES.remove(storage, poll_id);
+    Ok(raffle
+        .filter(|raffle| raffle.randomness.is_none())
+        .and_then(|raffle| {
+            raffle.prize.map(|prize| BankMsg::Send {
+                to_address: raffle.requested_by.into_string(),
+                amount: vec![prize],
+            })
+        }))
+}
+
+pub fn query_get_raffle(deps: Deps, _env: Env, poll_id: u64) -> StdResult<Binary> {
+    let raffle = RAFFLES.may_load(deps.storage, poll_id)?;
+    to_binary(&GetRaffleResponse { raffle })
+}
+
+/// Picks up to `winners` distinct voters. Each pick hashes the randomness with
+/// its round to choose among the voters not drawn yet.
+fn draw(
+    storage: &dyn Storage,
+    poll_id: u64,
+    randomness: &HexBinary,
+    winners: u32,
+) -> StdResult<Vec<Addr>> {
+    let mut voters = BALLOTS
+        .prefix(poll_id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut drawn = vec![];
+    for round in 0..winners {
+        if voters.is_empty() {
+            break;
+        }
+        let hash = Sha256::new()
+            .chain_update(randomness.as_slice())
+            .chain_update(round.to_be_bytes())
+            .finalize();
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&hash[..8]);
+        let index = u64::from_be_bytes(word) % voters.len() as u64;
+        drawn.push(voters.swap_remove(index as usize));
+    }
+    Ok(drawn)
+}
+
+/// Splits the prize evenly among the winners, the rest goes back to whoever
+/// started the raffle. Without winners the whole prize does.
+fn prize_msgs(raffle: &Raffle) -> Vec<BankMsg> {
+    let prize = match &raffle.prize {
+        Some(prize) => prize,
+        None => return vec![],
+    };
+    let send = |to: &Addr, amount: Uint128| BankMsg::Send {
+        to_address: to.to_string(),
+        amount: coins(amount.u128(), &prize.denom),
+    };
+    let count = Uint128::from(raffle.drawn.len() as u128);
+    let share = if count.is_zero() {
+        Uint128::zero()
+    } else {
+        prize.amount / count
+    };
+    let mut msgs = vec![];
+    if !share.is_zero() {
+        msgs.extend(raffle.drawn.iter().map(|winner| send(winner, share)));
+    }
+    let rest = prize.amount - share * count;
+    if !rest.is_zero() {
+        msgs.push(send(&raffle.requested_by, rest));
+    }
+    msgs
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, from_binary, CosmosMsg};
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{Choice, CreatePollMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+
+    use super::*;
+
+    #[test]
+    fn test_raffle() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            nois_proxy: Some("nois".to_string()),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        for voter in ["addr2", "addr3", "addr4"] {
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice: Choice::Yes,
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+
+        let start = ExecuteMsg::StartRaffle {
+            poll_id: 1,
+            winners: 2,
+            prize: Some(coin(101, "ujuno")),
+        };
+        let funds = [coin(101, "ujuno"), coin(50, "unois")];
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr1", &funds),
+            start.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::PollStillOpen {}));
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr1", &funds),
+            start,
+        )
+        .unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "nois".to_string(),
+                msg: to_binary(&NoisProxyExecuteMsg::GetNextRandomness {
+                    job_id: "raffle-1".to_string(),
+                })
+                .unwrap(),
+                funds: vec![coin(50, "unois")],
+            })
+        );
+
+        let callback = NoisCallback {
+            job_id: "raffle-1".to_string(),
+            published: env.block.time,
+            randomness: HexBinary::from([7u8; 32]),
+        };
+        let msg = ExecuteMsg::NoisReceive {
+            callback: callback.clone(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let msg = ExecuteMsg::NoisReceive { callback };
+        let result = execute(deps.as_mut(), env.clone(), mock_info("nois", &[]), msg).unwrap();
+
+        let resp: GetRaffleResponse =
+            from_binary(&query(deps.as_ref(), env, QueryMsg::GetRaffle { poll_id: 1 }).unwrap())
+                .unwrap();
+        let raffle = resp.raffle.unwrap();
+        assert_eq!(raffle.drawn.len(), 2);
+        assert_ne!(raffle.drawn[0], raffle.drawn[1]);
+        let payouts: Vec<_> = result
+            .messages
+            .into_iter()
+            .map(|msg| match msg.msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount }) => {
+                    (to_address, amount[0].amount.u128())
+                }
+                _ => panic!("unexpected message"),
+            })
+            .collect();
+        assert_eq!(
+            payouts,
+            vec![
+                (raffle.drawn[0].to_string(), 50),
+                (raffle.drawn[1].to_string(), 50),
+                ("addr1".to_string(), 1),
+            ]
+        );
+    }
+}
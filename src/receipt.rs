@@ -0,0 +1,202 @@
+//! cw721 receipts minted for every ballot through the `receipt_minter`
+//! configured, which must let this contract mint.
+//!
+//! Each receipt carries the poll id and a hash of the choice, so a voter can
+//! prove taking part without the token revealing how they voted. Mints are
+//! dispatched as submessages: one that fails is reported as an event and never
+//! reverts the vote.
+
+use cosmwasm_std::{
+    to_binary, to_vec, Addr, Binary, Deps, DepsMut, Env, Event, HexBinary, Response, StdResult,
+    Storage, SubMsg, SubMsgResult, WasmMsg,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::contract::MINT_RECEIPT_REPLY_ID;
+use crate::error::ContractError;
+use crate::msg::{Choice, GetReceiptResponse};
+use crate::state::{CONFIG, PENDING_RECEIPTS, RECEIPTS};
+
+/// Token metadata of a vote receipt.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReceiptExtension {
+    pub poll_id: u64,
+    /// sha256 of the JSON encoded choice.
+    pub choice_hash: HexBinary,
+}
+
+/// Execute messages of a cw721-base minter.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum MinterExecuteMsg {
+    Mint {
+        token_id: String,
+        owner: String,
+        token_uri: Option<String>,
+        extension: ReceiptExtension,
+    },
+}
+
+/// The mint of `voter`'s receipt for a ballot, when a minter is configured.
+pub(crate) fn receipt_msg(
+    storage: &mut dyn Storage,
+    poll_id: u64,
+    voter: &Addr,
+    choice: &Choice,
+) -> StdResult<Option<SubMsg>> {
+    let minter = match CONFIG.load(storage)?.receipt_minter {
+        Some(minter) => minter,
+        None => return Ok(None),
+    };
+    // mints reply in the order they were dispatched
+    let mut pending = PENDING_RECEIPTS.may_load(storage)?.unwrap_or_default();
+    pending.push((poll_id, voter.clone()));
+    PENDING_RECEIPTS.save(storage, &pending)?;
+
+    let mint = WasmMsg::Execute {
+        contract_addr: minter.into_string(),
+        msg: to_binary(&MinterExecuteMsg::Mint {
+            token_id: token_id(poll_id, voter),
+            owner: voter.to_string(),
+            token_uri: None,
+            extension: ReceiptExtension {
+                poll_id,
+                choice_hash: choice_hash(choice)?,
+            },
+        })?,
+        funds: vec![],
+    };
+    Ok(Some(SubMsg::reply_always(mint, MINT_RECEIPT_REPLY_ID)))
+}
+
+/// Records the receipt minted for the oldest pending mint.
+pub(crate) fn reply_mint_receipt(
+    deps: DepsMut,
+    result: SubMsgResult,
+) -> Result<Response, ContractError> {
+    let mut pending = PENDING_RECEIPTS.load(deps.storage)?;
+    if pending.is_empty() {
+        return Err(ContractError::UnknownReplyId {
+            id: MINT_RECEIPT_REPLY_ID,
+        });
+    }
+    let (poll_id, voter) = pending.remove(0);
+    PENDING_RECEIPTS.save(deps.storage, &pending)?;
+
+    let token_id = token_id(poll_id, &voter);
+    let response = Response::new().add_attribute("action", "reply_mint_receipt");
+    match result {
+        SubMsgResult::Ok(_) => {
+            RECEIPTS.save(deps.storage, (poll_id, &voter), &token_id)?;
+            Ok(response.add_attribute("token_id", token_id))
+        }
+        SubMsgResult::Err(error) => {
+            let event = Event::new("receipt_failed")
+                .add_attribute("poll_id", poll_id.to_string())
+                .add_attribute("voter", voter)
+                .add_attribute("error", error);
+            Ok(response.add_event(event))
+        }
+    }
+}
+
+pub fn query_get_receipt(deps: Deps, _env: Env, poll_id: u64, voter: String) -> StdResult<Binary> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let token_id = RECEIPTS.may_load(deps.storage, (poll_id, &voter))?;
+    to_binary(&GetReceiptResponse { token_id })
+}
+
+fn token_id(poll_id: u64, voter: &Addr) -> String {
+    format!("poll-{}-{}", poll_id, voter)
+}
+
+/// Lets anyone holding a receipt check the choice it was minted for.
+pub fn choice_hash(choice: &Choice) -> StdResult<HexBinary> {
+    let hash = Sha256::digest(to_vec(choice)?);
+    Ok(HexBinary::from(hash.as_slice()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, CosmosMsg, Reply, SubMsgResponse};
+
+    use crate::contract::{execute, instantiate, query, reply};
+    use crate::msg::{CreatePollMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+
+    use super::*;
+
+    #[test]
+    fn test_vote_receipt() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            receipt_minter: Some("minter".to_string()),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        for voter in ["addr2", "addr3"] {
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice: Choice::Yes,
+                proof: None,
+                memo: None,
+            };
+            let result = execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+            let mint = &result.messages[0];
+            assert_eq!(mint.id, MINT_RECEIPT_REPLY_ID);
+            assert_eq!(
+                mint.msg,
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "minter".to_string(),
+                    msg: to_binary(&MinterExecuteMsg::Mint {
+                        token_id: format!("poll-1-{}", voter),
+                        owner: voter.to_string(),
+                        token_uri: None,
+                        extension: ReceiptExtension {
+                            poll_id: 1,
+                            choice_hash: choice_hash(&Choice::Yes).unwrap(),
+                        },
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                })
+            );
+        }
+
+        let minted = Reply {
+            id: MINT_RECEIPT_REPLY_ID,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        };
+        reply(deps.as_mut(), env.clone(), minted).unwrap();
+        let failed = Reply {
+            id: MINT_RECEIPT_REPLY_ID,
+            result: SubMsgResult::Err("token_id already claimed".to_string()),
+        };
+        let result = reply(deps.as_mut(), env.clone(), failed).unwrap();
+        assert_eq!(result.events[0].ty, "receipt_failed");
+
+        let receipt = |voter: &str| -> GetReceiptResponse {
+            let msg = QueryMsg::GetReceipt {
+                poll_id: 1,
+                voter: voter.to_string(),
+            };
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap()
+        };
+        assert_eq!(receipt("addr2").token_id, Some("poll-1-addr2".to_string()));
+        assert_eq!(receipt("addr3").token_id, None);
+    }
+}
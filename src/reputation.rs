@@ -0,0 +1,253 @@
+//! Reputation counts what each address did: ballots cast, polls created and
+//! created polls that passed. With `reputation_bonus` configured every point
+//! adds to the weight of later votes, up to the configured multiplier.
+//!
+//! The counters are indexed by ballots cast, so `Leaderboard` reads the most
+//! active voters without going through their ballots.
+
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Decimal, Deps, Env, Order, StdResult, Storage, Uint128,
+};
+
+use crate::contract::{DEFAULT_LIMIT, MAX_LIMIT};
+use crate::error::ContractError;
+use crate::msg::{GetReputationResponse, LeaderboardEntry, LeaderboardResponse};
+use crate::state::{reputation, Config, Reputation, ReputationBonus, CONFIG};
+
+pub(crate) fn record_vote(storage: &mut dyn Storage, voter: &Addr) -> StdResult<()> {
+    update(storage, voter, |reputation| reputation.votes_cast += 1)
+}
+
+/// Takes back the vote of a retracted ballot.
+pub(crate) fn forget_vote(storage: &mut dyn Storage, voter: &Addr) -> StdResult<()> {
+    update(storage, voter, |reputation| {
+        reputation.votes_cast = reputation.votes_cast.saturating_sub(1)
+    })
+}
+
+pub(crate) fn record_poll_created(storage: &mut dyn Storage, creator: &Addr) -> StdResult<()> {
+    update(storage, creator, |reputation| reputation.polls_created += 1)
+}
+
+pub(crate) fn record_poll_won(storage: &mut dyn Storage, creator: &Addr) -> StdResult<()> {
+    update(storage, creator, |reputation| reputation.polls_won += 1)
+}
+
+/// Scales `weight` by `voter`'s reputation when a bonus is configured.
+pub(crate) fn apply_bonus(
+    storage: &dyn Storage,
+    config: &Config,
+    voter: &Addr,
+    weight: Uint128,
+) -> StdResult<Uint128> {
+    match &config.reputation_bonus {
+        Some(bonus) => Ok(weight * multiplier(storage, bonus, voter)?),
+        None => Ok(weight),
+    }
+}
+
+/// One plus `per_point` for every point of reputation, capped.
+fn multiplier(storage: &dyn Storage, bonus: &ReputationBonus, voter: &Addr) -> StdResult<Decimal> {
+    let score = reputation()
+        .may_load(storage, voter)?
+        .unwrap_or_default()
+        .score();
+    let multiplier = Decimal::from_atomics(score, 0)
+        .ok()
+        .and_then(|score| bonus.per_point.checked_mul(score).ok())
+        .and_then(|bonus| Decimal::one().checked_add(bonus).ok())
+        .unwrap_or(bonus.max_multiplier);
+    Ok(multiplier.min(bonus.max_multiplier))
+}
+
+pub(crate) fn validate_bonus(bonus: Option<&ReputationBonus>) -> Result<(), ContractError> {
+    match bonus {
+        Some(bonus) if bonus.max_multiplier < Decimal::one() => {
+            Err(ContractError::InvalidReputationBonus {})
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The counters of `address`, with the multiplier its votes get if any.
+pub fn query_get_reputation(deps: Deps, _env: Env, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let reputation = reputation()
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    let multiplier = CONFIG
+        .load(deps.storage)?
+        .reputation_bonus
+        .map(|bonus| multiplier(deps.storage, &bonus, &address))
+        .transpose()?;
+    to_binary(&GetReputationResponse {
+        reputation,
+        multiplier,
+    })
+}
+
+/// The `limit` addresses that cast the most ballots, most active first.
+pub fn query_leaderboard(deps: Deps, _env: Env, limit: Option<u32>) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let voters = reputation()
+        .idx
+        .votes_cast
+        .range(deps.storage, None, None, Order::Descending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, counters)| counters.votes_cast > 0)
+        })
+        .take(limit)
+        .map(|item| {
+            item.map(|(address, counters)| LeaderboardEntry {
+                address,
+                votes_cast: counters.votes_cast,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    to_binary(&LeaderboardResponse { voters })
+}
+
+fn update(
+    storage: &mut dyn Storage,
+    address: &Addr,
+    change: impl FnOnce(&mut Reputation),
+) -> StdResult<()> {
+    let mut counters = reputation().may_load(storage, address)?.unwrap_or_default();
+    change(&mut counters);
+    reputation().save(storage, address, &counters)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, to_binary};
+    use cw20::Cw20ReceiveMsg;
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{Choice, CreatePollMsg, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg};
+    use crate::state::polls;
+
+    use super::*;
+
+    #[test]
+    fn test_reputation_bonus() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let mut msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            reputation_bonus: Some(ReputationBonus {
+                per_point: Decimal::percent(50),
+                max_multiplier: Decimal::percent(90),
+            }),
+            ..Default::default()
+        };
+        let err = instantiate(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidReputationBonus {}));
+        msg.reputation_bonus = Some(ReputationBonus {
+            per_point: Decimal::percent(50),
+            max_multiplier: Decimal::percent(200),
+        });
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let create = |question: &str| {
+            ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
+            })
+        };
+        let vote = |poll_id: u64| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: "addr2".to_string(),
+                amount: Uint128::new(10),
+                msg: to_binary(&ReceiveMsg::Vote {
+                    poll_id,
+                    choice: Choice::Yes,
+                })
+                .unwrap(),
+            })
+        };
+        let token = mock_info("token", &[]);
+        let creator = mock_info("addr2", &[]);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            creator,
+            create("Do you love spark IBC"),
+        )
+        .unwrap();
+        execute(deps.as_mut(), env.clone(), token.clone(), vote(1)).unwrap();
+        let poll = polls().load(&deps.storage, 1).unwrap();
+        assert_eq!(poll.options[0].votes, Uint128::new(15));
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info,
+            create("Do you love cosmwasm"),
+        )
+        .unwrap();
+        execute(deps.as_mut(), env.clone(), token, vote(2)).unwrap();
+        let poll = polls().load(&deps.storage, 2).unwrap();
+        assert_eq!(poll.options[0].votes, Uint128::new(20));
+
+        let msg = QueryMsg::GetReputation {
+            address: "addr2".to_string(),
+        };
+        let resp: GetReputationResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(
+            resp.reputation,
+            Reputation {
+                votes_cast: 2,
+                polls_created: 1,
+                polls_won: 1,
+            }
+        );
+        assert_eq!(resp.multiplier, Some(Decimal::percent(200)));
+    }
+
+    #[test]
+    fn test_leaderboard() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        for question in ["Do you love spark IBC", "Do you love cosmwasm"] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+        for (voter, poll_id) in [("addr2", 1), ("addr3", 1), ("addr3", 2), ("addr4", 2)] {
+            let msg = ExecuteMsg::Vote {
+                poll_id,
+                choice: Choice::Yes,
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::RetractVote { poll_id: 2 };
+        execute(deps.as_mut(), env.clone(), mock_info("addr4", &[]), msg).unwrap();
+
+        let msg = QueryMsg::Leaderboard { limit: Some(5) };
+        let resp: LeaderboardResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        let ranked: Vec<_> = resp
+            .voters
+            .iter()
+            .map(|entry| (entry.address.as_str(), entry.votes_cast))
+            .collect();
+        assert_eq!(ranked, vec![("addr3", 2), ("addr2", 1)]);
+    }
+}
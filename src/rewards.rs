@@ -0,0 +1,413 @@
+//! Reward pools: anyone can fund a poll's pool while it is open, in a native
+//! coin or the configured cw20 token. Once the poll closes every voter can claim
+//! a share of the pool in proportion to their ballot's weight, until the claim
+//! period runs out and an owner sweeps what is left. The pools of polls that
+//! are cancelled or removed go back to their funders.
+
+use cosmwasm_std::{
+    coins, to_binary, Addr, BankMsg, Binary, BlockInfo, CosmosMsg, Deps, DepsMut, Env, Event,
+    MessageInfo, Order, Response, StdResult, Storage, Uint128,
+};
+use cw_utils::{nonpayable, one_coin, Duration};
+
+use crate::contract::{cw20_transfer, ensure_open, ensure_role, load_poll};
+use crate::error::ContractError;
+use crate::msg::GetRewardPoolResponse;
+use crate::state::{
+    Poll, RewardAsset, RewardPool, Role, BALLOTS, CONFIG, REWARD_CLAIMS, REWARD_FUNDERS,
+    REWARD_POOLS,
+};
+
+/// How long voters have to claim once a poll closes.
+const REWARD_CLAIM_PERIOD: Duration = Duration::Time(30 * 24 * 60 * 60);
+
+pub fn execute_fund_rewards(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    let coin = one_coin(&info)?;
+    let asset = RewardAsset::Native { denom: coin.denom };
+    fund_rewards(deps.storage, &info.sender, poll_id, asset, coin.amount)
+}
+
+/// Adds `amount` of `asset` to the pool of an open poll. A pool holds a single
+/// asset, set by its first funding.
+pub(crate) fn fund_rewards(
+    storage: &mut dyn Storage,
+    funder: &Addr,
+    poll_id: u64,
+    asset: RewardAsset,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let poll = load_poll(storage, poll_id)?;
+    ensure_open(&poll)?;
+    let mut pool = REWARD_POOLS
+        .may_load(storage, poll_id)?
+        .unwrap_or(RewardPool {
+            asset: asset.clone(),
+            amount: Uint128::zero(),
+            claimed: Uint128::zero(),
+            total_weight: Uint128::zero(),
+            claim_deadline: None,
+        });
+    if pool.asset != asset {
+        return Err(ContractError::RewardAssetMismatch {});
+    }
+    pool.amount = pool.amount.checked_add(amount)?;
+    REWARD_POOLS.save(storage, poll_id, &pool)?;
+    REWARD_FUNDERS.update(storage, (poll_id, funder), |funded| -> StdResult<_> {
+        Ok(funded.unwrap_or_default().checked_add(amount)?)
+    })?;
+
+    let event = Event::new("rewards_funded")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("funder", funder)
+        .add_attribute("amount", amount);
+    Ok(Response::new()
+        .add_attribute("action", "fund_rewards")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("pool", pool.amount)
+        .add_event(event))
+}
+
+/// Fixes the weight the pool of a poll that just closed is shared by and
+/// starts its claim period.
+pub(crate) fn open_reward_claims(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    poll: &Poll,
+) -> StdResult<()> {
+    let mut pool = match REWARD_POOLS.may_load(storage, poll.id)? {
+        Some(pool) => pool,
+        None => return Ok(()),
+    };
+    pool.total_weight = BALLOTS
+        .prefix(poll.id)
+        .range(storage, None, None, Order::Ascending)
+        .try_fold(Uint128::zero(), |total, item| -> StdResult<_> {
+            Ok(total.checked_add(item?.1.weight)?)
+        })?;
+    pool.claim_deadline = Some(REWARD_CLAIM_PERIOD.after(block));
+    REWARD_POOLS.save(storage, poll.id, &pool)
+}
+
+pub fn execute_claim_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut pool = REWARD_POOLS
+        .may_load(deps.storage, poll_id)?
+        .ok_or(ContractError::NoRewards { poll_id })?;
+    let claimable = pool
+        .claim_deadline
+        .is_some_and(|deadline| !deadline.is_expired(&env.block));
+    if !claimable {
+        return Err(ContractError::RewardsNotClaimable { poll_id });
+    }
+    let ballot = BALLOTS
+        .may_load(deps.storage, (poll_id, &info.sender))?
+        .ok_or(ContractError::NoVote { poll_id })?;
+    if REWARD_CLAIMS.has(deps.storage, (poll_id, &info.sender)) {
+        return Err(ContractError::AlreadyClaimed { poll_id });
+    }
+
+    let amount = pool.amount.multiply_ratio(ballot.weight, pool.total_weight);
+    REWARD_CLAIMS.save(deps.storage, (poll_id, &info.sender), &amount)?;
+    pool.claimed = pool.claimed.checked_add(amount)?;
+    REWARD_POOLS.save(deps.storage, poll_id, &pool)?;
+
+    Ok(Response::new()
+        .add_message(transfer(&pool.asset, &info.sender, amount)?)
+        .add_attribute("action", "claim_rewards")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("voter", info.sender)
+        .add_attribute("amount", amount))
+}
+
+/// Sends what voters left unclaimed to `recipient` once the claim period is
+/// over. Owners only.
+pub fn execute_sweep_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let pool = REWARD_POOLS
+        .may_load(deps.storage, poll_id)?
+        .ok_or(ContractError::NoRewards { poll_id })?;
+    if !pool
+        .claim_deadline
+        .is_some_and(|deadline| deadline.is_expired(&env.block))
+    {
+        return Err(ContractError::ClaimPeriodOpen { poll_id });
+    }
+
+    let amount = remove_pool(deps.storage, poll_id, &pool)?;
+    let mut response = Response::new();
+    if !amount.is_zero() {
+        response = response.add_message(transfer(&pool.asset, &recipient, amount)?);
+    }
+    Ok(response
+        .add_attribute("action", "sweep_rewards")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount))
+}
+
+/// Returns the unclaimed rest of a cancelled or removed poll's pool to its
+/// funders, in proportion to what each put in. The last funder also gets what
+/// the rounding leaves.
+pub(crate) fn refund_rewards(storage: &mut dyn Storage, poll_id: u64) -> StdResult<Vec<CosmosMsg>> {
+    let pool = match REWARD_POOLS.may_load(storage, poll_id)? {
+        Some(pool) => pool,
+        None => return Ok(vec![]),
+    };
+    let funders = REWARD_FUNDERS
+        .prefix(poll_id)
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut left = remove_pool(storage, poll_id, &pool)?;
+    let rest = left;
+
+    let mut refunds = vec![];
+    for (i, (funder, funded)) in funders.iter().enumerate() {
+        let refund = if i + 1 == funders.len() {
+            left
+        } else {
+            rest.multiply_ratio(*funded, pool.amount)
+        };
+        left = left.checked_sub(refund)?;
+        if !refund.is_zero() {
+            refunds.push(transfer(&pool.asset, funder, refund)?);
+        }
+    }
+    Ok(refunds)
+}
+
+/// Deletes the pool, its claims and its funders, returning what was left
+/// unclaimed.
+fn remove_pool(storage: &mut dyn Storage, poll_id: u64, pool: &RewardPool) -> StdResult<Uint128> {
+    let claimants = REWARD_CLAIMS
+        .prefix(poll_id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for claimant in claimants {
+        REWARD_CLAIMS.remove(storage, (poll_id, &claimant));
+    }
+    let funders = REWARD_FUNDERS
+        .prefix(poll_id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for funder in funders {
+        REWARD_FUNDERS.remove(storage, (poll_id, &funder));
+    }
+    REWARD_POOLS.remove(storage, poll_id);
+
+    Ok(pool.amount.checked_sub(pool.claimed)?)
+}
+
+pub fn query_get_reward_pool(deps: Deps, _env: Env, poll_id: u64) -> StdResult<Binary> {
+    let pool = REWARD_POOLS.may_load(deps.storage, poll_id)?;
+    to_binary(&GetRewardPoolResponse { pool })
+}
+
+fn transfer(asset: &RewardAsset, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(match asset {
+        RewardAsset::Native { denom } => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount.u128(), denom),
+        }
+        .into(),
+        RewardAsset::Cw20 { address } => cw20_transfer(address, recipient, amount)?.into(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, from_binary};
+    use cw20::Cw20ReceiveMsg;
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{Choice, CreatePollMsg, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg};
+
+    use super::*;
+
+    fn receive(sender: &str, amount: u128, msg: &ReceiveMsg) -> ExecuteMsg {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: sender.to_string(),
+            amount: Uint128::new(amount),
+            msg: to_binary(msg).unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_reward_pool() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let token = mock_info("token", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = receive("addr9", 100, &ReceiveMsg::FundRewards { poll_id: 1 });
+        execute(deps.as_mut(), env.clone(), token.clone(), msg).unwrap();
+        let msg = ExecuteMsg::FundRewards { poll_id: 1 };
+        let funder = mock_info("addr9", &[coin(1, "ujuno")]);
+        let err = execute(deps.as_mut(), env.clone(), funder, msg).unwrap_err();
+        assert!(matches!(err, ContractError::RewardAssetMismatch {}));
+
+        // token votes weigh as much as the tokens sent
+        for (voter, choice, amount) in [("addr2", Choice::Yes, 1), ("addr3", Choice::No, 3)] {
+            let msg = receive(voter, amount, &ReceiveMsg::Vote { poll_id: 1, choice });
+            execute(deps.as_mut(), env.clone(), token.clone(), msg).unwrap();
+        }
+        let claim = ExecuteMsg::ClaimRewards { poll_id: 1 };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            claim.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::RewardsNotClaimable { poll_id: 1 }
+        ));
+
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let token = Addr::unchecked("token");
+        let voter = mock_info("addr3", &[]);
+        let result = execute(deps.as_mut(), env.clone(), voter.clone(), claim.clone()).unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            cw20_transfer(&token, &voter.sender, Uint128::new(75))
+                .unwrap()
+                .into()
+        );
+        let err = execute(deps.as_mut(), env.clone(), voter, claim.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::AlreadyClaimed { poll_id: 1 }));
+
+        let sweep = ExecuteMsg::SweepRewards {
+            poll_id: 1,
+            recipient: "treasury".to_string(),
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), sweep.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::ClaimPeriodOpen { poll_id: 1 }));
+
+        env.block.time = env.block.time.plus_seconds(30 * 24 * 60 * 60);
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), claim).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::RewardsNotClaimable { poll_id: 1 }
+        ));
+        let result = execute(deps.as_mut(), env.clone(), info, sweep).unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            cw20_transfer(&token, &Addr::unchecked("treasury"), Uint128::new(25))
+                .unwrap()
+                .into()
+        );
+
+        let resp: GetRewardPoolResponse = from_binary(
+            &query(deps.as_ref(), env, QueryMsg::GetRewardPool { poll_id: 1 }).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(resp.pool, None);
+    }
+
+    #[test]
+    fn test_refund_rewards() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        for question in ["Do you love spark IBC", "Do you love juno"] {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+        let fund = |poll_id, funder: &str, amount| {
+            (
+                mock_info(funder, &[coin(amount, "ujuno")]),
+                ExecuteMsg::FundRewards { poll_id },
+            )
+        };
+        for (funder, msg) in [
+            fund(1, "addr8", 100),
+            fund(1, "addr9", 200),
+            fund(2, "addr8", 10),
+        ] {
+            execute(deps.as_mut(), env.clone(), funder, msg).unwrap();
+        }
+        let refund = |funder: &str, amount| -> CosmosMsg {
+            BankMsg::Send {
+                to_address: funder.to_string(),
+                amount: coins(amount, "ujuno"),
+            }
+            .into()
+        };
+
+        // a cancelled poll's pool goes back to its funders
+        let msg = ExecuteMsg::CancelPoll { poll_id: 2 };
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert!(result
+            .messages
+            .iter()
+            .any(|msg| msg.msg == refund("addr8", 10)));
+        assert!(!REWARD_POOLS.has(&deps.storage, 2));
+        assert!(!REWARD_FUNDERS.has(&deps.storage, (2, &Addr::unchecked("addr8"))));
+
+        // so does what voters left unclaimed in a removed poll's
+        for (voter, choice) in [("addr2", Choice::Yes), ("addr3", Choice::No)] {
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice,
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = ExecuteMsg::ClaimRewards { poll_id: 1 };
+        execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap();
+        let msg = ExecuteMsg::RemovePoll {
+            poll_id: 1,
+            reason: "spam".to_string(),
+            slash_deposit: false,
+        };
+        let result = execute(deps.as_mut(), env, info, msg).unwrap();
+        let msgs: Vec<_> = result.messages.into_iter().map(|msg| msg.msg).collect();
+        assert!(msgs.contains(&refund("addr8", 50)));
+        assert!(msgs.contains(&refund("addr9", 100)));
+        assert!(!msgs.iter().any(|msg| *msg == refund("addr1", 150)));
+        assert!(!REWARD_FUNDERS.has(&deps.storage, (1, &Addr::unchecked("addr9"))));
+    }
+}
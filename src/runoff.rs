@@ -0,0 +1,168 @@
+//! Runoffs: a poll created with a `runoff_period` whose leading option does
+//! not get more than its threshold of the option votes, a majority when unset,
+//! closes without a winner and opens a poll between its two leading options.
+//!
+//! Rounds are linked both ways through `runoff_of` and `runoff`, so the chain
+//! can be listed from any of its polls.
+
+use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, Response, StdResult};
+use cw_utils::Duration;
+
+use crate::contract::{create_poll, load_poll, poll_msg, poll_summary};
+use crate::error::ContractError;
+use crate::msg::ListPollsResponse;
+use crate::state::{polls, Poll, PollKind, POLL_COUNT};
+
+pub(crate) fn validate_runoff(
+    kind: &PollKind,
+    options: &[String],
+    runoff_period: Option<Duration>,
+) -> Result<(), ContractError> {
+    match runoff_period {
+        Some(Duration::Height(0) | Duration::Time(0)) => Err(ContractError::InvalidRunoff {}),
+        Some(_) if *kind != PollKind::SingleChoice || options.len() < 3 => {
+            Err(ContractError::InvalidRunoff {})
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Opens the runoff between `options` of a poll that just closed, on behalf of
+/// its creator.
+pub(crate) fn open_runoff(
+    mut deps: DepsMut,
+    env: &Env,
+    poll: &Poll,
+    options: (usize, usize),
+    period: Duration,
+) -> Result<(u64, Response), ContractError> {
+    let runoff_id = POLL_COUNT.load(deps.storage)? + 1;
+    let mut msg = poll_msg(deps.storage, poll.clone())?;
+    msg.question = format!("{} (runoff)", poll.question);
+    msg.options = Some(vec![
+        poll.options[options.0].label.clone(),
+        poll.options[options.1].label.clone(),
+    ]);
+    if !msg.option_details.is_empty() {
+        msg.option_details = vec![
+            msg.option_details[options.0].clone(),
+            msg.option_details[options.1].clone(),
+        ];
+    }
+    msg.expiration = Some(period.after(&env.block));
+    msg.runoff_period = None;
+    msg.allow_write_ins = false;
+    msg.allow_new_options = false;
+    let response = create_poll(deps.branch(), env, &poll.creator, None, msg)?;
+
+    let mut runoff = load_poll(deps.storage, runoff_id)?;
+    runoff.runoff_of = Some(poll.id);
+    polls().save(deps.storage, runoff_id, &runoff)?;
+    let mut closed = load_poll(deps.storage, poll.id)?;
+    closed.runoff = Some(runoff_id);
+    polls().save(deps.storage, poll.id, &closed)?;
+    Ok((runoff_id, response))
+}
+
+/// Every round of the runoff chain `poll_id` is part of, first round first.
+pub fn query_list_runoff_rounds(deps: Deps, env: Env, poll_id: u64) -> StdResult<Binary> {
+    let mut first = polls().load(deps.storage, poll_id)?;
+    while let Some(previous) = first.runoff_of {
+        first = polls().load(deps.storage, previous)?;
+    }
+    let mut rounds = vec![poll_summary(&first, &env.block)?];
+    let mut next = first.runoff;
+    while let Some(id) = next {
+        let round = polls().load(deps.storage, id)?;
+        rounds.push(poll_summary(&round, &env.block)?);
+        next = round.runoff;
+    }
+    to_binary(&ListPollsResponse { polls: rounds })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::from_binary;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{Choice, CreatePollMsg, ExecuteMsg, InstantiateMsg, QueryMsg};
+    use crate::state::PollStatus;
+
+    use super::*;
+
+    #[test]
+    fn test_runoff() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = |options: &[&str]| {
+            ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: "Which chain do you love".to_string(),
+                options: Some(options.iter().map(|option| option.to_string()).collect()),
+                runoff_period: Some(Duration::Height(100)),
+                ..Default::default()
+            })
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            msg(&["juno", "osmosis"]),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::InvalidRunoff {}));
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            info.clone(),
+            msg(&["juno", "osmosis", "stargaze"]),
+        )
+        .unwrap();
+
+        // stargaze leads with half the votes, short of a majority
+        let votes = [
+            ("addr2", 2),
+            ("addr3", 2),
+            ("addr4", 2),
+            ("addr5", 1),
+            ("addr6", 1),
+            ("addr7", 0),
+        ];
+        for (voter, option) in votes {
+            let msg = ExecuteMsg::Vote {
+                poll_id: 1,
+                choice: Choice::Option(option),
+                proof: None,
+                memo: None,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::ClosePoll { poll_id: 1 };
+        let result = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        assert!(result
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "runoff_poll_id" && attr.value == "2"));
+
+        let closed = polls().load(&deps.storage, 1).unwrap();
+        assert_eq!(closed.status, PollStatus::Closed);
+        let runoff = polls().load(&deps.storage, 2).unwrap();
+        assert_eq!(runoff.question, "Which chain do you love (runoff)");
+        let labels: Vec<_> = runoff.options.iter().map(|option| &option.label).collect();
+        assert_eq!(labels, ["stargaze", "osmosis"]);
+        assert_eq!(runoff.expiration, Duration::Height(100).after(&env.block));
+
+        let msg = QueryMsg::ListRunoffRounds { poll_id: 2 };
+        let resp: ListPollsResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        let ids: Vec<_> = resp.polls.iter().map(|poll| poll.id).collect();
+        assert_eq!(ids, [1, 2]);
+    }
+}
@@ -1,20 +1,832 @@
-use cosmwasm_std::Addr;
+use std::fmt;
+
+use cosmwasm_std::{
+    Addr, Binary, Coin, CosmosMsg, Decimal, Empty, HexBinary, IbcEndpoint, Timestamp, Uint128,
+};
+use cw_utils::{Duration, Expiration, Scheduled};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cw_storage_plus::{Item, Map};
+use cw_storage_plus::{
+    Index, IndexList, IndexedMap, Item, Map, MultiIndex, SnapshotItem, SnapshotMap, Strategy,
+    UniqueIndex,
+};
+
+use crate::msg::{Choice, CreatePollMsg};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
     pub admin_address: Addr,
+    /// Weighs votes cast with `Vote` or `CommitVote` unless the poll has its own
+    /// source, or `remote_power`, `snapshot_power` or `cw20_token` is set.
+    pub voting_power: VotingPowerSource,
+    /// Only delegations to these validators count towards `NativeStake`
+    /// weights. All do when empty.
+    pub stake_validators: Vec<String>,
+    pub cw20_token: Option<Addr>,
+    pub escrow_cw20: bool,
+    /// Votes are weighted by cw20 tokens bonded to this contract as of the
+    /// poll's creation height.
+    pub snapshot_power: bool,
+    /// Native coins locked by `CreatePoll` and refunded once the poll is closed.
+    pub poll_deposit: Option<Coin>,
+    /// Non-refundable fee paid on `CreatePoll`, kept until the admin withdraws it.
+    pub creation_fee: Option<Coin>,
+    /// While set, every execute message other than `Unpause` is rejected.
+    pub paused: bool,
+    /// Only addresses in `CREATORS` may create polls.
+    pub restrict_creation: bool,
+    /// Byte length bounds for poll questions, after trimming.
+    pub question_min_len: u32,
+    pub question_max_len: u32,
+    /// Most messages a `Multicall` may carry.
+    pub max_multicall: u32,
+    /// Applied to polls created without their own quorum.
+    pub default_quorum: Option<Uint128>,
+    /// Applied to polls created without their own pass threshold.
+    pub default_threshold: Option<Decimal>,
+    /// Sets the expiration of polls created without one, counted from their
+    /// start when it is of the same kind.
+    pub default_voting_period: Option<Duration>,
+    /// How long a passed poll has to wait after closing before `ExecutePoll`.
+    pub execution_delay: Option<Duration>,
+    /// How long passed polls can be vetoed for after closing. They cannot be
+    /// executed until it is over.
+    pub veto_period: Option<Duration>,
+    /// Replaces voter weights with their power on another chain, proven by
+    /// interchain queries each voter registers with `RegisterRemotePower`.
+    pub remote_power: Option<RemotePowerConfig>,
+    /// Nois proxy raffles get their randomness from.
+    pub nois_proxy: Option<Addr>,
+    /// Sent to the Nois proxy with every tie draw. Whoever closes a poll that
+    /// draws attaches it to `ClosePoll`.
+    pub nois_fee: Option<Coin>,
+    /// cw721 contract minting a receipt for every ballot cast.
+    pub receipt_minter: Option<Addr>,
+    /// Weighs votes up by the reputation of their voter.
+    pub reputation_bonus: Option<ReputationBonus>,
+    /// Weighs votes up for voters on a participation streak.
+    pub streak_bonus: Option<StreakBonus>,
+    /// Applied to polls created without their own weight cap.
+    pub weight_cap: Option<WeightCap>,
+}
+
+/// Multiplies vote weights by one plus `per_point` for every point of the
+/// voter's reputation, up to `max_multiplier`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReputationBonus {
+    pub per_point: Decimal,
+    pub max_multiplier: Decimal,
+}
+
+/// Where vote weights come from.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VotingPowerSource {
+    #[default]
+    FixedOnePerAddress,
+    /// The voter's balance of a cw20 token.
+    Cw20Balance { token: Addr },
+    /// Number of tokens of a cw721 collection the voter owns.
+    Cw721Count { collection: Addr },
+    /// Native tokens the voter has delegated in the bonded denom.
+    NativeStake,
+    /// The voter's weight in a cw4 group. Non-members cannot vote.
+    Cw4Group { group: Addr },
+    /// The voter's share of `asset` in a pair's reserves, from their balance
+    /// of its `lp_token`, multiplied by `rate`.
+    LpShare {
+        pair: Addr,
+        lp_token: Addr,
+        /// Denom or cw20 contract of the reserve asset counted.
+        asset: String,
+        rate: Decimal,
+    },
+    /// `base` plus the tokens still locked in a vesting contract, multiplied
+    /// by `multiplier`.
+    Vesting {
+        base: Box<VotingPowerSource>,
+        vesting: Addr,
+        multiplier: Decimal,
+    },
+}
+
+/// Neutron interchain query settings for remote voting power.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RemotePowerConfig {
+    /// IBC connection to the chain the power is held on.
+    pub connection_id: String,
+    /// Blocks between result updates submitted by relayers.
+    pub update_period: u64,
+    pub source: RemotePowerSource,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RemotePowerSource {
+    Balance {
+        denom: String,
+    },
+    /// Shares delegated to a validator, given by its raw operator address bytes.
+    Delegation {
+        validator: Binary,
+    },
+}
+
+/// Latest power proven for a voter by their interchain query.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RemotePower {
+    pub query_id: u64,
+    pub power: Uint128,
+    /// Remote block height the power was proven at, zero until the first result.
+    pub height: u64,
+}
+
+/// Roles that can be granted on top of the admin, who is always an owner.
+/// Owners can do everything, moderators can close polls early and manage the
+/// blocklist, operators can pause and unpause the contract, the veto council
+/// can veto passed polls and oracles resolve prediction markets.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Owner,
+    Moderator,
+    Operator,
+    VetoCouncil,
+    Oracle,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Role::Owner => write!(f, "owner"),
+            Role::Moderator => write!(f, "moderator"),
+            Role::Operator => write!(f, "operator"),
+            Role::VetoCouncil => write!(f, "veto_council"),
+            Role::Oracle => write!(f, "oracle"),
+        }
+    }
+}
+
+/// A contract spawned by `CreatePollContract` to hold a single poll.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChildContract {
+    pub id: u64,
+    /// Poll id the child's poll takes in this contract, which salts its
+    /// address.
+    pub poll_id: u64,
+    pub address: Addr,
+    pub question: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAdmin {
+    pub address: Addr,
+    pub expiry: Option<Expiration>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Poll {
+    pub id: u64,
+    pub creator: Addr,
+    pub question: String,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    /// External URL or IPFS hash with more context on the poll.
+    pub link: Option<String>,
+    pub start_height: u64,
+    /// Votes are rejected until this point is reached.
+    pub start: Option<Scheduled>,
+    pub options: Vec<PollOption>,
+    pub kind: PollKind,
+    pub abstain_votes: Uint128,
+    pub veto_votes: Uint128,
+    pub veto_threshold: Option<Decimal>,
+    /// Minimum total vote weight, abstentions included, for the poll to pass or
+    /// have a winner.
+    pub quorum: Option<Uint128>,
+    /// Share of yes votes among yes, no and veto votes that must be exceeded
+    /// for a yes/no poll to pass. A simple majority when unset.
+    pub threshold: Option<Decimal>,
+    pub expiration: Expiration,
+    pub status: PollStatus,
+    pub nft_gate: Option<NftGate>,
+    pub commit_reveal: Option<CommitReveal>,
+    /// Only addresses in `POLL_VOTERS` may vote.
+    pub private: bool,
+    /// Root of a Merkle tree over `sha256(voter)` leaves. Voters join
+    /// `POLL_VOTERS` by attaching a proof to their `Vote`.
+    pub merkle_root: Option<Binary>,
+    /// Dispatched by `ExecutePoll` once the poll has passed.
+    pub msgs: Vec<CosmosMsg>,
+    /// Earliest point `ExecutePoll` is accepted at, set when the poll passes.
+    pub executable_at: Option<Expiration>,
+    /// End of the window in which the poll can be vetoed, set when it passes.
+    pub veto_deadline: Option<Expiration>,
+    /// Makes this a spoke of a poll hosted on another chain.
+    pub hub: Option<HubLink>,
+    /// Overrides the configured source of vote weights.
+    pub voting_power: Option<VotingPowerSource>,
+    /// Number of addresses that sent `Endorse`, counted apart from votes.
+    pub endorsements: u64,
+    /// Set on the instances of a recurring poll.
+    pub series: Option<PollSeries>,
+    pub weight_decay: Option<WeightDecay>,
+    /// Most weight a single ballot can carry.
+    pub weight_cap: Option<WeightCap>,
+    /// Least voting power, before bonuses, an address needs to vote.
+    pub min_power: Option<Uint128>,
+    /// Share of `total_power` that must vote for the poll to have an outcome.
+    pub turnout: Option<Decimal>,
+    /// Total voting power when the poll was created, kept for polls with a
+    /// turnout.
+    pub total_power: Option<Uint128>,
+    pub tie_break: TieBreak,
+    /// Options found tied when the poll was last closed.
+    pub tied: Vec<u32>,
+    /// Option the tie was broken in favor of.
+    pub tie_winner: Option<u32>,
+    /// How long the runoff opened when no option wins lasts.
+    pub runoff_period: Option<Duration>,
+    /// Poll this one is the runoff of.
+    pub runoff_of: Option<u64>,
+    /// Runoff opened when this poll closed.
+    pub runoff: Option<u64>,
+    /// Keeps the poll pending until another poll passes.
+    pub condition: Option<PollCondition>,
+    /// Voters can add options along with their vote.
+    pub allow_write_ins: bool,
+    /// The creator can add options while the poll is open.
+    pub allow_new_options: bool,
+}
+
+/// See `CreatePollMsg::opens_if_passed`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollCondition {
+    pub parent: u64,
+    /// Voting period the poll gets once it opens, none for polls that never
+    /// expire.
+    pub voting_period: Option<Duration>,
+    /// Set once the parent passed and the poll opened.
+    pub met: bool,
+}
+
+/// What closing a poll does when its leading options are tied, or when the yes
+/// votes of a yes/no poll exactly meet what it takes to pass.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    /// Yes/no polls are rejected, other polls close without a winner.
+    #[default]
+    Reject,
+    /// Yes/no polls pass. Only accepted on yes/no polls.
+    FavorYes,
+    /// Keeps the poll open `by` more, once. A tie after that is rejected.
+    ExtendDeadline { by: Duration },
+    /// Draws the winner among the tied options with randomness from the
+    /// configured Nois proxy. The closer pays its fee, see `Config::nois_fee`.
+    RandomViaNois,
+}
+
+/// See `WeightDecayMsg`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WeightDecay {
+    pub initial: Decimal,
+    pub closing: Decimal,
+    /// When voting opened, in the same unit as the poll's expiration.
+    pub opens: Scheduled,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollSeries {
+    /// Id of the series' first poll.
+    pub first: u64,
+    pub previous: Option<u64>,
+    pub interval: Duration,
+    /// Instances still to be opened after this one.
+    pub remaining: u32,
+}
+
+/// Points a local poll at the hub poll its tally is synced to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HubLink {
+    pub channel_id: String,
+    pub poll_id: u64,
+    /// Number of tallies sent so far, the hub drops any it has seen newer ones than.
+    pub sequence: u64,
+    /// Outcome reported by the hub once it closes the poll.
+    pub result: Option<PollStatus>,
+}
+
+/// Latest cumulative tally a spoke sent for a hub poll.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct RemoteTally {
+    pub sequence: u64,
+    pub options: Vec<Uint128>,
+    pub abstain_votes: Uint128,
+    pub veto_votes: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CommitReveal {
+    pub reveal_expiration: Expiration,
+    pub deposit: Option<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftGate {
+    pub collection: Addr,
+    /// Replaces the voter's weight with the number of tokens they hold.
+    pub weight_by_count: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollKind {
+    #[default]
+    SingleChoice,
+    /// Voters rank the options and the winner is found by instant runoff at close.
+    RankedChoice,
+    /// Voters approve any subset of the options, each approved option gets their weight.
+    Approval,
+    /// Voters spread credits across options, paying `votes²` credits per option.
+    /// Everyone starts with `credits`; more can be bought one-for-one by depositing
+    /// `credit_denom` when it is set.
+    Quadratic {
+        credits: u64,
+        credit_denom: Option<String>,
+    },
+    /// No options, supporters sign with `Choice::Yes`. Passes as soon as the
+    /// signatures reach `target`, counted per signer, or by voting weight when
+    /// `weighted` is set. Petitions short of the target when they close are
+    /// rejected.
+    Petition { target: Uint128, weighted: bool },
+    /// A prediction market: bettors stake `denom` on an option with `PlaceBet`
+    /// and the winners share the pool once an oracle resolves the outcome.
+    Prediction { denom: String },
+    /// Ballots gather conviction, their weight for every block they stay on
+    /// an option, for at most `max_blocks` blocks when set. Changing or
+    /// retracting a ballot starts it over. Options are ranked by conviction at
+    /// close, and the quorum is measured in conviction too.
+    Conviction { max_blocks: Option<u64> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollStatus {
+    /// Open but not started yet. Never stored, queries report it in place of
+    /// `Open` until the poll's `start` is reached.
+    Pending,
+    Open,
+    Passed,
+    Rejected,
+    Closed,
+    /// Passed and had its messages dispatched.
+    Executed,
+    /// Passed, but one of its messages failed when executed.
+    Failed,
+    /// Passed but vetoed before it could be executed.
+    Vetoed,
+    /// Called off by its creator or the admin, no outcome is recorded.
+    Cancelled,
+    /// Closed with less than its turnout voting, whatever the votes were.
+    QuorumNotMet,
+}
+
+impl fmt::Display for PollStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PollStatus::Pending => write!(f, "pending"),
+            PollStatus::Open => write!(f, "open"),
+            PollStatus::Passed => write!(f, "passed"),
+            PollStatus::Rejected => write!(f, "rejected"),
+            PollStatus::Closed => write!(f, "closed"),
+            PollStatus::Executed => write!(f, "executed"),
+            PollStatus::Vetoed => write!(f, "vetoed"),
+            PollStatus::Failed => write!(f, "failed"),
+            PollStatus::Cancelled => write!(f, "cancelled"),
+            PollStatus::QuorumNotMet => write!(f, "quorum_not_met"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollOption {
+    pub label: String,
+    pub votes: Uint128,
+    pub description: Option<String>,
+    /// External URL or IPFS hash of an image of the option.
+    pub image: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Ballot {
+    pub choice: Choice,
+    pub weight: Uint128,
+    /// Block height the ballot was cast, or last updated, at.
+    pub height: u64,
+    /// Rationale given with a `Vote`.
+    pub memo: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Commitment {
+    pub hash: Binary,
+    pub weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SurveyQuestion {
     pub question: String,
-    pub yes_votes: u64,
-    pub no_votes: u64,
+    pub options: Vec<PollOption>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Survey {
+    pub id: u64,
+    pub creator: Addr,
+    pub title: String,
+    pub questions: Vec<SurveyQuestion>,
+    pub expiration: Expiration,
+    pub respondents: u64,
+}
+
+/// Settings polls can be created from, see `ExecuteMsg::RegisterTemplate`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollTemplate {
+    pub id: u64,
+    pub name: String,
+    pub options: Option<Vec<String>>,
+    pub kind: PollKind,
+    pub voting_period: Option<Duration>,
+    pub veto_threshold: Option<Decimal>,
+    pub quorum: Option<Uint128>,
+    pub threshold: Option<Decimal>,
+}
+
+/// Polls run as one ballot, see `ExecuteMsg::CreateCampaign`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Campaign {
+    pub id: u64,
+    pub owner: Addr,
+    pub name: String,
+    /// In the order they were added.
+    pub poll_ids: Vec<u64>,
+    pub settings: CampaignSettings,
+}
+
+/// Settings shared by the polls of a campaign. Those set replace the poll's own
+/// when it is added.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct CampaignSettings {
+    pub quorum: Option<Uint128>,
+    pub threshold: Option<Decimal>,
+}
+
+/// A quadratic funding round, see `ExecuteMsg::CreateRound`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Round {
+    pub id: u64,
+    pub creator: Addr,
+    pub name: String,
+    pub denom: String,
+    pub matching_pool: Uint128,
+    pub expiration: Expiration,
+    /// Set once the round paid out.
+    pub closed: bool,
+    /// Projects by the index contributions refer to them with.
+    pub projects: Vec<Project>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Project {
+    pub name: String,
+    pub recipient: Addr,
+    /// Escrowed until the round closes.
+    pub contributions: Uint128,
+    /// Sum over contributors of the square root of their total contribution.
+    pub sqrt_sum: Decimal,
+    pub contributors: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Comment {
+    pub id: u64,
+    pub author: Addr,
+    pub body: String,
+    /// Id of the comment on the same poll this one answers.
+    pub reply_to: Option<u64>,
+    pub created_at: Timestamp,
+}
+
+/// Running totals kept up to date by the execute handlers.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Stats {
+    pub total_votes: u64,
+    pub open_polls: u64,
+    pub unique_voters: u64,
+}
+
+/// Multiplies the vote weights of voters on a streak of at least `min_streak`
+/// polls by one plus `per_poll` for every poll of it, up to `max_multiplier`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StreakBonus {
+    pub min_streak: u64,
+    pub per_poll: Decimal,
+    pub max_multiplier: Decimal,
+}
+
+/// Limits the weight of any single ballot, after bonuses, so that large
+/// holders cannot decide a poll alone.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WeightCap {
+    Absolute {
+        amount: Uint128,
+    },
+    /// Share of the total voting power when the poll opened, or of the token
+    /// supply when votes are weighed by a cw20 token.
+    ShareOfSupply {
+        share: Decimal,
+    },
+}
+
+/// Polls in a row an address voted on, counted in the order they closed.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Streak {
+    pub current: u64,
+    pub longest: u64,
+    /// Number of the last close the address voted on the poll of.
+    pub last_close: u64,
+}
+
+/// What an address did, kept up to date by the execute handlers.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Reputation {
+    pub votes_cast: u64,
+    pub polls_created: u64,
+    /// Polls created by the address that passed.
+    pub polls_won: u64,
+}
+
+impl Reputation {
+    /// Every counter is worth a point.
+    pub fn score(&self) -> u64 {
+        self.votes_cast + self.polls_created + self.polls_won
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollDeposit {
+    pub depositor: Addr,
+    pub amount: Coin,
+}
+
+/// Escrowed with `CreatePoll`, see `CreatePollMsg::bounty`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Bounty {
+    pub creator: Addr,
+    pub amount: Coin,
+    pub recipient: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardAsset {
+    Native { denom: String },
+    Cw20 { address: Addr },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardPool {
+    pub asset: RewardAsset,
+    pub amount: Uint128,
+    pub claimed: Uint128,
+    /// Weight of all ballots on the poll, fixed when it closes.
+    pub total_weight: Uint128,
+    /// Claims are open from the poll's close until this passes.
+    pub claim_deadline: Option<Expiration>,
+}
+
+/// Cw20 tokens a sponsor set aside for the voters of some polls, see
+/// `ReceiveMsg::CreateIncentive`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Incentive {
+    pub id: u64,
+    pub sponsor: Addr,
+    pub target: IncentiveTarget,
+    /// Most a single poll closing allots to its voters.
+    pub per_poll: Uint128,
+    pub amount: Uint128,
+    /// Part of `amount` already allotted to voters.
+    pub allotted: Uint128,
+    pub expiration: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IncentiveTarget {
+    /// Every poll with the tag.
+    Tag {
+        tag: String,
+    },
+    Polls {
+        poll_ids: Vec<u64>,
+    },
+}
+
+/// A draw among the voters of a poll, see `ExecuteMsg::StartRaffle`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Raffle {
+    pub requested_by: Addr,
+    pub winners: u32,
+    pub prize: Option<Coin>,
+    /// Beacon the winners were drawn with, `None` until the proxy answers.
+    pub randomness: Option<HexBinary>,
+    pub drawn: Vec<Addr>,
+}
+
+/// Coins a bettor has on one option of a prediction market.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Stake {
+    pub option: u32,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct CreditAccount {
+    /// Credits bought with `DepositCredits`, one per coin of the poll's
+    /// `credit_denom`.
+    pub deposited: u64,
+    pub spent: u64,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
-pub const POLLS: Map<String, Poll> = Map::new("polls");
+pub const ROLES: Map<&Addr, Role> = Map::new("roles");
+/// Addresses barred from voting and creating polls.
+pub const BLOCKLIST: Map<&Addr, Empty> = Map::new("blocklist");
+pub const CREATORS: Map<&Addr, Empty> = Map::new("creators");
+/// Contracts sent a `PollHookMsg` whenever a poll is created, voted on or closed.
+pub const HOOKS: Map<&Addr, Empty> = Map::new("hooks");
+pub const POLL_VOTERS: Map<(u64, &Addr), Empty> = Map::new("poll_voters");
+pub const STATS: Item<Stats> = Item::new("stats");
+/// Every address that has ever had a ballot counted.
+pub const VOTERS: Map<&Addr, Empty> = Map::new("voters");
+/// Polls closed so far, numbering the closes streaks refer to.
+pub const CLOSE_COUNT: Item<u64> = Item::new("close_count");
+pub const STREAKS: Map<&Addr, Streak> = Map::new("streaks");
+pub const PENDING_ADMIN: Item<PendingAdmin> = Item::new("pending_admin");
+pub const POLL_COUNT: Item<u64> = Item::new("poll_count");
+/// Poll whose messages are being dispatched, read back when they reply.
+pub const EXECUTING_POLL: Item<u64> = Item::new("executing_poll");
+/// Voter whose interchain query is being registered, read back on reply.
+pub const REGISTERING_VOTER: Item<Addr> = Item::new("registering_voter");
+/// Ballots whose receipt mint has not replied yet, oldest first.
+pub const PENDING_RECEIPTS: Item<Vec<(u64, Addr)>> = Item::new("pending_receipts");
+/// Token id of the receipt minted for each ballot.
+pub const RECEIPTS: Map<(u64, &Addr), String> = Map::new("receipts");
+pub const REMOTE_POWER: Map<&Addr, RemotePower> = Map::new("remote_power");
+/// Voters by the id of their interchain query.
+pub const REMOTE_POWER_QUERIES: Map<u64, Addr> = Map::new("remote_power_queries");
+pub const CHILD_COUNT: Item<u64> = Item::new("child_count");
+pub const CHILDREN: Map<u64, ChildContract> = Map::new("children");
+/// Poll id and poll to create in the child being instantiated by
+/// `CreatePollContract`.
+pub const PENDING_CHILD_POLL: Item<(u64, CreatePollMsg)> = Item::new("pending_child_poll");
+pub struct PollIndexes<'a> {
+    pub question: UniqueIndex<'a, String, Poll, u64>,
+    pub creator: MultiIndex<'a, Addr, Poll, u64>,
+    pub status: MultiIndex<'a, String, Poll, u64>,
+    /// Status and endorsement count, to list the most endorsed polls of a status.
+    pub endorsements: MultiIndex<'a, (String, u64), Poll, u64>,
+}
+
+impl<'a> IndexList<Poll> for PollIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Poll>> + '_> {
+        let v: Vec<&dyn Index<Poll>> = vec![
+            &self.question,
+            &self.creator,
+            &self.status,
+            &self.endorsements,
+        ];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn polls<'a>() -> IndexedMap<'a, u64, Poll, PollIndexes<'a>> {
+    let indexes = PollIndexes {
+        question: UniqueIndex::new(|poll| poll.question.clone(), "polls__question"),
+        creator: MultiIndex::new(|_pk, poll| poll.creator.clone(), "polls", "polls__creator"),
+        status: MultiIndex::new(
+            |_pk, poll| poll.status.to_string(),
+            "polls",
+            "polls__status",
+        ),
+        endorsements: MultiIndex::new(
+            |_pk, poll| (poll.status.to_string(), poll.endorsements),
+            "polls",
+            "polls__endorsements",
+        ),
+    };
+    IndexedMap::new("polls", indexes)
+}
+
+pub struct ReputationIndexes<'a> {
+    /// Ballots cast over the address's lifetime, to rank the most active voters.
+    pub votes_cast: MultiIndex<'a, u64, Reputation, Addr>,
+}
+
+impl<'a> IndexList<Reputation> for ReputationIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Reputation>> + '_> {
+        let v: Vec<&dyn Index<Reputation>> = vec![&self.votes_cast];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn reputation<'a>() -> IndexedMap<'a, &'a Addr, Reputation, ReputationIndexes<'a>> {
+    let indexes = ReputationIndexes {
+        votes_cast: MultiIndex::new(
+            |_pk, reputation| reputation.votes_cast,
+            "reputation",
+            "reputation__votes_cast",
+        ),
+    };
+    IndexedMap::new("reputation", indexes)
+}
+
+/// Connected IBC channels, with the counterparty endpoint of each.
+pub const CHANNELS: Map<&str, IbcEndpoint> = Map::new("channels");
+/// Tallies received from spokes, by hub poll and channel. They are already
+/// included in the hub poll's counts.
+pub const REMOTE_TALLIES: Map<(u64, &str), RemoteTally> = Map::new("remote_tallies");
+/// Local spoke polls by the channel and id of their hub poll.
+pub const HUB_POLLS: Map<(&str, u64), u64> = Map::new("hub_polls");
+pub const POLL_TAGS: Map<(&str, u64), Empty> = Map::new("poll_tags");
+/// Instances of recurring polls by the id of their series' first poll.
+pub const SERIES: Map<(u64, u64), Empty> = Map::new("series");
+/// Polls waiting on a parent to pass, by the parent's id.
+pub const DEPENDENT_POLLS: Map<(u64, u64), Empty> = Map::new("dependent_polls");
+pub const BALLOTS: Map<(u64, &Addr), Ballot> = Map::new("ballots");
+pub const ENDORSEMENTS: Map<(u64, &Addr), Empty> = Map::new("endorsements");
+pub const COMMENTS: Map<(u64, u64), Comment> = Map::new("comments");
+/// Last comment id handed out on each poll.
+pub const COMMENT_COUNTS: Map<u64, u64> = Map::new("comment_counts");
+pub const TEMPLATE_COUNT: Item<u64> = Item::new("template_count");
+pub const TEMPLATES: Map<u64, PollTemplate> = Map::new("templates");
+pub const CAMPAIGN_COUNT: Item<u64> = Item::new("campaign_count");
+pub const CAMPAIGNS: Map<u64, Campaign> = Map::new("campaigns");
+/// Campaign each poll was added to.
+pub const POLL_CAMPAIGNS: Map<u64, u64> = Map::new("poll_campaigns");
+pub const ROUND_COUNT: Item<u64> = Item::new("round_count");
+pub const ROUNDS: Map<u64, Round> = Map::new("rounds");
+/// Total each address contributed to a project, by round and project index.
+pub const CONTRIBUTIONS: Map<(u64, u32, &Addr), Uint128> = Map::new("contributions");
+pub const SURVEY_COUNT: Item<u64> = Item::new("survey_count");
+pub const SURVEYS: Map<u64, Survey> = Map::new("surveys");
+/// Option index picked for each question of a survey, in question order.
+pub const SURVEY_ANSWERS: Map<(u64, &Addr), Vec<u32>> = Map::new("survey_answers");
+pub const ESCROWS: Map<(u64, &Addr), Uint128> = Map::new("escrows");
+pub const COMMITMENTS: Map<(u64, &Addr), Commitment> = Map::new("commitments");
+pub const CREDITS: Map<(u64, &Addr), CreditAccount> = Map::new("credits");
+pub const STAKES: Map<(u64, &Addr), Stake> = Map::new("stakes");
+/// Option each resolved prediction market settled on.
+pub const OUTCOMES: Map<u64, u32> = Map::new("outcomes");
+pub const DEPOSITS: Map<u64, PollDeposit> = Map::new("deposits");
+pub const BOUNTIES: Map<u64, Bounty> = Map::new("bounties");
+pub const RAFFLES: Map<u64, Raffle> = Map::new("raffles");
+pub const REWARD_POOLS: Map<u64, RewardPool> = Map::new("reward_pools");
+/// Rewards each voter claimed from a poll's pool.
+pub const REWARD_CLAIMS: Map<(u64, &Addr), Uint128> = Map::new("reward_claims");
+/// What each funder put into a poll's pool.
+pub const REWARD_FUNDERS: Map<(u64, &Addr), Uint128> = Map::new("reward_funders");
+pub const INCENTIVE_COUNT: Item<u64> = Item::new("incentive_count");
+pub const INCENTIVES: Map<u64, Incentive> = Map::new("incentives");
+/// Incentives by the tag they target.
+pub const TAG_INCENTIVES: Map<(&str, u64), Empty> = Map::new("tag_incentives");
+/// Incentives by each poll they target.
+pub const POLL_INCENTIVES: Map<(u64, u64), Empty> = Map::new("poll_incentives");
+/// Cw20 tokens allotted to each voter by incentives and not claimed yet.
+pub const INCENTIVE_ACCRUALS: Map<&Addr, Uint128> = Map::new("incentive_accruals");
+/// Creation fees collected so far, by denom.
+pub const FEES: Map<&str, Uint128> = Map::new("fees");
+pub const VOTING_POWER: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "voting_power",
+    "voting_power__checkpoints",
+    "voting_power__changelog",
+    Strategy::EveryBlock,
+);
+/// Sum of `VOTING_POWER`.
+pub const TOTAL_POWER: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_power",
+    "total_power__checkpoints",
+    "total_power__changelog",
+    Strategy::EveryBlock,
+);
+/// Weights pushed by the cw4 group's `MemberChangedHook`, consulted before
+/// querying the group.
+pub const GROUP_WEIGHTS: SnapshotMap<&Addr, u64> = SnapshotMap::new(
+    "group_weights",
+    "group_weights__checkpoints",
+    "group_weights__changelog",
+    Strategy::EveryBlock,
+);
@@ -0,0 +1,193 @@
+//! Participation streaks: how many polls in a row, in the order they closed,
+//! an address voted on.
+//!
+//! Closes are numbered as they happen and only the voters of the poll closing
+//! are updated. A streak whose last close is not the latest one is broken, so
+//! addresses that did not vote never need to be visited.
+
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Decimal, Deps, Env, Order, StdResult, Storage, Uint128,
+};
+
+use crate::error::ContractError;
+use crate::msg::GetStreakResponse;
+use crate::state::{Config, StreakBonus, BALLOTS, CLOSE_COUNT, STREAKS};
+
+/// Extends the streaks of everyone who voted on the poll closing.
+pub(crate) fn record_close(storage: &mut dyn Storage, poll_id: u64) -> StdResult<()> {
+    let close = CLOSE_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    CLOSE_COUNT.save(storage, &close)?;
+    let voters = BALLOTS
+        .prefix(poll_id)
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for voter in voters {
+        let mut streak = STREAKS.may_load(storage, &voter)?.unwrap_or_default();
+        streak.current = if streak.last_close + 1 == close {
+            streak.current + 1
+        } else {
+            1
+        };
+        streak.longest = streak.longest.max(streak.current);
+        streak.last_close = close;
+        STREAKS.save(storage, &voter, &streak)?;
+    }
+    Ok(())
+}
+
+/// The streak `address` is on, zero once a poll closed without its vote.
+fn current_streak(storage: &dyn Storage, address: &Addr) -> StdResult<u64> {
+    let streak = STREAKS.may_load(storage, address)?.unwrap_or_default();
+    let close = CLOSE_COUNT.may_load(storage)?.unwrap_or_default();
+    Ok(if streak.last_close == close {
+        streak.current
+    } else {
+        0
+    })
+}
+
+/// Scales `weight` up for voters on a long enough streak.
+pub(crate) fn apply_streak_bonus(
+    storage: &dyn Storage,
+    config: &Config,
+    voter: &Addr,
+    weight: Uint128,
+) -> StdResult<Uint128> {
+    let bonus = match &config.streak_bonus {
+        Some(bonus) => bonus,
+        None => return Ok(weight),
+    };
+    let streak = current_streak(storage, voter)?;
+    if streak < bonus.min_streak {
+        return Ok(weight);
+    }
+    let multiplier = Decimal::from_atomics(streak, 0)
+        .ok()
+        .and_then(|streak| bonus.per_poll.checked_mul(streak).ok())
+        .and_then(|bonus| Decimal::one().checked_add(bonus).ok())
+        .unwrap_or(bonus.max_multiplier)
+        .min(bonus.max_multiplier);
+    Ok(weight * multiplier)
+}
+
+pub(crate) fn validate_streak_bonus(bonus: Option<&StreakBonus>) -> Result<(), ContractError> {
+    match bonus {
+        Some(bonus) if bonus.max_multiplier < Decimal::one() => {
+            Err(ContractError::InvalidStreakBonus {})
+        }
+        _ => Ok(()),
+    }
+}
+
+pub fn query_get_streak(deps: Deps, _env: Env, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let longest = STREAKS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default()
+        .longest;
+    to_binary(&GetStreakResponse {
+        current: current_streak(deps.storage, &address)?,
+        longest,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::from_binary;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cw20::Cw20ReceiveMsg;
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{Choice, CreatePollMsg, ExecuteMsg, InstantiateMsg, QueryMsg, ReceiveMsg};
+    use crate::state::polls;
+
+    use super::*;
+
+    #[test]
+    fn test_streak() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            cw20_token: Some("token".to_string()),
+            streak_bonus: Some(StreakBonus {
+                min_streak: 2,
+                per_poll: Decimal::percent(10),
+                max_multiplier: Decimal::percent(125),
+            }),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let vote = |voter: &str, poll_id: u64| {
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: voter.to_string(),
+                amount: Uint128::new(100),
+                msg: to_binary(&ReceiveMsg::Vote {
+                    poll_id,
+                    choice: Choice::Yes,
+                })
+                .unwrap(),
+            })
+        };
+        let token = mock_info("token", &[]);
+        let questions = [
+            "Do you love spark IBC",
+            "Do you love cosmwasm",
+            "Do you love juno",
+            "Do you love osmosis",
+        ];
+        for (poll_id, question) in (1..).zip(questions) {
+            let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+                question: question.to_string(),
+                ..Default::default()
+            });
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                token.clone(),
+                vote("addr2", poll_id),
+            )
+            .unwrap();
+            if poll_id != 3 {
+                execute(
+                    deps.as_mut(),
+                    env.clone(),
+                    token.clone(),
+                    vote("addr3", poll_id),
+                )
+                .unwrap();
+            }
+            let msg = ExecuteMsg::ClosePoll { poll_id };
+            execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        }
+        // addr2 voted on a streak of 2 and then 3, capped at 25% more
+        let weights: Vec<_> = (1..=4)
+            .map(|poll_id| polls().load(&deps.storage, poll_id).unwrap().options[0].votes)
+            .collect();
+        assert_eq!(weights, [200, 200, 120, 225].map(Uint128::new).to_vec());
+
+        let streak = |address: &str| -> GetStreakResponse {
+            let msg = QueryMsg::GetStreak {
+                address: address.to_string(),
+            };
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap()
+        };
+        assert_eq!(
+            streak("addr2"),
+            GetStreakResponse {
+                current: 4,
+                longest: 4,
+            }
+        );
+        assert_eq!(
+            streak("addr3"),
+            GetStreakResponse {
+                current: 1,
+                longest: 2,
+            }
+        );
+    }
+}
@@ -0,0 +1,229 @@
+//! Surveys group several questions under one id and are answered in a single
+//! `SubmitSurvey`. Every respondent counts once, whatever the voting power
+//! configured for polls.
+
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Env, Event, MessageInfo, Response, StdResult, Uint128,
+};
+use cw_utils::{nonpayable, Expiration};
+
+use crate::contract::{ensure_not_blocked, normalize_question, validate_options};
+use crate::error::ContractError;
+use crate::msg::{GetSurveyAnswersResponse, GetSurveyResponse, SurveyQuestionMsg};
+use crate::state::{
+    PollOption, Survey, SurveyQuestion, CONFIG, CREATORS, SURVEYS, SURVEY_ANSWERS, SURVEY_COUNT,
+};
+
+const MAX_SURVEY_QUESTIONS: usize = 10;
+
+pub fn execute_create_survey(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    title: String,
+    questions: Vec<SurveyQuestionMsg>,
+    expiration: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ensure_not_blocked(deps.storage, &info.sender)?;
+    let config = CONFIG.load(deps.storage)?;
+    if config.restrict_creation && !CREATORS.has(deps.storage, &info.sender) {
+        return Err(ContractError::CreationNotAllowed {});
+    }
+    let title = normalize_question(&config, &title)?;
+    if questions.is_empty() || questions.len() > MAX_SURVEY_QUESTIONS {
+        return Err(ContractError::InvalidQuestionCount {
+            max: MAX_SURVEY_QUESTIONS,
+        });
+    }
+    let questions = questions
+        .into_iter()
+        .map(|SurveyQuestionMsg { question, options }| {
+            validate_options(&options)?;
+            Ok(SurveyQuestion {
+                question: normalize_question(&config, &question)?,
+                options: options
+                    .into_iter()
+                    .map(|label| PollOption {
+                        label,
+                        votes: Uint128::zero(),
+                        description: None,
+                        image: None,
+                    })
+                    .collect(),
+            })
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+    let expiration = expiration.unwrap_or(Expiration::Never {});
+    if expiration.is_expired(&env.block) {
+        return Err(ContractError::InvalidExpiration {});
+    }
+
+    let id = SURVEY_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    SURVEY_COUNT.save(deps.storage, &id)?;
+    let survey = Survey {
+        id,
+        creator: info.sender,
+        title,
+        questions,
+        expiration,
+        respondents: 0,
+    };
+    SURVEYS.save(deps.storage, id, &survey)?;
+
+    let event = Event::new("survey_created")
+        .add_attribute("survey_id", id.to_string())
+        .add_attribute("title", survey.title)
+        .add_attribute("creator", survey.creator);
+    Ok(Response::new()
+        .add_attribute("action", "create_survey")
+        .add_attribute("survey_id", id.to_string())
+        .add_event(event))
+}
+
+/// Records one option index per question, in question order.
+pub fn execute_submit_survey(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    survey_id: u64,
+    answers: Vec<u32>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    ensure_not_blocked(deps.storage, &info.sender)?;
+    let mut survey = SURVEYS
+        .may_load(deps.storage, survey_id)?
+        .ok_or(ContractError::SurveyNotFound { survey_id })?;
+    if survey.expiration.is_expired(&env.block) {
+        return Err(ContractError::SurveyClosed { survey_id });
+    }
+    if SURVEY_ANSWERS.has(deps.storage, (survey_id, &info.sender)) {
+        return Err(ContractError::AlreadyAnswered { survey_id });
+    }
+    if answers.len() != survey.questions.len() {
+        return Err(ContractError::AnswerCountMismatch {
+            expected: survey.questions.len(),
+        });
+    }
+    for (question, answer) in survey.questions.iter_mut().zip(&answers) {
+        let option = question
+            .options
+            .get_mut(*answer as usize)
+            .ok_or(ContractError::InvalidChoice {})?;
+        option.votes += Uint128::one();
+    }
+    survey.respondents += 1;
+    SURVEYS.save(deps.storage, survey_id, &survey)?;
+    SURVEY_ANSWERS.save(deps.storage, (survey_id, &info.sender), &answers)?;
+
+    let event = Event::new("survey_submitted")
+        .add_attribute("survey_id", survey_id.to_string())
+        .add_attribute("respondent", info.sender);
+    Ok(Response::new()
+        .add_attribute("action", "submit_survey")
+        .add_attribute("survey_id", survey_id.to_string())
+        .add_event(event))
+}
+
+/// The survey with the answer counts of every question.
+pub fn query_get_survey(deps: Deps, env: Env, survey_id: u64) -> StdResult<Binary> {
+    let survey = SURVEYS.load(deps.storage, survey_id)?;
+    let is_open = !survey.expiration.is_expired(&env.block);
+    to_binary(&GetSurveyResponse { survey, is_open })
+}
+
+pub fn query_get_survey_answers(
+    deps: Deps,
+    _env: Env,
+    survey_id: u64,
+    respondent: String,
+) -> StdResult<Binary> {
+    let respondent = deps.api.addr_validate(&respondent)?;
+    let answers = SURVEY_ANSWERS.may_load(deps.storage, (survey_id, &respondent))?;
+    to_binary(&GetSurveyAnswersResponse { answers })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::from_binary;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+
+    use super::*;
+
+    #[test]
+    fn test_survey() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let question = |question: &str, options: &[&str]| SurveyQuestionMsg {
+            question: question.to_string(),
+            options: options.iter().map(|option| option.to_string()).collect(),
+        };
+        let msg = ExecuteMsg::CreateSurvey {
+            title: "Community survey".to_string(),
+            questions: vec![
+                question("Favourite chain", &["juno", "osmosis", "neutron"]),
+                question("Do you stake", &["yes", "no"]),
+            ],
+            expiration: None,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::SubmitSurvey {
+            survey_id: 1,
+            answers: vec![1],
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::AnswerCountMismatch { expected: 2 }
+        ));
+        let msg = ExecuteMsg::SubmitSurvey {
+            survey_id: 1,
+            answers: vec![1, 2],
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidChoice {}));
+
+        for (respondent, answers) in [("addr1", vec![1, 0]), ("addr2", vec![1, 1])] {
+            let msg = ExecuteMsg::SubmitSurvey {
+                survey_id: 1,
+                answers,
+            };
+            execute(deps.as_mut(), env.clone(), mock_info(respondent, &[]), msg).unwrap();
+        }
+        let msg = ExecuteMsg::SubmitSurvey {
+            survey_id: 1,
+            answers: vec![0, 0],
+        };
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::AlreadyAnswered { survey_id: 1 }
+        ));
+
+        let msg = QueryMsg::GetSurvey { survey_id: 1 };
+        let resp: GetSurveyResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(resp.survey.respondents, 2);
+        assert_eq!(resp.survey.questions[0].options[1].votes, Uint128::new(2));
+        assert_eq!(resp.survey.questions[1].options[0].votes, Uint128::new(1));
+
+        let msg = QueryMsg::GetSurveyAnswers {
+            survey_id: 1,
+            respondent: "addr2".to_string(),
+        };
+        let resp: GetSurveyAnswersResponse =
+            from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+        assert_eq!(resp.answers, Some(vec![1, 1]));
+    }
+}
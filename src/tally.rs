@@ -0,0 +1,527 @@
+use cosmwasm_std::{BlockInfo, Decimal, OverflowError, OverflowOperation, Uint128};
+use cw_utils::{Expiration, Scheduled};
+
+use crate::error::ContractError;
+use crate::msg::{Allocation, Choice};
+use crate::state::{Poll, PollKind, PollStatus};
+
+pub fn option_index(poll: &Poll, choice: &Choice) -> Option<usize> {
+    match choice {
+        Choice::Yes => poll.options.iter().position(|option| option.label == "yes"),
+        Choice::No => poll.options.iter().position(|option| option.label == "no"),
+        Choice::Option(index) => Some(*index as usize).filter(|i| *i < poll.options.len()),
+        Choice::Abstain
+        | Choice::NoWithVeto
+        | Choice::Ranking(_)
+        | Choice::Allocation(_)
+        | Choice::Approval(_) => None,
+    }
+}
+
+/// Validates `choice` against the poll kind and adds `weight` to the running tally.
+/// Ranked ballots count towards their first preference until the poll is closed,
+/// quadratic ballots add the allocated votes regardless of weight, approval
+/// ballots add the full weight to every approved option and petition signatures
+/// add one, or the weight for weighted petitions.
+pub fn add_vote(poll: &mut Poll, choice: &Choice, weight: Uint128) -> Result<(), ContractError> {
+    apply_vote(poll, choice, weight, Uint128::checked_add)
+}
+
+/// Reverts a ballot previously counted with [`add_vote`].
+pub fn remove_vote(poll: &mut Poll, choice: &Choice, weight: Uint128) -> Result<(), ContractError> {
+    apply_vote(poll, choice, weight, Uint128::checked_sub)
+}
+
+fn apply_vote(
+    poll: &mut Poll,
+    choice: &Choice,
+    weight: Uint128,
+    apply: fn(Uint128, Uint128) -> Result<Uint128, OverflowError>,
+) -> Result<(), ContractError> {
+    let apply = |count: &mut Uint128, amount: Uint128| -> Result<(), ContractError> {
+        *count = apply(*count, amount)?;
+        Ok(())
+    };
+    let index = match (&poll.kind, choice) {
+        (PollKind::SingleChoice, Choice::Abstain) => {
+            apply(&mut poll.abstain_votes, weight)?;
+            return Ok(());
+        }
+        (PollKind::SingleChoice, Choice::NoWithVeto) if is_binary(poll) => {
+            apply(&mut poll.veto_votes, weight)?;
+            return Ok(());
+        }
+        (PollKind::SingleChoice | PollKind::Conviction { .. }, _) => option_index(poll, choice),
+        (PollKind::RankedChoice, Choice::Ranking(ranking)) => {
+            validate_ranking(poll, ranking)?;
+            Some(ranking[0] as usize)
+        }
+        (PollKind::Approval, Choice::Approval(approved)) => {
+            validate_approval(poll, approved)?;
+            for index in approved {
+                apply(&mut poll.options[*index as usize].votes, weight)?;
+            }
+            return Ok(());
+        }
+        (PollKind::Petition { weighted, .. }, Choice::Yes) => {
+            let weight = if *weighted { weight } else { Uint128::one() };
+            apply(&mut poll.options[0].votes, weight)?;
+            return Ok(());
+        }
+        (PollKind::Quadratic { .. }, Choice::Allocation(allocations)) => {
+            validate_allocations(poll, allocations)?;
+            for allocation in allocations {
+                apply(
+                    &mut poll.options[allocation.option as usize].votes,
+                    Uint128::from(allocation.votes),
+                )?;
+            }
+            return Ok(());
+        }
+        _ => None,
+    }
+    .ok_or(ContractError::InvalidChoice {})?;
+
+    apply(&mut poll.options[index].votes, weight)
+}
+
+/// Whether `choice` supports the option at `index`: picks it, ranks it first,
+/// approves it or allocates votes to it.
+pub fn backs_option(poll: &Poll, choice: &Choice, index: usize) -> bool {
+    match choice {
+        Choice::Ranking(ranking) => ranking.first() == Some(&(index as u32)),
+        Choice::Approval(approved) => approved.contains(&(index as u32)),
+        Choice::Allocation(allocations) => allocations
+            .iter()
+            .any(|allocation| allocation.option as usize == index),
+        _ => option_index(poll, choice) == Some(index),
+    }
+}
+
+/// Credits spent by a quadratic ballot: each option costs the square of its votes.
+pub fn quadratic_cost(allocations: &[Allocation]) -> Result<u64, ContractError> {
+    allocations
+        .iter()
+        .try_fold(0u64, |cost, allocation| {
+            let square = allocation
+                .votes
+                .checked_mul(allocation.votes)
+                .ok_or_else(|| {
+                    OverflowError::new(OverflowOperation::Mul, allocation.votes, allocation.votes)
+                })?;
+            cost.checked_add(square)
+                .ok_or_else(|| OverflowError::new(OverflowOperation::Add, cost, square))
+        })
+        .map_err(ContractError::from)
+}
+
+fn validate_allocations(poll: &Poll, allocations: &[Allocation]) -> Result<(), ContractError> {
+    let valid = !allocations.is_empty()
+        && allocations.iter().enumerate().all(|(i, allocation)| {
+            (allocation.option as usize) < poll.options.len()
+                && allocation.votes > 0
+                && !allocations[..i]
+                    .iter()
+                    .any(|other| other.option == allocation.option)
+        });
+    if !valid {
+        return Err(ContractError::InvalidChoice {});
+    }
+    Ok(())
+}
+
+fn validate_ranking(poll: &Poll, ranking: &[u32]) -> Result<(), ContractError> {
+    if !distinct_options(poll, ranking) {
+        return Err(ContractError::InvalidChoice {});
+    }
+    Ok(())
+}
+
+fn validate_approval(poll: &Poll, approved: &[u32]) -> Result<(), ContractError> {
+    if !distinct_options(poll, approved) {
+        return Err(ContractError::InvalidChoice {});
+    }
+    Ok(())
+}
+
+fn distinct_options(poll: &Poll, indices: &[u32]) -> bool {
+    !indices.is_empty()
+        && indices.iter().enumerate().all(|(i, index)| {
+            (*index as usize) < poll.options.len() && !indices[..i].contains(index)
+        })
+}
+
+/// Everything counted on the poll, including abstentions and vetoes.
+pub fn total_votes(poll: &Poll) -> Result<Uint128, OverflowError> {
+    poll.options
+        .iter()
+        .map(|option| option.votes)
+        .chain([poll.abstain_votes, poll.veto_votes])
+        .try_fold(Uint128::zero(), |total, votes| total.checked_add(votes))
+}
+
+/// Whether the poll is a plain yes/no question that can pass or be rejected.
+pub fn is_binary(poll: &Poll) -> bool {
+    poll.kind == PollKind::SingleChoice
+        && poll.options.len() == 2
+        && option_index(poll, &Choice::Yes).is_some()
+        && option_index(poll, &Choice::No).is_some()
+}
+
+/// Scales `weight` by the poll's weight decay at `block`: the factor moves
+/// linearly from the initial one when voting opens to the closing one at
+/// expiration.
+pub fn decayed_weight(
+    poll: &Poll,
+    block: &BlockInfo,
+    weight: Uint128,
+) -> Result<Uint128, ContractError> {
+    let decay = match &poll.weight_decay {
+        Some(decay) => decay,
+        None => return Ok(weight),
+    };
+    let (elapsed, window) = match (decay.opens, poll.expiration) {
+        (Scheduled::AtHeight(opens), Expiration::AtHeight(end)) => (
+            block.height.saturating_sub(opens),
+            end.saturating_sub(opens),
+        ),
+        (Scheduled::AtTime(opens), Expiration::AtTime(end)) => (
+            block.time.nanos().saturating_sub(opens.nanos()),
+            end.nanos().saturating_sub(opens.nanos()),
+        ),
+        _ => return Err(ContractError::InvalidWeightDecay {}),
+    };
+    let progress = if window == 0 {
+        Decimal::one()
+    } else {
+        Decimal::from_ratio(elapsed.min(window), window)
+    };
+    let factor = if decay.closing >= decay.initial {
+        decay.initial + (decay.closing - decay.initial) * progress
+    } else {
+        decay.initial - (decay.initial - decay.closing) * progress
+    };
+    let scaled = weight
+        .checked_multiply_ratio(factor.atomics(), Decimal::one().atomics())
+        .map_err(|_| OverflowError::new(OverflowOperation::Mul, weight, factor))?;
+    Ok(scaled)
+}
+
+/// Whether a petition has collected its target of signatures.
+pub fn petition_reached(poll: &Poll) -> bool {
+    match poll.kind {
+        PollKind::Petition { target, .. } => poll.options[0].votes >= target,
+        _ => false,
+    }
+}
+
+/// Whether enough weight was cast on the poll to count its result.
+pub fn quorum_reached(poll: &Poll) -> Result<bool, OverflowError> {
+    if !turnout_reached(poll)? {
+        return Ok(false);
+    }
+    match poll.quorum {
+        Some(quorum) => Ok(total_votes(poll)? >= quorum),
+        None => Ok(true),
+    }
+}
+
+/// Whether the share of the total power at creation that voted, abstentions
+/// included, reached the poll's turnout.
+pub fn turnout_reached(poll: &Poll) -> Result<bool, OverflowError> {
+    match (poll.turnout, poll.total_power) {
+        (Some(turnout), Some(total_power)) => Ok(total_votes(poll)? >= total_power * turnout),
+        _ => Ok(true),
+    }
+}
+
+/// Outcome of a yes/no poll or petition, or `None` for polls without
+/// pass/reject semantics. Petitions pass once their signatures reach the target.
+/// Polls short of their quorum are rejected. Vetoes count against the poll, and
+/// reaching the veto threshold (as a share of all votes, abstentions included)
+/// rejects it outright. Otherwise yes votes must make up more than the pass
+/// threshold of the yes, no and veto votes, or all of them.
+pub fn binary_outcome(poll: &Poll) -> Result<Option<PollStatus>, ContractError> {
+    if matches!(poll.kind, PollKind::Petition { .. }) {
+        return Ok(Some(if petition_reached(poll) {
+            PollStatus::Passed
+        } else {
+            PollStatus::Rejected
+        }));
+    }
+    if !is_binary(poll) {
+        return Ok(None);
+    }
+    if !quorum_reached(poll)? {
+        return Ok(Some(PollStatus::Rejected));
+    }
+    let votes = |choice| {
+        option_index(poll, &choice).map_or(Uint128::zero(), |index| poll.options[index].votes)
+    };
+    let (yes, no) = (votes(Choice::Yes), votes(Choice::No));
+
+    if vetoed(poll)? {
+        return Ok(Some(PollStatus::Rejected));
+    }
+    if let Some(winner) = poll.tie_winner {
+        return Ok(Some(
+            if option_index(poll, &Choice::Yes) == Some(winner as usize) {
+                PollStatus::Passed
+            } else {
+                PollStatus::Rejected
+            },
+        ));
+    }
+
+    let against = no.checked_add(poll.veto_votes)?;
+    let passed = match poll.threshold {
+        Some(threshold) => {
+            let cast = yes.checked_add(against)?;
+            !cast.is_zero() && clears(Decimal::from_ratio(yes, cast), threshold)
+        }
+        None => yes > against,
+    };
+    if passed {
+        Ok(Some(PollStatus::Passed))
+    } else {
+        Ok(Some(PollStatus::Rejected))
+    }
+}
+
+/// Whether `share` is more than `threshold`, or all of the votes so that
+/// unanimous thresholds can be met.
+fn clears(share: Decimal, threshold: Decimal) -> bool {
+    share > threshold || share == Decimal::one()
+}
+
+/// Whether the veto votes reached the poll's veto threshold, as a share of all
+/// votes.
+fn vetoed(poll: &Poll) -> Result<bool, OverflowError> {
+    match poll.veto_threshold {
+        Some(threshold) => {
+            let total = total_votes(poll)?;
+            Ok(!total.is_zero() && Decimal::from_ratio(poll.veto_votes, total) >= threshold)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Indexes of the options tied for the lead of a poll that reached its quorum,
+/// empty when there is no tie. A yes/no poll is tied when its yes votes exactly
+/// meet its pass threshold, or equal the votes against it without one.
+/// Ranked-choice polls and petitions are never tied.
+pub fn tied_options(poll: &Poll) -> Result<Vec<u32>, OverflowError> {
+    if !quorum_reached(poll)? {
+        return Ok(vec![]);
+    }
+    if is_binary(poll) {
+        let (yes_index, no_index) = match (
+            option_index(poll, &Choice::Yes),
+            option_index(poll, &Choice::No),
+        ) {
+            (Some(yes), Some(no)) => (yes, no),
+            _ => return Ok(vec![]),
+        };
+        if vetoed(poll)? {
+            return Ok(vec![]);
+        }
+        let yes = poll.options[yes_index].votes;
+        let against = poll.options[no_index].votes.checked_add(poll.veto_votes)?;
+        let cast = yes.checked_add(against)?;
+        let tied = match poll.threshold {
+            // all the votes clear a unanimous threshold rather than tie it
+            Some(threshold) => {
+                !cast.is_zero()
+                    && threshold < Decimal::one()
+                    && Decimal::from_ratio(yes, cast) == threshold
+            }
+            None => !yes.is_zero() && yes == against,
+        };
+        return Ok(if tied {
+            vec![yes_index as u32, no_index as u32]
+        } else {
+            vec![]
+        });
+    }
+    if matches!(
+        poll.kind,
+        PollKind::RankedChoice | PollKind::Petition { .. }
+    ) {
+        return Ok(vec![]);
+    }
+    let max = poll
+        .options
+        .iter()
+        .map(|option| option.votes)
+        .max()
+        .unwrap_or_default();
+    let leaders: Vec<_> = (0..)
+        .zip(&poll.options)
+        .filter(|(_, option)| option.votes == max)
+        .map(|(index, _)| index)
+        .collect();
+    Ok(if max.is_zero() || leaders.len() < 2 {
+        vec![]
+    } else {
+        leaders
+    })
+}
+
+/// The two leading options of a poll with a runoff period whose leader did
+/// not get more than its pass threshold, a majority when unset, of the option
+/// votes. Options tied for second place go by their order.
+pub fn runoff_options(poll: &Poll) -> Result<Option<(usize, usize)>, OverflowError> {
+    if poll.runoff_period.is_none() || poll.options.len() < 2 || !quorum_reached(poll)? {
+        return Ok(None);
+    }
+    let total = poll
+        .options
+        .iter()
+        .try_fold(Uint128::zero(), |total, option| {
+            total.checked_add(option.votes)
+        })?;
+    let mut ranked: Vec<usize> = (0..poll.options.len()).collect();
+    ranked.sort_by(|a, b| poll.options[*b].votes.cmp(&poll.options[*a].votes));
+    let (first, second) = (ranked[0], ranked[1]);
+    let threshold = poll.threshold.unwrap_or(Decimal::percent(50));
+    if total.is_zero()
+        || clears(
+            Decimal::from_ratio(poll.options[first].votes, total),
+            threshold,
+        )
+    {
+        return Ok(None);
+    }
+    Ok(Some((first, second)))
+}
+
+/// Returns the option with the most votes, or `None` when nobody voted or the
+/// lead is tied.
+pub fn leading_option(poll: &Poll) -> Option<usize> {
+    let max = poll.options.iter().map(|option| option.votes).max()?;
+    let mut leaders = poll
+        .options
+        .iter()
+        .enumerate()
+        .filter(|(_, option)| option.votes == max);
+    match (leaders.next(), leaders.next()) {
+        (Some((index, _)), None) if !max.is_zero() => Some(index),
+        _ => None,
+    }
+}
+
+/// Runs an instant-runoff count over weighted rankings. Each round every ballot
+/// counts for its highest-ranked remaining option; an option with a strict
+/// majority wins, otherwise the options with the fewest votes are eliminated.
+/// Returns `None` if no ballots remain or the final options are tied.
+pub fn instant_runoff(
+    num_options: usize,
+    ballots: &[(Vec<u32>, Uint128)],
+) -> Result<Option<usize>, OverflowError> {
+    let mut eliminated = vec![false; num_options];
+    loop {
+        let mut counts = vec![Uint128::zero(); num_options];
+        for (ranking, weight) in ballots {
+            if let Some(index) = ranking
+                .iter()
+                .map(|index| *index as usize)
+                .find(|index| !eliminated[*index])
+            {
+                counts[index] = counts[index].checked_add(*weight)?;
+            }
+        }
+
+        let total = counts
+            .iter()
+            .try_fold(Uint128::zero(), |total, count| total.checked_add(*count))?;
+        if total.is_zero() {
+            return Ok(None);
+        }
+        let remaining: Vec<usize> = (0..num_options).filter(|i| !eliminated[*i]).collect();
+        if let Some(winner) = remaining.iter().find(|i| counts[**i] > total - counts[**i]) {
+            return Ok(Some(*winner));
+        }
+
+        let min = match remaining.iter().map(|i| counts[*i]).min() {
+            Some(min) => min,
+            None => return Ok(None),
+        };
+        let losers: Vec<usize> = remaining
+            .into_iter()
+            .filter(|i| counts[*i] == min)
+            .collect();
+        if losers.len() == eliminated.iter().filter(|e| !**e).count() {
+            return Ok(None);
+        }
+        for index in losers {
+            eliminated[index] = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ballots(rankings: &[(&[u32], u128)]) -> Vec<(Vec<u32>, Uint128)> {
+        rankings
+            .iter()
+            .map(|(ranking, weight)| (ranking.to_vec(), Uint128::new(*weight)))
+            .collect()
+    }
+
+    #[test]
+    fn test_quadratic_cost() {
+        let allocations = [
+            Allocation {
+                option: 0,
+                votes: 3,
+            },
+            Allocation {
+                option: 1,
+                votes: 1,
+            },
+        ];
+        assert_eq!(quadratic_cost(&allocations).unwrap(), 10);
+
+        let allocations = [Allocation {
+            option: 0,
+            votes: u64::MAX,
+        }];
+        assert!(matches!(
+            quadratic_cost(&allocations),
+            Err(ContractError::Overflow(_))
+        ));
+    }
+
+    #[test]
+    fn test_instant_runoff_majority_in_first_round() {
+        let ballots = ballots(&[(&[0, 1], 3), (&[1, 0], 1), (&[2], 1)]);
+        assert_eq!(instant_runoff(3, &ballots), Ok(Some(0)));
+    }
+
+    #[test]
+    fn test_instant_runoff_transfers_eliminated_votes() {
+        // option 2 is eliminated first and its ballots move to option 1
+        let ballots = ballots(&[(&[0], 4), (&[1], 3), (&[2, 1], 2)]);
+        assert_eq!(instant_runoff(3, &ballots), Ok(Some(1)));
+    }
+
+    #[test]
+    fn test_instant_runoff_tie() {
+        let ballots = ballots(&[(&[0], 2), (&[1], 2)]);
+        assert_eq!(instant_runoff(2, &ballots), Ok(None));
+        assert_eq!(instant_runoff(2, &[]), Ok(None));
+    }
+
+    #[test]
+    fn test_instant_runoff_overflow() {
+        // on one option, then across options
+        for rankings in [
+            [(&[0][..], u128::MAX), (&[0], 1)],
+            [(&[0], u128::MAX), (&[1], 1)],
+        ] {
+            assert!(instant_runoff(2, &ballots(&rankings)).is_err());
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! Poll templates: option sets and voting settings registered by the owners,
+//! that creators refer to by id instead of repeating them in every
+//! `CreatePoll`. A template is checked when it is registered, and again like
+//! any other poll when one is created from it.
+
+use cosmwasm_std::{
+    to_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+};
+use cw_storage_plus::Bound;
+use cw_utils::nonpayable;
+
+use crate::contract::{
+    create_poll, ensure_role, validate_options, validate_threshold, validate_voting_period,
+    DEFAULT_LIMIT, MAX_LIMIT,
+};
+use crate::error::ContractError;
+use crate::funding::ensure_name;
+use crate::msg::{CreatePollMsg, ListTemplatesResponse, TemplateMsg};
+use crate::state::{PollKind, PollTemplate, Role, CONFIG, TEMPLATES, TEMPLATE_COUNT};
+
+pub fn execute_register_template(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: TemplateMsg,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+    let TemplateMsg {
+        name,
+        options,
+        kind,
+        voting_period,
+        veto_threshold,
+        quorum,
+        threshold,
+    } = msg;
+    ensure_name(&name)?;
+    match (&kind, &options) {
+        (PollKind::Petition { .. }, Some(_)) => return Err(ContractError::InvalidPetition {}),
+        (_, Some(options)) => validate_options(options)?,
+        (_, None) => {}
+    }
+    validate_voting_period(voting_period)?;
+    if veto_threshold.is_some_and(|threshold| threshold.is_zero() || threshold > Decimal::one()) {
+        return Err(ContractError::InvalidVetoThreshold {});
+    }
+    validate_threshold(threshold)?;
+
+    let id = TEMPLATE_COUNT.may_load(deps.storage)?.unwrap_or_default() + 1;
+    TEMPLATE_COUNT.save(deps.storage, &id)?;
+    let template = PollTemplate {
+        id,
+        name,
+        options,
+        kind,
+        voting_period,
+        veto_threshold,
+        quorum,
+        threshold,
+    };
+    TEMPLATES.save(deps.storage, id, &template)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "register_template")
+        .add_attribute("template_id", id.to_string())
+        .add_attribute("name", template.name))
+}
+
+/// Polls already created from the template are left as they are.
+pub fn execute_remove_template(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    template_id: u64,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let config = CONFIG.load(deps.storage)?;
+    ensure_role(deps.storage, &config, &info.sender, &[Role::Owner])?;
+    load_template(deps.as_ref(), template_id)?;
+    TEMPLATES.remove(deps.storage, template_id);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_template")
+        .add_attribute("template_id", template_id.to_string()))
+}
+
+/// Creates a poll as `CreatePoll` would, with the settings of the template.
+pub fn execute_create_poll_from_template(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    template_id: u64,
+    question: String,
+    description: Option<String>,
+    tags: Vec<String>,
+) -> Result<Response, ContractError> {
+    let template = load_template(deps.as_ref(), template_id)?;
+    let msg = CreatePollMsg {
+        question,
+        description,
+        tags,
+        options: template.options,
+        kind: template.kind,
+        expiration: template
+            .voting_period
+            .map(|period| period.after(&env.block)),
+        veto_threshold: template.veto_threshold,
+        quorum: template.quorum,
+        threshold: template.threshold,
+        ..Default::default()
+    };
+    Ok(
+        create_poll(deps, &env, &info.sender, Some(&info.funds), msg)?
+            .add_attribute("template_id", template_id.to_string()),
+    )
+}
+
+pub fn query_list_templates(
+    deps: Deps,
+    _env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let templates = TEMPLATES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, template)| template))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    to_binary(&ListTemplatesResponse { templates })
+}
+
+fn load_template(deps: Deps, template_id: u64) -> Result<PollTemplate, ContractError> {
+    TEMPLATES
+        .may_load(deps.storage, template_id)?
+        .ok_or(ContractError::TemplateNotFound { template_id })
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, Uint128};
+    use cw_utils::{Duration, Expiration};
+
+    use crate::contract::{execute, instantiate, query};
+    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+    use crate::state::polls;
+
+    use super::*;
+
+    #[test]
+    fn test_poll_template() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let mut template = TemplateMsg {
+            name: "Chain vote".to_string(),
+            options: Some(vec!["juno".to_string(), "juno".to_string()]),
+            voting_period: Some(Duration::Height(100)),
+            quorum: Some(Uint128::new(10)),
+            threshold: Some(Decimal::percent(60)),
+            ..Default::default()
+        };
+        let msg = ExecuteMsg::RegisterTemplate(template.clone());
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOption { .. }));
+        template.options = Some(vec!["juno".to_string(), "osmosis".to_string()]);
+        let msg = ExecuteMsg::RegisterTemplate(template);
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // anyone can create polls from it
+        let msg = ExecuteMsg::CreatePollFromTemplate {
+            template_id: 1,
+            question: "Which chain do you love".to_string(),
+            description: None,
+            tags: vec![],
+        };
+        let result = execute(deps.as_mut(), env.clone(), mock_info("addr2", &[]), msg).unwrap();
+        assert!(result
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "template_id" && attr.value == "1"));
+        let poll = polls().load(&deps.storage, 1).unwrap();
+        assert_eq!(poll.creator, "addr2");
+        let labels: Vec<_> = poll.options.iter().map(|option| &option.label).collect();
+        assert_eq!(labels, ["juno", "osmosis"]);
+        assert_eq!(
+            poll.expiration,
+            Expiration::AtHeight(env.block.height + 100)
+        );
+        assert_eq!(poll.quorum, Some(Uint128::new(10)));
+        assert_eq!(poll.threshold, Some(Decimal::percent(60)));
+
+        let msg = ExecuteMsg::RemoveTemplate { template_id: 1 };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let msg = QueryMsg::ListTemplates {
+            start_after: None,
+            limit: None,
+        };
+        let resp: ListTemplatesResponse =
+            from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert!(resp.templates.is_empty());
+        let msg = ExecuteMsg::CreatePollFromTemplate {
+            template_id: 1,
+            question: "Which chain do you love most".to_string(),
+            description: None,
+            tags: vec![],
+        };
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::TemplateNotFound { template_id: 1 }
+        ));
+    }
+}
@@ -0,0 +1,258 @@
+//! Tie-breaking: what closing a poll does when its leading options are tied,
+//! see `TieBreak`.
+//!
+//! Extending keeps the poll open once more, a tie after that is not broken. A
+//! Nois draw leaves the poll open but closed to votes until the proxy answers
+//! with `NoisReceive`, which picks the winner among the tied options and
+//! finalizes the poll. The proxy's fee is attached to the `ClosePoll` that
+//! requests the draw, so that it never comes out of coins the contract holds
+//! for others.
+
+use cosmwasm_std::{
+    to_binary, Addr, BlockInfo, Coin, DepsMut, Env, Event, HexBinary, Response, Storage, WasmMsg,
+};
+use cw_utils::{Duration, Expiration};
+use sha2::{Digest, Sha256};
+
+use crate::contract::{finalize_poll, load_poll};
+use crate::error::ContractError;
+use crate::raffle::NoisProxyExecuteMsg;
+use crate::state::{polls, Config, Poll, PollStatus, TieBreak};
+use crate::tally::is_binary;
+
+pub(crate) const TIE_JOB_PREFIX: &str = "tie-";
+
+/// Whether `poll` is waiting on the randomness to break its tie.
+pub(crate) fn awaiting_draw(poll: &Poll) -> bool {
+    poll.tie_break == TieBreak::RandomViaNois
+        && poll.status == PollStatus::Open
+        && !poll.tied.is_empty()
+        && poll.tie_winner.is_none()
+}
+
+pub(crate) fn validate_tie_break(config: &Config, poll: &Poll) -> Result<(), ContractError> {
+    let valid = match poll.tie_break {
+        TieBreak::Reject => true,
+        TieBreak::FavorYes => is_binary(poll),
+        TieBreak::ExtendDeadline { by } => {
+            let same_unit = matches!(
+                (by, poll.expiration),
+                (Duration::Height(_), Expiration::AtHeight(_))
+                    | (Duration::Time(_), Expiration::AtTime(_))
+            );
+            let by_zero = matches!(by, Duration::Height(0) | Duration::Time(0));
+            !by_zero && poll.commit_reveal.is_none() && (same_unit || poll.weight_decay.is_none())
+        }
+        TieBreak::RandomViaNois => {
+            if config.nois_proxy.is_none() {
+                return Err(ContractError::NoisNotConfigured {});
+            }
+            true
+        }
+    };
+    if !valid {
+        return Err(ContractError::InvalidTieBreak {});
+    }
+    Ok(())
+}
+
+/// Keeps a tied poll open for `by` more. The tally is read back from storage
+/// so that a conviction count done for the close is not kept.
+pub(crate) fn extend_deadline(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    poll_id: u64,
+    tied: Vec<u32>,
+    by: Duration,
+) -> Result<Response, ContractError> {
+    let mut poll = load_poll(storage, poll_id)?;
+    poll.tied = tied;
+    poll.expiration = by.after(block);
+    polls().save(storage, poll_id, &poll)?;
+
+    let event = Event::new("poll_extended")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("expiration", poll.expiration.to_string());
+    Ok(Response::new().add_event(event))
+}
+
+/// Asks the Nois proxy for the randomness to pick among the tied options,
+/// forwarding it the `fee` paid by the closer.
+pub(crate) fn request_draw(
+    storage: &mut dyn Storage,
+    config: &Config,
+    mut poll: Poll,
+    tied: Vec<u32>,
+    fee: Vec<Coin>,
+) -> Result<Response, ContractError> {
+    let proxy = config
+        .nois_proxy
+        .clone()
+        .ok_or(ContractError::NoisNotConfigured {})?;
+    poll.tied = tied;
+    polls().save(storage, poll.id, &poll)?;
+
+    let request = WasmMsg::Execute {
+        contract_addr: proxy.into_string(),
+        msg: to_binary(&NoisProxyExecuteMsg::GetNextRandomness {
+            job_id: format!("{}{}", TIE_JOB_PREFIX, poll.id),
+        })?,
+        funds: fee,
+    };
+    let event = Event::new("tie_draw_requested").add_attribute("poll_id", poll.id.to_string());
+    Ok(Response::new().add_message(request).add_event(event))
+}
+
+/// Picks the winner of a tie with the randomness the proxy answered and
+/// finalizes the poll.
+pub(crate) fn receive_draw(
+    deps: DepsMut,
+    env: &Env,
+    proxy: &Addr,
+    poll_id: u64,
+    randomness: &HexBinary,
+) -> Result<Response, ContractError> {
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    if !awaiting_draw(&poll) {
+        return Err(ContractError::UnknownJob {
+            job_id: format!("{}{}", TIE_JOB_PREFIX, poll_id),
+        });
+    }
+    let hash = Sha256::digest(randomness.as_slice());
+    let mut word = [0u8; 8];
+    word.copy_from_slice(&hash[..8]);
+    let winner = poll.tied[(u64::from_be_bytes(word) % poll.tied.len() as u64) as usize];
+    poll.tie_winner = Some(winner);
+
+    let closed = finalize_poll(deps, env, poll, proxy)?;
+    Ok(closed
+        .add_attribute("action", "nois_receive")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("tie_winner", winner.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coin, CosmosMsg};
+
+    use crate::contract::{execute, instantiate};
+    use crate::msg::{Choice, CreatePollMsg, ExecuteMsg, InstantiateMsg, NoisCallback};
+
+    use super::*;
+
+    #[test]
+    fn test_tie_break() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            nois_proxy: Some("nois".to_string()),
+            nois_fee: Some(coin(50, "unois")),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msgs = [
+            CreatePollMsg {
+                question: "Do you love spark IBC".to_string(),
+                tie_break: TieBreak::ExtendDeadline {
+                    by: Duration::Height(10),
+                },
+                ..Default::default()
+            },
+            CreatePollMsg {
+                question: "Which chain do you love".to_string(),
+                options: Some(vec!["juno".to_string(), "osmosis".to_string()]),
+                tie_break: TieBreak::RandomViaNois,
+                ..Default::default()
+            },
+        ];
+        for (poll_id, msg) in (1..).zip(msgs) {
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                info.clone(),
+                ExecuteMsg::CreatePoll(msg),
+            )
+            .unwrap();
+            for (voter, choice) in [("addr2", Choice::Option(0)), ("addr3", Choice::Option(1))] {
+                let msg = ExecuteMsg::Vote {
+                    poll_id,
+                    choice,
+                    proof: None,
+                    memo: None,
+                };
+                execute(deps.as_mut(), env.clone(), mock_info(voter, &[]), msg).unwrap();
+            }
+        }
+        let close = |poll_id| ExecuteMsg::ClosePoll { poll_id };
+
+        // only draws take the fee
+        let paid = mock_info("addr1", &[coin(50, "unois")]);
+        let err = execute(deps.as_mut(), env.clone(), paid, close(1)).unwrap_err();
+        assert!(matches!(err, ContractError::Payment(_)));
+
+        // the first tie extends the poll, the second one rejects it
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), close(1)).unwrap();
+        assert_eq!(result.events[0].ty, "poll_extended");
+        let poll = polls().load(&deps.storage, 1).unwrap();
+        assert_eq!(poll.status, PollStatus::Open);
+        assert_eq!(poll.expiration, Expiration::AtHeight(env.block.height + 10));
+        execute(deps.as_mut(), env.clone(), info.clone(), close(1)).unwrap();
+        let poll = polls().load(&deps.storage, 1).unwrap();
+        assert_eq!(poll.status, PollStatus::Rejected);
+
+        // the closer pays for the draw
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), close(2)).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFunds { .. }));
+        let closer = mock_info("addr1", &[coin(50, "unois")]);
+        let result = execute(deps.as_mut(), env.clone(), closer, close(2)).unwrap();
+        assert_eq!(
+            result.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "nois".to_string(),
+                msg: to_binary(&NoisProxyExecuteMsg::GetNextRandomness {
+                    job_id: "tie-2".to_string(),
+                })
+                .unwrap(),
+                funds: vec![coin(50, "unois")],
+            })
+        );
+        let msg = ExecuteMsg::Vote {
+            poll_id: 2,
+            choice: Choice::Option(0),
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), mock_info("addr4", &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::TieDrawPending { poll_id: 2 }));
+
+        let msg = ExecuteMsg::NoisReceive {
+            callback: NoisCallback {
+                job_id: "tie-2".to_string(),
+                published: env.block.time,
+                randomness: HexBinary::from(vec![7; 32]),
+            },
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), env.clone(), mock_info("nois", &[]), msg).unwrap();
+        let poll = polls().load(&deps.storage, 2).unwrap();
+        assert_eq!(poll.status, PollStatus::Closed);
+        assert!(poll
+            .tie_winner
+            .is_some_and(|winner| poll.tied.contains(&winner)));
+
+        // only yes/no polls can favor yes
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Which chain do you love most".to_string(),
+            options: Some(vec!["juno".to_string(), "osmosis".to_string()]),
+            tie_break: TieBreak::FavorYes,
+            ..Default::default()
+        });
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidTieBreak {}));
+    }
+}
@@ -0,0 +1,352 @@
+//! Options added to open polls. On polls created with `allow_write_ins`,
+//! voters can vote for an option the poll does not list yet, which is appended
+//! on first use. On polls created with `allow_new_options`, the creator can
+//! append options with `AddOption`. Either way the usual option limits apply,
+//! and existing ballots keep pointing at the same options.
+
+use cosmwasm_std::{Binary, DepsMut, Env, Event, MessageInfo, Response, Uint128};
+use cw_utils::nonpayable;
+
+use crate::contract::{ensure_open, load_poll, vote, MAX_OPTION_LEN, MAX_POLL_OPTIONS};
+use crate::error::ContractError;
+use crate::msg::Choice;
+use crate::state::{polls, Poll, PollKind, PollOption, TieBreak};
+use crate::tally::is_binary;
+
+/// See `validate_new_options` for `threshold_set`.
+pub(crate) fn validate_write_ins(poll: &Poll, threshold_set: bool) -> Result<(), ContractError> {
+    if poll.kind != PollKind::SingleChoice
+        || poll.commit_reveal.is_some()
+        || poll.hub.is_some()
+        || has_fixed_outcome(poll, threshold_set)
+    {
+        return Err(ContractError::InvalidWriteIns {});
+    }
+    Ok(())
+}
+
+/// `threshold_set` is whether the creator gave the poll a threshold, rather
+/// than getting the configured default.
+pub(crate) fn validate_new_options(poll: &Poll, threshold_set: bool) -> Result<(), ContractError> {
+    let fixed_kind = matches!(
+        poll.kind,
+        PollKind::Petition { .. } | PollKind::Prediction { .. }
+    );
+    if fixed_kind || poll.hub.is_some() || has_fixed_outcome(poll, threshold_set) {
+        return Err(ContractError::InvalidNewOptions {});
+    }
+    Ok(())
+}
+
+/// Whether the poll's outcome is read from its options as created: yes/no
+/// polls pass or are rejected, and so do polls that dispatch messages, favor
+/// yes on ties or were given a pass threshold. An extra option would leave
+/// them unable to pass.
+fn has_fixed_outcome(poll: &Poll, threshold_set: bool) -> bool {
+    is_binary(poll)
+        || !poll.msgs.is_empty()
+        || poll.tie_break == TieBreak::FavorYes
+        || threshold_set
+}
+
+/// Votes for `label`, adding it to the poll's options first if it is new.
+pub fn execute_vote_write_in(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    label: String,
+    proof: Option<Vec<Binary>>,
+    memo: Option<String>,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    if !poll.allow_write_ins {
+        return Err(ContractError::WriteInsNotAllowed { poll_id });
+    }
+    ensure_open(&poll)?;
+    let existing = poll.options.iter().position(|option| option.label == label);
+    let (option, event) = match existing {
+        Some(option) => (option as u32, None),
+        None => {
+            let option = push_option(&mut poll, label)?;
+            polls().save(deps.storage, poll_id, &poll)?;
+            let event = Event::new("option_written_in")
+                .add_attribute("poll_id", poll_id.to_string())
+                .add_attribute("option", option.to_string())
+                .add_attribute("label", &poll.options[option as usize].label)
+                .add_attribute("voter", &info.sender);
+            (option, Some(event))
+        }
+    };
+
+    let response = vote(
+        deps,
+        &env,
+        &info.sender,
+        poll_id,
+        Choice::Option(option),
+        proof,
+        memo,
+    )?;
+    Ok(response.add_events(event))
+}
+
+pub fn execute_add_option(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    label: String,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    let mut poll = load_poll(deps.storage, poll_id)?;
+    if info.sender != poll.creator {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !poll.allow_new_options {
+        return Err(ContractError::NewOptionsNotAllowed { poll_id });
+    }
+    ensure_open(&poll)?;
+    if poll.expiration.is_expired(&env.block) {
+        return Err(ContractError::PollExpired {});
+    }
+    let option = push_option(&mut poll, label)?;
+    polls().save(deps.storage, poll_id, &poll)?;
+
+    let event = Event::new("option_added")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_attribute("option", option.to_string())
+        .add_attribute("label", &poll.options[option as usize].label);
+    Ok(Response::new()
+        .add_attribute("action", "add_option")
+        .add_attribute("poll_id", poll_id.to_string())
+        .add_event(event))
+}
+
+/// Appends an option with no votes to `poll` and returns its index.
+pub(crate) fn push_option(poll: &mut Poll, label: String) -> Result<u32, ContractError> {
+    if label.trim().is_empty()
+        || label.len() > MAX_OPTION_LEN
+        || poll.options.iter().any(|option| option.label == label)
+    {
+        return Err(ContractError::InvalidOption { label });
+    }
+    if poll.options.len() >= MAX_POLL_OPTIONS {
+        return Err(ContractError::TooManyOptions {
+            max: MAX_POLL_OPTIONS,
+        });
+    }
+    poll.options.push(PollOption {
+        label,
+        votes: Uint128::zero(),
+        description: None,
+        image: None,
+    });
+    Ok(poll.options.len() as u32 - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+    use cosmwasm_std::{coins, BankMsg, Decimal};
+    use cw_utils::Expiration;
+
+    use crate::contract::{execute, instantiate};
+    use crate::msg::{CreatePollMsg, ExecuteMsg, InstantiateMsg};
+
+    use super::*;
+
+    #[test]
+    fn test_write_ins() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let chains = CreatePollMsg {
+            question: "Which chain do you love".to_string(),
+            options: Some(vec!["juno".to_string(), "osmosis".to_string()]),
+            allow_write_ins: true,
+            ..Default::default()
+        };
+        let msg = ExecuteMsg::CreatePoll(chains.clone());
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // a write-in would keep yes/no polls and proposals from passing
+        let chains = CreatePollMsg {
+            question: "Which chain do you love most".to_string(),
+            ..chains
+        };
+        let invalid = [
+            CreatePollMsg {
+                kind: PollKind::Approval,
+                ..chains.clone()
+            },
+            CreatePollMsg {
+                question: "Do you love spark IBC".to_string(),
+                options: None,
+                ..chains.clone()
+            },
+            CreatePollMsg {
+                msgs: vec![BankMsg::Burn {
+                    amount: coins(1, "ujuno"),
+                }
+                .into()],
+                ..chains.clone()
+            },
+            CreatePollMsg {
+                threshold: Some(Decimal::percent(60)),
+                ..chains
+            },
+        ];
+        for msg in invalid {
+            let msg = ExecuteMsg::CreatePoll(msg);
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidWriteIns {}));
+        }
+
+        let write_in = |label: &str| ExecuteMsg::VoteWriteIn {
+            poll_id: 1,
+            label: label.to_string(),
+            proof: None,
+            memo: None,
+        };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), write_in(" ")).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOption { .. }));
+        let result = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            write_in("stargaze"),
+        )
+        .unwrap();
+        assert!(result
+            .events
+            .iter()
+            .any(|event| event.ty == "option_written_in"));
+        // the second write-in of a label votes for the same option
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr3", &[]),
+            write_in("stargaze"),
+        )
+        .unwrap();
+        let poll = polls().load(&deps.storage, 1).unwrap();
+        assert_eq!(poll.options.len(), 3);
+        assert_eq!(poll.options[2].votes, Uint128::new(2));
+
+        for option in 3..MAX_POLL_OPTIONS {
+            let voter = format!("voter{}", option);
+            let label = format!("chain{}", option);
+            execute(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(&voter, &[]),
+                write_in(&label),
+            )
+            .unwrap();
+        }
+        let err = execute(deps.as_mut(), env, info, write_in("cosmos")).unwrap_err();
+        assert!(matches!(
+            err,
+            ContractError::TooManyOptions {
+                max: MAX_POLL_OPTIONS
+            }
+        ));
+    }
+
+    #[test]
+    fn test_add_option() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        let info = mock_info("addr1", &[]);
+        let msg = InstantiateMsg {
+            admin_address: "addr1".to_string(),
+            ..Default::default()
+        };
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let msg = ExecuteMsg::CreatePoll(CreatePollMsg {
+            question: "Which chain do you love".to_string(),
+            options: Some(vec!["juno".to_string(), "osmosis".to_string()]),
+            kind: PollKind::RankedChoice,
+            expiration: Some(Expiration::AtHeight(env.block.height + 10)),
+            allow_new_options: true,
+            ..Default::default()
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // polls that pass or are rejected depend on the options they have
+        let chains = CreatePollMsg {
+            question: "Which chain do you love most".to_string(),
+            options: Some(vec!["juno".to_string(), "osmosis".to_string()]),
+            allow_new_options: true,
+            ..Default::default()
+        };
+        let yes_no = CreatePollMsg {
+            question: "Do you love spark IBC".to_string(),
+            options: None,
+            ..chains.clone()
+        };
+        let fixed = [
+            CreatePollMsg {
+                kind: PollKind::Prediction {
+                    denom: "ujuno".to_string(),
+                },
+                ..chains.clone()
+            },
+            yes_no.clone(),
+            CreatePollMsg {
+                tie_break: TieBreak::FavorYes,
+                ..yes_no
+            },
+            CreatePollMsg {
+                msgs: vec![BankMsg::Burn {
+                    amount: coins(1, "ujuno"),
+                }
+                .into()],
+                ..chains.clone()
+            },
+            CreatePollMsg {
+                threshold: Some(Decimal::percent(60)),
+                ..chains
+            },
+        ];
+        for msg in fixed {
+            let msg = ExecuteMsg::CreatePoll(msg);
+            let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+            assert!(matches!(err, ContractError::InvalidNewOptions {}));
+        }
+
+        let add = |label: &str| ExecuteMsg::AddOption {
+            poll_id: 1,
+            label: label.to_string(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("addr2", &[]),
+            add("stargaze"),
+        )
+        .unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), add("juno")).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidOption { .. }));
+        let result = execute(deps.as_mut(), env.clone(), info.clone(), add("stargaze")).unwrap();
+        let event = &result.events[0];
+        assert_eq!(event.ty, "option_added");
+        assert_eq!(event.attributes[1].value, "2");
+        let poll = polls().load(&deps.storage, 1).unwrap();
+        assert_eq!(poll.options.len(), 3);
+
+        env.block.height += 10;
+        let err = execute(deps.as_mut(), env, info, add("cosmos")).unwrap_err();
+        assert!(matches!(err, ContractError::PollExpired {}));
+    }
+}